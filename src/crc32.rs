@@ -1,5 +1,7 @@
 //! Helper module to compute a CRC32 checksum
 
+use std::error::Error;
+use std::fmt;
 use std::io;
 use std::io::prelude::*;
 
@@ -36,9 +38,28 @@ impl<R> Crc32Reader<R> {
     }
 }
 
+/// The error returned once the contents turn out not to match their checksum.
+#[derive(Debug)]
+struct InvalidChecksum;
+
+impl fmt::Display for InvalidChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid checksum")
+    }
+}
+
+impl Error for InvalidChecksum {}
+
 #[cold]
-fn invalid_checksum() -> io::Error {
-    io::Error::new(io::ErrorKind::InvalidData, "Invalid checksum")
+pub(crate) fn invalid_checksum() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, InvalidChecksum)
+}
+
+/// Whether `error` was returned by a [`Crc32Reader`] because the checksum didn't match.
+pub(crate) fn is_invalid_checksum(error: &io::Error) -> bool {
+    error
+        .get_ref()
+        .is_some_and(|inner| inner.is::<InvalidChecksum>())
 }
 
 impl<R: Read> Read for Crc32Reader<R> {
@@ -30,8 +30,12 @@
 #![warn(missing_docs)]
 #![allow(unexpected_cfgs)] // Needed for cfg(fuzzing) on nightly as of 2024-05-06
 pub use crate::compression::{CompressionMethod, SUPPORTED_COMPRESSION_METHODS};
+#[cfg(feature = "tokio")]
+pub use crate::read::AsyncZipArchive;
 pub use crate::read::ZipArchive;
 pub use crate::types::{AesMode, DateTime};
+#[cfg(feature = "tokio")]
+pub use crate::write::AsyncZipWriter;
 pub use crate::write::ZipWriter;
 
 #[cfg(feature = "aes-crypto")]
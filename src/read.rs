@@ -16,7 +16,6 @@ use crate::types::{
 use crate::zipcrypto::{ZipCryptoReader, ZipCryptoReaderValid, ZipCryptoValidator};
 use indexmap::IndexMap;
 use std::borrow::Cow;
-use std::ffi::OsString;
 use std::fs::create_dir_all;
 use std::io::{self, copy, prelude::*, sink, SeekFrom};
 use std::mem;
@@ -24,6 +23,7 @@ use std::mem::size_of;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, OnceLock};
 
 #[cfg(feature = "deflate-flate2")]
@@ -51,17 +51,65 @@ pub(crate) mod lzma;
 #[cfg(feature = "xz")]
 pub(crate) mod xz;
 
+mod data_descriptor;
+
+mod extract;
+
+pub use extract::{ExtractOptions, OverwritePolicy, SymlinkPolicy};
+
+mod lazy;
+
+pub use lazy::LazyZipArchive;
+
+mod limits;
+
+mod names;
+
+mod positional;
+
+pub use positional::ReadAt;
+
+mod seekable;
+
+pub use seekable::ZipFileSeek;
+
+mod slice_archive;
+
+pub use slice_archive::{ZipSliceArchive, ZipSliceReader};
+
+mod verify;
+
+use verify::{reject_overlapping_entries, Tolerated};
+pub use verify::{ArchiveProblem, EntryProblem, EntryReport, HeaderField, VerificationReport};
+
+mod volumes;
+
+pub use volumes::MultiVolumeReader;
+
+#[cfg(feature = "tokio")]
+mod async_archive;
+
+#[cfg(feature = "tokio")]
+pub use async_archive::{AsyncZipArchive, AsyncZipFile};
+
 #[cfg(feature = "parallelism")]
 pub(crate) mod pipelining;
+#[cfg(feature = "parallelism")]
+pub use pipelining::extraction::{
+    pool_extract, EntrySelector, ExtractedEntry, ExtractionMethod, ExtractionParameters,
+    ExtractionPassword, ExtractionReport, SplitExtractionError,
+};
 #[cfg(all(unix, feature = "parallelism"))]
-pub use pipelining::split_extraction::{split_extract, ExtractionParameters, SplitExtractionError};
+pub use pipelining::split_extraction::{split_extract, ExtractionSource, PositionalSource};
 #[cfg(feature = "parallelism")]
 pub(crate) mod split;
 
 // Put the struct declaration in a private module to convince rustdoc to display ZipArchive nicely
 pub(crate) mod zip_archive {
+    use super::names::NameIndex;
+    use super::verify::Tolerated;
     use indexmap::IndexMap;
-    use std::sync::Arc;
+    use std::sync::{Arc, OnceLock};
 
     /// Extract immutable data from `ZipArchive` to make it cheap to clone
     #[derive(Debug)]
@@ -72,6 +120,9 @@ pub(crate) mod zip_archive {
         // This isn't yet used anywhere, but it is here for use cases in the future.
         #[allow(dead_code)]
         pub(super) config: super::Config,
+        pub(super) limits: super::Limits,
+        pub(super) tolerated: Tolerated,
+        pub(super) name_index: OnceLock<NameIndex>,
     }
 
     #[derive(Debug)]
@@ -82,19 +133,28 @@ pub(crate) mod zip_archive {
         // This isn't yet used anywhere, but it is here for use cases in the future.
         #[allow(dead_code)]
         pub(super) config: super::Config,
+        pub(super) limits: super::Limits,
+        pub(super) tolerated: Tolerated,
     }
 
     impl SharedBuilder {
         pub fn build(self) -> Shared {
             let mut index_map = IndexMap::with_capacity(self.files.len());
+            let mut tolerated = self.tolerated;
             self.files.into_iter().for_each(|file| {
-                index_map.insert(file.file_name.clone(), file);
+                let name = file.file_name.clone();
+                if let Some(shadowed) = index_map.insert(name, file) {
+                    tolerated.duplicate_names.push(shadowed.file_name);
+                }
             });
             Shared {
                 files: index_map,
                 offset: self.offset,
                 dir_start: self.dir_start,
                 config: self.config,
+                limits: self.limits,
+                tolerated,
+                name_index: OnceLock::new(),
             }
         }
     }
@@ -138,20 +198,39 @@ use crate::result::ZipError::{InvalidArchive, InvalidPassword, UnsupportedArchiv
 use crate::spec::is_dir;
 use crate::types::ffi::S_IFLNK;
 use crate::unstable::{path_to_string, LittleEndianReadExt};
+use data_descriptor::{has_zip64_extra_field, DataDescriptorReader, StreamSource};
 pub use zip_archive::ZipArchive;
 
+/// Where the compressed data of an entry is read from, through a reader of type `R`.
+pub(crate) enum EntrySource<'a, R: Read + ?Sized = dyn Read + 'a> {
+    /// A reader that has been moved to the start of the data, and that the entry borrows.
+    Reader(&'a mut R),
+    /// A reader of the entry's own, such as one making positional reads from a reader that other
+    /// entries may be reading at the same time.
+    Owned(Box<R>),
+}
+
+impl<R: Read + ?Sized> Read for EntrySource<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            EntrySource::Reader(r) => r.read(buf),
+            EntrySource::Owned(r) => r.read(buf),
+        }
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
-pub(crate) enum CryptoReader<'a> {
-    Plaintext(io::Take<&'a mut dyn Read>),
-    ZipCrypto(ZipCryptoReaderValid<io::Take<&'a mut dyn Read>>),
+pub(crate) enum CryptoReader<'a, R: Read + ?Sized = dyn Read + 'a> {
+    Plaintext(io::Take<EntrySource<'a, R>>),
+    ZipCrypto(ZipCryptoReaderValid<io::Take<EntrySource<'a, R>>>),
     #[cfg(feature = "aes-crypto")]
     Aes {
-        reader: AesReaderValid<io::Take<&'a mut dyn Read>>,
+        reader: AesReaderValid<io::Take<EntrySource<'a, R>>>,
         vendor_version: AesVendorVersion,
     },
 }
 
-impl<'a> Read for CryptoReader<'a> {
+impl<R: Read + ?Sized> Read for CryptoReader<'_, R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         match self {
             CryptoReader::Plaintext(r) => r.read(buf),
@@ -180,9 +259,9 @@ impl<'a> Read for CryptoReader<'a> {
     }
 }
 
-impl<'a> CryptoReader<'a> {
+impl<'a, R: Read + ?Sized> CryptoReader<'a, R> {
     /// Consumes this decoder, returning the underlying reader.
-    pub fn into_inner(self) -> io::Take<&'a mut dyn Read> {
+    pub fn into_inner(self) -> io::Take<EntrySource<'a, R>> {
         match self {
             CryptoReader::Plaintext(r) => r,
             CryptoReader::ZipCrypto(r) => r.into_inner(),
@@ -206,25 +285,28 @@ impl<'a> CryptoReader<'a> {
     }
 }
 
-pub(crate) enum ZipFileReader<'a> {
+pub(crate) enum ZipFileReader<'a, R: Read + ?Sized = dyn Read + 'a> {
     NoReader,
-    Raw(io::Take<&'a mut dyn Read>),
-    Stored(Crc32Reader<CryptoReader<'a>>),
+    Raw(io::Take<EntrySource<'a, R>>),
+    Stored(Crc32Reader<CryptoReader<'a, R>>),
     #[cfg(feature = "_deflate-any")]
-    Deflated(Crc32Reader<DeflateDecoder<CryptoReader<'a>>>),
+    Deflated(Crc32Reader<DeflateDecoder<CryptoReader<'a, R>>>),
     #[cfg(feature = "deflate64")]
-    Deflate64(Crc32Reader<Deflate64Decoder<io::BufReader<CryptoReader<'a>>>>),
+    Deflate64(Crc32Reader<Deflate64Decoder<io::BufReader<CryptoReader<'a, R>>>>),
     #[cfg(feature = "bzip2")]
-    Bzip2(Crc32Reader<BzDecoder<CryptoReader<'a>>>),
+    Bzip2(Crc32Reader<BzDecoder<CryptoReader<'a, R>>>),
     #[cfg(feature = "zstd")]
-    Zstd(Crc32Reader<ZstdDecoder<'a, io::BufReader<CryptoReader<'a>>>>),
+    Zstd(Crc32Reader<ZstdDecoder<'a, io::BufReader<CryptoReader<'a, R>>>>),
     #[cfg(feature = "lzma")]
-    Lzma(Crc32Reader<Box<LzmaDecoder<CryptoReader<'a>>>>),
+    Lzma(Crc32Reader<Box<LzmaDecoder<CryptoReader<'a, R>>>>),
     #[cfg(feature = "xz")]
-    Xz(Crc32Reader<XzDecoder<CryptoReader<'a>>>),
+    Xz(Crc32Reader<XzDecoder<io::BufReader<CryptoReader<'a, R>>>>),
+    /// An entry read from a stream, whose sizes follow its data, and the compressed bytes it has
+    /// consumed so far.
+    DataDescriptor(Box<R>, Arc<AtomicU64>),
 }
 
-impl<'a> Read for ZipFileReader<'a> {
+impl<R: Read + ?Sized> Read for ZipFileReader<'_, R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         match self {
             ZipFileReader::NoReader => panic!("ZipFileReader was in an invalid state"),
@@ -242,6 +324,7 @@ impl<'a> Read for ZipFileReader<'a> {
             ZipFileReader::Lzma(r) => r.read(buf),
             #[cfg(feature = "xz")]
             ZipFileReader::Xz(r) => r.read(buf),
+            ZipFileReader::DataDescriptor(r, _) => r.read(buf),
         }
     }
 
@@ -262,6 +345,7 @@ impl<'a> Read for ZipFileReader<'a> {
             ZipFileReader::Lzma(r) => r.read_exact(buf),
             #[cfg(feature = "xz")]
             ZipFileReader::Xz(r) => r.read_exact(buf),
+            ZipFileReader::DataDescriptor(r, _) => r.read_exact(buf),
         }
     }
 
@@ -282,6 +366,7 @@ impl<'a> Read for ZipFileReader<'a> {
             ZipFileReader::Lzma(r) => r.read_to_end(buf),
             #[cfg(feature = "xz")]
             ZipFileReader::Xz(r) => r.read_to_end(buf),
+            ZipFileReader::DataDescriptor(r, _) => r.read_to_end(buf),
         }
     }
 
@@ -302,11 +387,12 @@ impl<'a> Read for ZipFileReader<'a> {
             ZipFileReader::Lzma(r) => r.read_to_string(buf),
             #[cfg(feature = "xz")]
             ZipFileReader::Xz(r) => r.read_to_string(buf),
+            ZipFileReader::DataDescriptor(r, _) => r.read_to_string(buf),
         }
     }
 }
 
-impl<'a> ZipFileReader<'a> {
+impl<R: Read + ?Sized> ZipFileReader<'_, R> {
     /// Consumes this decoder, returning the underlying reader.
     pub fn drain(self) {
         let mut inner = match self {
@@ -331,23 +417,32 @@ impl<'a> ZipFileReader<'a> {
                 return;
             }
             #[cfg(feature = "xz")]
-            ZipFileReader::Xz(r) => r.into_inner().into_inner().into_inner(),
+            ZipFileReader::Xz(r) => r.into_inner().into_inner().into_inner().into_inner(),
+            ZipFileReader::DataDescriptor(mut r, _) => {
+                // The end of the data can only be found by decoding it, which also consumes the
+                // descriptor after it.
+                let _ = copy(&mut r, &mut sink());
+                return;
+            }
         };
         let _ = copy(&mut inner, &mut sink());
     }
 }
 
 /// A struct for reading a zip file
-pub struct ZipFile<'a> {
+///
+/// Its compressed data is read through `R`, which is only something other than `dyn Read` for
+/// the files of an `AsyncZipArchive`, whose readers are `Send`.
+pub struct ZipFile<'a, R: Read + ?Sized = dyn Read + 'a> {
     pub(crate) data: Cow<'a, ZipFileData>,
-    pub(crate) crypto_reader: Option<CryptoReader<'a>>,
-    pub(crate) reader: ZipFileReader<'a>,
+    pub(crate) crypto_reader: Option<CryptoReader<'a, R>>,
+    pub(crate) reader: ZipFileReader<'a, R>,
 }
 
 pub(crate) fn find_content<'a>(
     data: &ZipFileData,
     reader: &'a mut (impl Read + Seek),
-) -> ZipResult<io::Take<&'a mut dyn Read>> {
+) -> ZipResult<io::Take<EntrySource<'a>>> {
     // TODO: use .get_or_try_init() once stabilized to provide a closure returning a Result!
     let data_start = match data.data_start.get() {
         Some(data_start) => *data_start,
@@ -355,7 +450,7 @@ pub(crate) fn find_content<'a>(
     };
 
     reader.seek(io::SeekFrom::Start(data_start))?;
-    Ok((reader as &mut dyn Read).take(data.compressed_size))
+    Ok(EntrySource::Reader(reader as &'a mut dyn Read).take(data.compressed_size))
 }
 
 fn find_data_start(
@@ -367,7 +462,11 @@ fn find_data_start(
 
     // Parse static-sized fields and check the magic value.
     let block = ZipLocalEntryBlock::parse(reader)?;
+    Ok(data_start_from_local_block(data, &block))
+}
 
+/// Records where an entry's data starts, given its local header.
+pub(crate) fn data_start_from_local_block(data: &ZipFileData, block: &ZipLocalEntryBlock) -> u64 {
     // Calculate the end of the local header from the fields we just parsed.
     let variable_fields_len =
         // Each of these fields must be converted to u64 before adding, as the result may
@@ -384,20 +483,68 @@ fn find_data_start(
             debug_assert_eq!(*data.data_start.get().unwrap(), data_start);
         }
     }
-    Ok(data_start)
+    data_start
+}
+
+/// Replaces the error from the last candidate for the central directory to be tried, unless a limit
+/// was exceeded, which would apply to any of them.
+pub(crate) fn no_central_directory(error: ZipError) -> ZipError {
+    match error {
+        ZipError::LimitExceeded(_) => error,
+        _ => InvalidArchive("No valid central directory found"),
+    }
+}
+
+/// Checks that a password is given for an encrypted entry, and discards one given for any other.
+pub(crate) fn password_for<'p>(
+    data: &ZipFileData,
+    password: Option<&'p [u8]>,
+) -> ZipResult<Option<&'p [u8]>> {
+    match (password, data.encrypted) {
+        (None, true) => Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED)),
+        (Some(_), false) => Ok(None), //Password supplied, but none needed! Discard.
+        _ => Ok(password),
+    }
+}
+
+/// Opens an entry whose compressed data is read from `limit_reader`, decrypting it if `password`
+/// is given. Xz entries are refused if `limits` don't allow them to be decompressed in memory.
+pub(crate) fn open_entry<'a, R: Read + ?Sized>(
+    data: &'a ZipFileData,
+    limit_reader: io::Take<EntrySource<'a, R>>,
+    password: Option<&[u8]>,
+    limits: &Limits,
+) -> ZipResult<ZipFile<'a, R>> {
+    limits.check_in_memory_entry(data, false)?;
+    let crypto_reader = make_crypto_reader(
+        data.compression_method,
+        data.crc32,
+        data.last_modified_time,
+        data.using_data_descriptor,
+        limit_reader,
+        password,
+        data.aes_mode,
+        #[cfg(feature = "aes-crypto")]
+        data.compressed_size,
+    )?;
+    Ok(ZipFile {
+        crypto_reader: Some(crypto_reader),
+        reader: ZipFileReader::NoReader,
+        data: Cow::Borrowed(data),
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
-pub(crate) fn make_crypto_reader<'a>(
+pub(crate) fn make_crypto_reader<'a, R: Read + ?Sized>(
     compression_method: CompressionMethod,
     crc32: u32,
     mut last_modified_time: Option<DateTime>,
     using_data_descriptor: bool,
-    reader: io::Take<&'a mut dyn Read>,
+    reader: io::Take<EntrySource<'a, R>>,
     password: Option<&[u8]>,
     aes_info: Option<(AesMode, AesVendorVersion, CompressionMethod)>,
     #[cfg(feature = "aes-crypto")] compressed_size: u64,
-) -> ZipResult<CryptoReader<'a>> {
+) -> ZipResult<CryptoReader<'a, R>> {
     #[allow(deprecated)]
     {
         if let CompressionMethod::Unsupported(_) = compression_method {
@@ -434,11 +581,13 @@ pub(crate) fn make_crypto_reader<'a>(
     Ok(reader)
 }
 
-pub(crate) fn make_reader(
+/// `uncompressed_size` bounds what's decompressed into memory at once, for methods that have to.
+pub(crate) fn make_reader<R: Read + ?Sized>(
     compression_method: CompressionMethod,
     crc32: u32,
-    reader: CryptoReader,
-) -> ZipResult<ZipFileReader> {
+    #[cfg_attr(not(feature = "xz"), allow(unused_variables))] uncompressed_size: u64,
+    reader: CryptoReader<'_, R>,
+) -> ZipResult<ZipFileReader<'_, R>> {
     let ae2_encrypted = reader.is_ae2_encrypted();
 
     match compression_method {
@@ -494,7 +643,7 @@ pub(crate) fn make_reader(
         }
         #[cfg(feature = "xz")]
         CompressionMethod::Xz => {
-            let reader = XzDecoder::new(reader);
+            let reader = XzDecoder::new(io::BufReader::new(reader), uncompressed_size);
             Ok(ZipFileReader::Xz(Crc32Reader::new(
                 reader,
                 crc32,
@@ -508,7 +657,11 @@ pub(crate) fn make_reader(
 #[derive(Debug)]
 pub(crate) struct CentralDirectoryInfo {
     pub(crate) archive_offset: u64,
+    /// An offset implied by the end record that was ignored, since there's no central directory
+    /// there.
+    pub(crate) ignored_archive_offset: Option<u64>,
     pub(crate) directory_start: u64,
+    pub(crate) directory_size: u64,
     pub(crate) cde_position: u64,
     pub(crate) number_of_files: usize,
     pub(crate) disk_number: u32,
@@ -534,6 +687,9 @@ impl<R> ZipArchive<R> {
             config: Config {
                 archive_offset: ArchiveOffset::Known(initial_offset),
             },
+            limits: Limits::default(),
+            tolerated: Default::default(),
+            name_index: OnceLock::new(),
         });
         Ok(Self {
             reader,
@@ -556,6 +712,59 @@ impl<R> ZipArchive<R> {
     }
 }
 
+impl<R: Read + Seek> ZipArchive<MultiVolumeReader<R>> {
+    /// Read a ZIP archive split into several volumes, such as `archive.z01`, `archive.z02`, ...,
+    /// `archive.zip`, which must be given in that order.
+    ///
+    /// Each entry is read from the volume named by its central directory header, at an offset
+    /// relative to the start of that volume; a spanning signature at the start of the first volume
+    /// is part of that volume. Archives that were split by simply cutting them into pieces, whose
+    /// disk numbers are all 0, are read as if the volumes were concatenated.
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// fn open_volumes() -> zip::result::ZipResult<()> {
+    ///     let volumes = ["archive.z01", "archive.z02", "archive.zip"]
+    ///         .into_iter()
+    ///         .map(File::open)
+    ///         .collect::<Result<Vec<_>, _>>()?;
+    ///     let mut zip = zip::ZipArchive::open_split(volumes)?;
+    ///     println!("{} files", zip.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn open_split(volumes: Vec<R>) -> ZipResult<Self> {
+        Self::open_split_with_config(Config::default(), volumes)
+    }
+
+    /// Read a ZIP archive split into several volumes, as [`ZipArchive::open_split`] does, with the
+    /// given read configuration.
+    pub fn open_split_with_config(config: Config, volumes: Vec<R>) -> ZipResult<Self> {
+        Self::open_split_with_limits(config, Limits::default(), volumes)
+    }
+
+    /// Read a ZIP archive split into several volumes, as [`ZipArchive::open_split`] does, with the
+    /// given read configuration and [`Limits`], which are checked as in
+    /// [`ZipArchive::with_limits`].
+    pub fn open_split_with_limits(
+        config: Config,
+        limits: Limits,
+        volumes: Vec<R>,
+    ) -> ZipResult<Self> {
+        let mut reader = MultiVolumeReader::new(volumes)?;
+        let volume_starts = reader.volume_starts().to_vec();
+        let (footer, shared) = Self::get_metadata(config, limits, &mut reader, &volume_starts)?;
+        if limits.reject_overlapping_entries {
+            reject_overlapping_entries(&shared, &mut reader)?;
+        }
+        Ok(ZipArchive {
+            reader,
+            shared: shared.into(),
+            comment: footer.zip_file_comment.into(),
+        })
+    }
+}
+
 impl<R: Read + Seek> ZipArchive<R> {
     pub(crate) fn merge_contents<W: Write + io::Seek>(
         &mut self,
@@ -579,6 +788,8 @@ impl<R: Read + Seek> ZipArchive<R> {
             f.header_start = f.header_start.checked_add(new_initial_header_start).ok_or(
                 ZipError::InvalidArchive("new header start from merge would have been too large"),
             )?;
+            /* Entries from a split archive end up in a single volume. */
+            f.disk_number = 0;
             /* This is only ever used internally to cache metadata lookups (it's not part of the
              * zip spec), and 0 is the sentinel value. */
             // f.central_header_start = 0;
@@ -623,7 +834,21 @@ impl<R: Read + Seek> ZipArchive<R> {
         reader: &mut R,
         footer: &spec::Zip32CentralDirectoryEnd,
         cde_start_pos: u64,
+        volume_starts: &[u64],
     ) -> ZipResult<CentralDirectoryInfo> {
+        if is_split(footer.disk_number.into(), volume_starts) {
+            return Self::get_directory_info_split(
+                footer.disk_number.into(),
+                footer.disk_with_central_directory.into(),
+                footer.central_directory_offset.into(),
+                footer.central_directory_size.into(),
+                footer.number_of_files.into(),
+                cde_start_pos,
+                false,
+                volume_starts,
+            );
+        }
+        let mut ignored_archive_offset = None;
         let archive_offset = match config.archive_offset {
             ArchiveOffset::Known(n) => n,
             ArchiveOffset::FromCentralDirectory | ArchiveOffset::Detect => {
@@ -650,6 +875,7 @@ impl<R: Read + Seek> ZipArchive<R> {
                     if spec::Magic::from_le_bytes(buf)
                         != spec::Magic::CENTRAL_DIRECTORY_HEADER_SIGNATURE
                     {
+                        ignored_archive_offset = Some(offset).filter(|&offset| offset != 0);
                         offset = 0;
                     }
                 }
@@ -662,7 +888,9 @@ impl<R: Read + Seek> ZipArchive<R> {
         let number_of_files = footer.number_of_files_on_this_disk as usize;
         Ok(CentralDirectoryInfo {
             archive_offset,
+            ignored_archive_offset,
             directory_start,
+            directory_size: footer.central_directory_size.into(),
             number_of_files,
             disk_number: footer.disk_number as u32,
             disk_with_central_directory: footer.disk_with_central_directory as u32,
@@ -684,11 +912,53 @@ impl<R: Read + Seek> ZipArchive<R> {
         }
     }
 
+    /// Locates the central directory of an archive whose offsets are relative to the start of the
+    /// volume they refer to.
+    #[allow(clippy::too_many_arguments)]
+    fn get_directory_info_split(
+        disk_number: u32,
+        disk_with_central_directory: u32,
+        central_directory_offset: u64,
+        central_directory_size: u64,
+        number_of_files: u64,
+        cde_start_pos: u64,
+        is_zip64: bool,
+        volume_starts: &[u64],
+    ) -> ZipResult<CentralDirectoryInfo> {
+        if disk_number as usize != volume_starts.len() - 1 {
+            return Err(InvalidArchive(
+                "Number of volumes doesn't match the end of central directory record",
+            ));
+        }
+        if disk_with_central_directory > disk_number {
+            return Err(InvalidArchive(
+                "Central directory starts on a volume after the last one",
+            ));
+        }
+        let directory_start = volume_starts[disk_with_central_directory as usize]
+            .checked_add(central_directory_offset)
+            .filter(|&start| start <= cde_start_pos)
+            .ok_or(InvalidArchive("Invalid central directory size or offset"))?;
+        Ok(CentralDirectoryInfo {
+            // Entries are located by their own volume's start instead.
+            archive_offset: 0,
+            ignored_archive_offset: None,
+            directory_start,
+            directory_size: central_directory_size,
+            number_of_files: number_of_files as usize,
+            disk_number,
+            disk_with_central_directory,
+            cde_position: cde_start_pos,
+            is_zip64,
+        })
+    }
+
     fn get_directory_info_zip64(
         config: &Config,
         reader: &mut R,
         footer: &spec::Zip32CentralDirectoryEnd,
         cde_start_pos: u64,
+        volume_starts: &[u64],
     ) -> ZipResult<Vec<ZipResult<CentralDirectoryInfo>>> {
         // See if there's a ZIP64 footer. The ZIP64 locator if present will
         // have its signature 20 bytes in front of the standard footer. The
@@ -700,6 +970,28 @@ impl<R: Read + Seek> ZipArchive<R> {
         ))?;
         let locator64 = spec::Zip64CentralDirectoryEndLocator::parse(reader)?;
 
+        if is_split(locator64.number_of_disks.saturating_sub(1), volume_starts) {
+            // The ZIP64 end of central directory record is at a known offset into its volume.
+            let footer64_start = volume_starts
+                .get(locator64.disk_with_central_directory as usize)
+                .and_then(|start| start.checked_add(locator64.end_of_central_directory_offset))
+                .ok_or(InvalidArchive(
+                    "ZIP64 end of central directory record is on a volume that wasn't provided",
+                ))?;
+            reader.seek(io::SeekFrom::Start(footer64_start))?;
+            let footer64 = spec::Zip64CentralDirectoryEnd::parse(reader)?;
+            return Ok(vec![Self::get_directory_info_split(
+                footer64.disk_number,
+                footer64.disk_with_central_directory,
+                footer64.central_directory_offset,
+                footer64.central_directory_size,
+                footer64.number_of_files,
+                cde_start_pos,
+                true,
+                volume_starts,
+            )]);
+        }
+
         // We need to reassess `archive_offset`. We know where the ZIP64
         // central-directory-end structure *should* be, but unfortunately we
         // don't know how to precisely relate that location to our current
@@ -723,6 +1015,7 @@ impl<R: Read + Seek> ZipArchive<R> {
         let search_results = spec::Zip64CentralDirectoryEnd::find_and_parse(reader, lower, upper)?;
         let results: Vec<ZipResult<CentralDirectoryInfo>> =
             search_results.into_iter().map(|(footer64, archive_offset)| {
+                let mut ignored_archive_offset = None;
                 let archive_offset = match config.archive_offset {
                     ArchiveOffset::Known(n) => n,
                     ArchiveOffset::FromCentralDirectory => archive_offset,
@@ -741,7 +1034,10 @@ impl<R: Read + Seek> ZipArchive<R> {
                                     Some(archive_offset)
                                 }
                             })
-                        .unwrap_or(0)
+                        .unwrap_or_else(|| {
+                            ignored_archive_offset = Some(archive_offset).filter(|&offset| offset != 0);
+                            0
+                        })
                     }
                 };
                 let directory_start = footer64
@@ -766,7 +1062,9 @@ impl<R: Read + Seek> ZipArchive<R> {
                 } else {
                     Ok(CentralDirectoryInfo {
                         archive_offset,
+                        ignored_archive_offset,
                         directory_start,
+                        directory_size: footer64.central_directory_size,
                         number_of_files: footer64.number_of_files as usize,
                         disk_number: footer64.disk_number,
                         disk_with_central_directory: footer64.disk_with_central_directory,
@@ -780,22 +1078,62 @@ impl<R: Read + Seek> ZipArchive<R> {
 
     /// Get the directory start offset and number of files. This is done in a
     /// separate function to ease the control flow design.
+    ///
+    /// `volume_starts` gives the offset of each volume in `reader` for a split archive; it's empty
+    /// otherwise.
     pub(crate) fn get_metadata(
         config: Config,
+        limits: Limits,
         reader: &mut R,
+        volume_starts: &[u64],
     ) -> ZipResult<(Zip32CentralDirectoryEnd, Shared)> {
+        let cde_locations = spec::Zip32CentralDirectoryEnd::find_and_parse(reader)?;
+        let mut rejected_ends = Vec::new();
+        let (footer, mut shared) = Self::select_central_directory(
+            config,
+            reader,
+            volume_starts,
+            cde_locations,
+            |dir_info, reader| {
+                Self::read_central_header(dir_info, config, limits, reader, volume_starts, true)
+            },
+            |shared| shared.files.len(),
+            &mut rejected_ends,
+        )?;
+        shared.tolerated.rejected_ends = rejected_ends;
+        reader.seek(io::SeekFrom::Start(shared.dir_start))?;
+        Ok((footer, shared.build()))
+    }
+
+    /// Tries the central directories that the end records in `cde_locations` point to, starting
+    /// with the last one, and returns the first that `read` accepts with as many entries as its
+    /// end record claims. Where the end records tried before it start, and why they were
+    /// rejected, go in `rejected_ends`.
+    pub(crate) fn select_central_directory<T>(
+        config: Config,
+        reader: &mut R,
+        volume_starts: &[u64],
+        cde_locations: Box<[(Rc<Zip32CentralDirectoryEnd>, u64)]>,
+        mut read: impl FnMut(CentralDirectoryInfo, &mut R) -> ZipResult<T>,
+        len: impl Fn(&T) -> usize,
+        rejected_ends: &mut Vec<(u64, String)>,
+    ) -> ZipResult<(Zip32CentralDirectoryEnd, T)> {
         let mut invalid_errors_32 = Vec::new();
         let mut unsupported_errors_32 = Vec::new();
         let mut invalid_errors_64 = Vec::new();
         let mut unsupported_errors_64 = Vec::new();
         let mut ok_results = Vec::new();
-        let cde_locations = spec::Zip32CentralDirectoryEnd::find_and_parse(reader)?;
         cde_locations
             .into_vec()
             .into_iter()
             .for_each(|(footer, cde_start_pos)| {
-                let zip32_result =
-                    Self::get_directory_info_zip32(&config, reader, &footer, cde_start_pos);
+                let zip32_result = Self::get_directory_info_zip32(
+                    &config,
+                    reader,
+                    &footer,
+                    cde_start_pos,
+                    volume_starts,
+                );
                 Self::sort_result(
                     zip32_result,
                     &mut invalid_errors_32,
@@ -805,8 +1143,13 @@ impl<R: Read + Seek> ZipArchive<R> {
                 );
                 let mut inner_results = Vec::with_capacity(1);
                 // Check if file has a zip64 footer
-                let zip64_vec_result =
-                    Self::get_directory_info_zip64(&config, reader, &footer, cde_start_pos);
+                let zip64_vec_result = Self::get_directory_info_zip64(
+                    &config,
+                    reader,
+                    &footer,
+                    cde_start_pos,
+                    volume_starts,
+                );
                 Self::sort_result(
                     zip64_vec_result,
                     &mut invalid_errors_64,
@@ -834,40 +1177,33 @@ impl<R: Read + Seek> ZipArchive<R> {
         });
         let mut best_result = None;
         for (footer, result) in ok_results {
-            let mut inner_result = Vec::with_capacity(1);
             let is_zip64 = result.is_zip64;
-            Self::sort_result(
-                Self::read_central_header(result, config, reader),
-                if is_zip64 {
-                    &mut invalid_errors_64
-                } else {
-                    &mut invalid_errors_32
-                },
-                if is_zip64 {
-                    &mut unsupported_errors_64
-                } else {
-                    &mut unsupported_errors_32
-                },
-                &mut inner_result,
-                &(),
-            );
-            if let Some((_, shared)) = inner_result.into_iter().next() {
-                if shared.files.len() == footer.number_of_files as usize
-                    || (is_zip64 && footer.number_of_files == ZIP64_ENTRY_THR as u16)
+            let cde_position = result.cde_position;
+            let (invalid_errors, unsupported_errors) = if is_zip64 {
+                (&mut invalid_errors_64, &mut unsupported_errors_64)
+            } else {
+                (&mut invalid_errors_32, &mut unsupported_errors_32)
+            };
+            let error = match read(result, reader) {
+                Ok(directory)
+                    if len(&directory) == footer.number_of_files as usize
+                        || (is_zip64 && footer.number_of_files == ZIP64_ENTRY_THR as u16) =>
                 {
-                    best_result = Some((footer, shared));
+                    best_result = Some((footer, directory));
                     break;
-                } else {
-                    if is_zip64 {
-                        &mut invalid_errors_64
-                    } else {
-                        &mut invalid_errors_32
-                    }
-                    .push(InvalidArchive("wrong number of files"))
                 }
+                Ok(_) => InvalidArchive("wrong number of files"),
+                Err(e @ ZipError::LimitExceeded(_)) => return Err(e),
+                Err(e) => e,
+            };
+            // Kept for ZipArchive::verify, in case a later candidate is used instead.
+            rejected_ends.push((cde_position, error.to_string()));
+            match error {
+                ZipError::UnsupportedArchive(_) => unsupported_errors.push(error),
+                _ => invalid_errors.push(error),
             }
         }
-        let Some((footer, shared)) = best_result else {
+        let Some((footer, directory)) = best_result else {
             return Err(unsupported_errors_32
                 .into_iter()
                 .chain(unsupported_errors_64)
@@ -876,14 +1212,18 @@ impl<R: Read + Seek> ZipArchive<R> {
                 .next()
                 .unwrap());
         };
-        reader.seek(io::SeekFrom::Start(shared.dir_start))?;
-        Ok((Rc::try_unwrap(footer).unwrap(), shared.build()))
+        Ok((Rc::try_unwrap(footer).unwrap(), directory))
     }
 
-    fn read_central_header(
+    /// Parses the central directory that `dir_info` describes. Unless `read_local_headers` is set,
+    /// each entry's local header is left to be read when it's opened.
+    pub(crate) fn read_central_header(
         dir_info: CentralDirectoryInfo,
         config: Config,
+        limits: Limits,
         reader: &mut R,
+        volume_starts: &[u64],
+        read_local_headers: bool,
     ) -> Result<SharedBuilder, ZipError> {
         // If the parsed number of files is greater than the offset then
         // something fishy is going on and we shouldn't trust number_of_files.
@@ -892,13 +1232,24 @@ impl<R: Read + Seek> ZipArchive<R> {
         } else {
             dir_info.number_of_files
         };
-        if dir_info.disk_number != dir_info.disk_with_central_directory {
-            return unsupported_zip_error("Support for multi-disk files is not implemented");
-        }
+        let volume_starts = if is_split(dir_info.disk_number, volume_starts) {
+            volume_starts
+        } else if dir_info.disk_number != dir_info.disk_with_central_directory {
+            return unsupported_zip_error(
+                "Multi-disk files can only be read with ZipArchive::open_split",
+            );
+        } else {
+            &[]
+        };
+        limits.check_entry_count(dir_info.number_of_files)?;
         let mut files = Vec::with_capacity(file_capacity);
         reader.seek(io::SeekFrom::Start(dir_info.directory_start))?;
         for _ in 0..dir_info.number_of_files {
-            let file = central_header_to_zip_file(reader, dir_info.archive_offset)?;
+            let file = if read_local_headers {
+                central_header_to_zip_file(reader, dir_info.archive_offset, volume_starts)?
+            } else {
+                central_header_to_zip_file_lazily(reader, dir_info.archive_offset)?
+            };
             files.push(file);
         }
         Ok(SharedBuilder {
@@ -906,6 +1257,12 @@ impl<R: Read + Seek> ZipArchive<R> {
             offset: dir_info.archive_offset,
             dir_start: dir_info.directory_start,
             config,
+            limits,
+            tolerated: Tolerated {
+                directory_size: Some(dir_info.directory_size),
+                ignored_archive_offset: dir_info.ignored_archive_offset,
+                ..Default::default()
+            },
         })
     }
 
@@ -972,113 +1329,29 @@ impl<R: Read + Seek> ZipArchive<R> {
     /// Read a ZIP archive providing a read configuration, collecting the files it contains.
     ///
     /// This uses the central directory record of the ZIP file, and ignores local file headers.
-    pub fn with_config(config: Config, mut reader: R) -> ZipResult<ZipArchive<R>> {
-        reader.seek(SeekFrom::Start(0))?;
-        if let Ok((footer, shared)) = Self::get_metadata(config, &mut reader) {
-            return Ok(ZipArchive {
-                reader,
-                shared: shared.into(),
-                comment: footer.zip_file_comment.into(),
-            });
-        }
-        Err(InvalidArchive("No valid central directory found"))
+    pub fn with_config(config: Config, reader: R) -> ZipResult<ZipArchive<R>> {
+        Self::with_limits(config, Limits::default(), reader)
     }
 
-    /// Extract a Zip archive into a directory, overwriting files if they
-    /// already exist. Paths are sanitized with [`ZipFile::enclosed_name`].
+    /// Read a ZIP archive from an untrusted source, with a read configuration and [`Limits`] on
+    /// what it may contain.
     ///
-    /// Extraction is not atomic. If an error is encountered, some of the files
-    /// may be left on disk. However, on Unix targets, no newly-created directories with part but
-    /// not all of their contents extracted will be readable, writable or usable as process working
-    /// directories by any non-root user except you.
-    ///
-    /// On Unix and Windows, symbolic links are extracted correctly. On other platforms such as
-    /// WebAssembly, symbolic links aren't supported, so they're extracted as normal files
-    /// containing the target path in UTF-8.
-    pub fn extract<P: AsRef<Path>>(&mut self, directory: P) -> ZipResult<()> {
-        use std::fs;
-        #[cfg(unix)]
-        let mut files_by_unix_mode = Vec::new();
-        for i in 0..self.len() {
-            let mut file = self.by_index(i)?;
-            let filepath = file
-                .enclosed_name()
-                .ok_or(ZipError::InvalidArchive("Invalid file path"))?;
-
-            let outpath = directory.as_ref().join(filepath);
-
-            if file.is_dir() {
-                Self::make_writable_dir_all(&outpath)?;
-                continue;
-            }
-            let symlink_target = if file.is_symlink() && (cfg!(unix) || cfg!(windows)) {
-                let mut target = Vec::with_capacity(file.size() as usize);
-                file.read_exact(&mut target)?;
-                Some(target)
-            } else {
-                None
-            };
-            drop(file);
-            if let Some(p) = outpath.parent() {
-                Self::make_writable_dir_all(p)?;
-            }
-            if let Some(target) = symlink_target {
-                #[cfg(unix)]
-                {
-                    use std::os::unix::ffi::OsStringExt;
-                    let target = OsString::from_vec(target);
-                    let target_path = directory.as_ref().join(target);
-                    std::os::unix::fs::symlink(target_path, outpath.as_path())?;
-                }
-                #[cfg(windows)]
-                {
-                    let Ok(target) = String::from_utf8(target) else {
-                        return Err(ZipError::InvalidArchive("Invalid UTF-8 as symlink target"));
-                    };
-                    let target = target.into_boxed_str();
-                    let target_is_dir_from_archive =
-                        self.shared.files.contains_key(&target) && is_dir(&target);
-                    let target_path = directory.as_ref().join(OsString::from(target.to_string()));
-                    let target_is_dir = if target_is_dir_from_archive {
-                        true
-                    } else if let Ok(meta) = std::fs::metadata(&target_path) {
-                        meta.is_dir()
-                    } else {
-                        false
-                    };
-                    if target_is_dir {
-                        std::os::windows::fs::symlink_dir(target_path, outpath.as_path())?;
-                    } else {
-                        std::os::windows::fs::symlink_file(target_path, outpath.as_path())?;
-                    }
-                }
-                continue;
-            }
-            let mut file = self.by_index(i)?;
-            let mut outfile = fs::File::create(&outpath)?;
-            io::copy(&mut file, &mut outfile)?;
-            #[cfg(unix)]
-            {
-                // Check for real permissions, which we'll set in a second pass
-                if let Some(mode) = file.unix_mode() {
-                    files_by_unix_mode.push((outpath.clone(), mode));
-                }
-            }
-        }
-        #[cfg(unix)]
-        {
-            use std::cmp::Reverse;
-            use std::os::unix::fs::PermissionsExt;
-
-            if files_by_unix_mode.len() > 1 {
-                // Ensure we update children's permissions before making a parent unwritable
-                files_by_unix_mode.sort_by_key(|(path, _)| Reverse(path.clone()));
-            }
-            for (path, mode) in files_by_unix_mode.into_iter() {
-                fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
-            }
+    /// The entry count is checked here, along with overlapping entries if
+    /// [`Limits::reject_overlapping_entries`] is set; the other limits are enforced when the
+    /// archive is extracted. Since Xz entries are decompressed into memory, ones larger than
+    /// [`Limits::max_total_size`] can't be opened at all.
+    pub fn with_limits(config: Config, limits: Limits, mut reader: R) -> ZipResult<ZipArchive<R>> {
+        reader.seek(SeekFrom::Start(0))?;
+        let (footer, shared) =
+            Self::get_metadata(config, limits, &mut reader, &[]).map_err(no_central_directory)?;
+        if limits.reject_overlapping_entries {
+            reject_overlapping_entries(&shared, &mut reader)?;
         }
-        Ok(())
+        Ok(ZipArchive {
+            reader,
+            shared: shared.into(),
+            comment: footer.zip_file_comment.into(),
+        })
     }
 
     fn make_writable_dir_all<T: AsRef<Path>>(outpath: T) -> Result<(), ZipError> {
@@ -1141,12 +1414,12 @@ impl<R: Read + Seek> ZipArchive<R> {
     /// There are many passwords out there that will also pass the validity checks
     /// we are able to perform. This is a weakness of the ZipCrypto algorithm,
     /// due to its fairly primitive approach to cryptography.
-    pub fn by_name_decrypt(&mut self, name: &str, password: &[u8]) -> ZipResult<ZipFile> {
+    pub fn by_name_decrypt(&mut self, name: &str, password: &[u8]) -> ZipResult<ZipFile<'_>> {
         self.by_name_with_optional_password(name, Some(password))
     }
 
     /// Search for a file entry by name
-    pub fn by_name(&mut self, name: &str) -> ZipResult<ZipFile> {
+    pub fn by_name(&mut self, name: &str) -> ZipResult<ZipFile<'_>> {
         self.by_name_with_optional_password(name, None)
     }
 
@@ -1171,6 +1444,14 @@ impl<R: Read + Seek> ZipArchive<R> {
             .map(|(name, _)| name.as_ref())
     }
 
+    /// Search for a file entry by name, and read it from any position. See [`ZipFileSeek`].
+    pub fn by_name_seek(&mut self, name: &str) -> ZipResult<ZipFileSeek<'_>> {
+        let Some(index) = self.shared.files.get_index_of(name) else {
+            return Err(ZipError::FileNotFound);
+        };
+        self.by_index_seek(index)
+    }
+
     fn by_name_with_optional_password<'a>(
         &'a mut self,
         name: &str,
@@ -1223,40 +1504,34 @@ impl<R: Read + Seek> ZipArchive<R> {
         })
     }
 
+    /// Get a contained file by index, and read it from any position. See [`ZipFileSeek`].
+    pub fn by_index_seek(&mut self, file_number: usize) -> ZipResult<ZipFileSeek<'_>> {
+        let (_, data) = self
+            .shared
+            .files
+            .get_index(file_number)
+            .ok_or(ZipError::FileNotFound)?;
+        self.shared.limits.check_in_memory_entry(data, false)?;
+        let data_start = match data.data_start.get() {
+            Some(data_start) => *data_start,
+            None => find_data_start(data, &mut self.reader)?,
+        };
+        ZipFileSeek::new(data, &mut self.reader, data_start)
+    }
+
     fn by_index_with_optional_password(
         &mut self,
         file_number: usize,
-        mut password: Option<&[u8]>,
+        password: Option<&[u8]>,
     ) -> ZipResult<ZipFile<'_>> {
         let (_, data) = self
             .shared
             .files
             .get_index(file_number)
             .ok_or(ZipError::FileNotFound)?;
-
-        match (password, data.encrypted) {
-            (None, true) => return Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED)),
-            (Some(_), false) => password = None, //Password supplied, but none needed! Discard.
-            _ => {}
-        }
+        let password = password_for(data, password)?;
         let limit_reader = find_content(data, &mut self.reader)?;
-
-        let crypto_reader = make_crypto_reader(
-            data.compression_method,
-            data.crc32,
-            data.last_modified_time,
-            data.using_data_descriptor,
-            limit_reader,
-            password,
-            data.aes_mode,
-            #[cfg(feature = "aes-crypto")]
-            data.compressed_size,
-        )?;
-        Ok(ZipFile {
-            crypto_reader: Some(crypto_reader),
-            reader: ZipFileReader::NoReader,
-            data: Cow::Borrowed(data),
-        })
+        open_entry(data, limit_reader, password, &self.shared.limits)
     }
 
     /// Unwrap and return the inner reader object
@@ -1283,17 +1558,36 @@ const fn unsupported_zip_error<T>(detail: &'static str) -> ZipResult<T> {
     Err(ZipError::UnsupportedArchive(detail))
 }
 
+/// Whether an archive whose last disk is `disk_number` should be read as split across
+/// `volume_starts`, with offsets relative to the volume they refer to. Split archives where every
+/// disk number is 0 (as when a single file is simply cut into pieces) are read as one stream.
+fn is_split(disk_number: u32, volume_starts: &[u64]) -> bool {
+    disk_number != 0 && !volume_starts.is_empty()
+}
+
 /// Parse a central directory entry to collect the information for the file.
+///
+/// For a split archive, `volume_starts` gives the offset of each volume in `reader`; the local
+/// header is then found on the volume it names.
 pub(crate) fn central_header_to_zip_file<R: Read + Seek>(
     reader: &mut R,
     archive_offset: u64,
+    volume_starts: &[u64],
 ) -> ZipResult<ZipFileData> {
     let central_header_start = reader.stream_position()?;
 
     // Parse central header
     let block = ZipCentralEntryBlock::parse(reader)?;
-    let file =
+    let mut file =
         central_header_to_zip_file_inner(reader, archive_offset, central_header_start, block)?;
+    if !volume_starts.is_empty() {
+        file.header_start = volume_starts
+            .get(file.disk_number as usize)
+            .and_then(|start| start.checked_add(file.header_start))
+            .ok_or(InvalidArchive(
+                "File starts on a volume that wasn't provided",
+            ))?;
+    }
     let central_header_end = reader.stream_position()?;
     let data_start = find_data_start(&file, reader)?;
     if data_start > central_header_start {
@@ -1306,6 +1600,24 @@ pub(crate) fn central_header_to_zip_file<R: Read + Seek>(
     Ok(file)
 }
 
+/// Like [`central_header_to_zip_file`] for an archive that isn't split, without reading the local
+/// header.
+fn central_header_to_zip_file_lazily<R: Read + Seek>(
+    reader: &mut R,
+    archive_offset: u64,
+) -> ZipResult<ZipFileData> {
+    let central_header_start = reader.stream_position()?;
+    let block = ZipCentralEntryBlock::parse(reader)?;
+    let file =
+        central_header_to_zip_file_inner(reader, archive_offset, central_header_start, block)?;
+    if file.header_start >= central_header_start {
+        return Err(InvalidArchive(
+            "A file can't start after its central-directory header",
+        ));
+    }
+    Ok(file)
+}
+
 #[inline]
 fn read_variable_length_byte_field<R: Read>(reader: &mut R, len: usize) -> io::Result<Box<[u8]>> {
     let mut data = vec![0; len].into_boxed_slice();
@@ -1334,7 +1646,7 @@ fn central_header_to_zip_file_inner<R: Read>(
         file_name_length,
         extra_field_length,
         file_comment_length,
-        // disk_number,
+        disk_number,
         // internal_file_attributes,
         external_file_attributes,
         offset,
@@ -1377,6 +1689,7 @@ fn central_header_to_zip_file_inner<R: Read>(
         central_extra_field: None,
         file_comment,
         header_start: offset.into(),
+        disk_number: disk_number.into(),
         extra_data_start: None,
         central_header_start,
         data_start: OnceLock::new(),
@@ -1470,6 +1783,12 @@ pub(crate) fn parse_single_extra_field<R: Read>(
                 file.header_start = reader.read_u64_le()?;
                 consumed_len += size_of::<u64>();
             }
+            // Unlike the sizes and offset, which some writers record even when the header holds
+            // the real value, the disk number is only here when the header's is 0xFFFF.
+            if file.disk_number == u16::MAX.into() {
+                file.disk_number = reader.read_u32_le()?;
+                consumed_len += size_of::<u32>();
+            }
             let Some(leftover_len) = (len as usize).checked_sub(consumed_len) else {
                 return Err(InvalidArchive("ZIP64 extra-data field is the wrong length"));
             };
@@ -1543,16 +1862,32 @@ pub(crate) fn parse_single_extra_field<R: Read>(
 }
 
 /// Methods for retrieving information on zip files
-impl<'a> ZipFile<'a> {
-    fn get_reader(&mut self) -> ZipResult<&mut ZipFileReader<'a>> {
+impl<'a, R: Read + ?Sized> ZipFile<'a, R> {
+    fn get_reader(&mut self) -> ZipResult<&mut ZipFileReader<'a, R>> {
         if let ZipFileReader::NoReader = self.reader {
             let data = &self.data;
             let crypto_reader = self.crypto_reader.take().expect("Invalid reader state");
-            self.reader = make_reader(data.compression_method, data.crc32, crypto_reader)?;
+            self.reader = make_reader(
+                data.compression_method,
+                data.crc32,
+                data.uncompressed_size,
+                crypto_reader,
+            )?;
         }
         Ok(&mut self.reader)
     }
 
+    /// The compressed bytes the contents may have been read from so far, which is only known as
+    /// they're read for entries from a stream with a data descriptor.
+    pub(crate) fn compressed_bytes_read(&mut self) -> u64 {
+        match &mut self.reader {
+            ZipFileReader::DataDescriptor(_, compressed_size) => {
+                compressed_size.load(Ordering::Relaxed)
+            }
+            _ => self.data.compressed_size,
+        }
+    }
+
     pub(crate) fn get_raw_reader(&mut self) -> &mut dyn Read {
         if let ZipFileReader::NoReader = self.reader {
             let crypto_reader = self.crypto_reader.take().expect("Invalid reader state");
@@ -1708,7 +2043,7 @@ impl<'a> ZipFile<'a> {
     }
 }
 
-impl<'a> Read for ZipFile<'a> {
+impl<R: Read + ?Sized> Read for ZipFile<'_, R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.get_reader()?.read(buf)
     }
@@ -1726,7 +2061,7 @@ impl<'a> Read for ZipFile<'a> {
     }
 }
 
-impl<'a> Drop for ZipFile<'a> {
+impl<R: Read + ?Sized> Drop for ZipFile<'_, R> {
     fn drop(&mut self) {
         // self.data is Owned, this reader is constructed by a streaming reader.
         // In this case, we want to exhaust the reader so that the next file is accessible.
@@ -1761,11 +2096,48 @@ impl<'a> Drop for ZipFile<'a> {
 /// The Drop implementation of ZipFile ensures that the reader will be correctly positioned after
 /// the structure is done.
 ///
+/// Entries whose sizes are stored in a data descriptor after their data (such as those written by
+/// [`crate::ZipWriter::new_stream`]) are supported for the Stored, Deflated, Deflate64, Bzip2,
+/// Zstd, Xz and Lzma methods; the descriptor is checked against the decompressed data and then
+/// consumed, so the next header can be read. Since the end of such an entry can only be found by
+/// looking past it, `reader` is read one byte at a time while decoding them; wrap it in a
+/// [`std::io::BufReader`] beforehand, or use [`crate::read::stream::ZipStreamReader`], which
+/// does so itself.
+///
+/// An Lzma entry with a data descriptor is decompressed whole into memory when it's first read,
+/// however large it turns out to be, as is each block of an Xz one.
+/// [`crate::read::stream::ZipStreamReader::with_limits`] bounds this by [`Limits::max_total_size`].
+///
 /// Missing fields are:
 /// * `comment`: set to an empty string
 /// * `data_start`: set to 0
 /// * `external_attributes`: `unix_mode()`: will return None
-pub fn read_zipfile_from_stream<'a, R: Read>(reader: &'a mut R) -> ZipResult<Option<ZipFile<'_>>> {
+///
+/// For entries with a data descriptor, `crc32`, `compressed_size` and `size` are also left as
+/// they appear in the local header, which is usually 0.
+pub fn read_zipfile_from_stream<'a, R: Read>(reader: &'a mut R) -> ZipResult<Option<ZipFile<'a>>> {
+    read_zipfile_from_stream_with(reader, None, |reader| {
+        StreamSource::unbuffered(reader as &'a mut dyn Read)
+    })
+}
+
+/// Like [`read_zipfile_from_stream`], but lets entries with a data descriptor look ahead through
+/// the reader's buffer, and fails on those that would be decompressed into memory whole if
+/// they're larger than `max_buffered`.
+pub(crate) fn read_zipfile_from_buffered_stream<'a, R: BufRead>(
+    reader: &'a mut R,
+    max_buffered: Option<u64>,
+) -> ZipResult<Option<ZipFile<'a>>> {
+    read_zipfile_from_stream_with(reader, max_buffered, |reader| {
+        StreamSource::Buffered(reader as &'a mut dyn BufRead)
+    })
+}
+
+fn read_zipfile_from_stream_with<'a, R: Read>(
+    reader: &'a mut R,
+    max_buffered: Option<u64>,
+    make_source: impl FnOnce(&'a mut R) -> StreamSource<'a>,
+) -> ZipResult<Option<ZipFile<'a>>> {
     // We can't use the typical ::parse() method, as we follow separate code paths depending on the
     // "magic" value (since the magic value will be from the central directory header if we've
     // finished iterating over all the actual files).
@@ -1785,16 +2157,40 @@ pub fn read_zipfile_from_stream<'a, R: Read>(reader: &'a mut R) -> ZipResult<Opt
     let block = ZipLocalEntryBlock::interpret(&block)?;
 
     let mut result = ZipFileData::from_local_block(block, reader)?;
+    // Streamed entries are never decrypted
+    if result.encrypted {
+        return Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED));
+    }
 
     match parse_extra_field(&mut result) {
         Ok(..) | Err(ZipError::Io(..)) => {}
         Err(e) => return Err(e),
     }
 
-    let limit_reader = (reader as &'a mut dyn Read).take(result.compressed_size);
+    if result.using_data_descriptor {
+        let zip64 = result
+            .extra_field
+            .as_deref()
+            .is_some_and(|extra_field| has_zip64_extra_field(extra_field));
+        let reader = DataDescriptorReader::new(
+            make_source(reader),
+            result.compression_method,
+            zip64,
+            max_buffered,
+        )?;
+        let compressed_size = reader.compressed_size();
+        return Ok(Some(ZipFile {
+            data: Cow::Owned(result),
+            crypto_reader: None,
+            reader: ZipFileReader::DataDescriptor(Box::new(reader), compressed_size),
+        }));
+    }
+
+    let limit_reader = EntrySource::Reader(reader as &'a mut dyn Read).take(result.compressed_size);
 
     let result_crc32 = result.crc32;
     let result_compression_method = result.compression_method;
+    let uncompressed_size = result.uncompressed_size;
     let crypto_reader = make_crypto_reader(
         result_compression_method,
         result_crc32,
@@ -1810,7 +2206,12 @@ pub fn read_zipfile_from_stream<'a, R: Read>(reader: &'a mut R) -> ZipResult<Opt
     Ok(Some(ZipFile {
         data: Cow::Owned(result),
         crypto_reader: None,
-        reader: make_reader(result_compression_method, result_crc32, crypto_reader)?,
+        reader: make_reader(
+            result_compression_method,
+            result_crc32,
+            uncompressed_size,
+            crypto_reader,
+        )?,
     }))
 }
 
@@ -2084,4 +2485,217 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn open_split_archive() -> ZipResult<()> {
+        let volumes = vec![
+            Cursor::new(&include_bytes!("../tests/data/split.z01")[..]),
+            Cursor::new(&include_bytes!("../tests/data/split.zip")[..]),
+        ];
+        // The first volume starts with the spanning signature.
+        assert_eq!(&volumes[0].get_ref()[..4], b"PK\x07\x08");
+        let mut archive = ZipArchive::open_split(volumes)?;
+        assert_eq!(
+            archive.file_names().collect::<Vec<_>>(),
+            ["first.txt", "big.bin", "last.txt"]
+        );
+
+        let mut contents = String::new();
+        archive
+            .by_name("first.txt")?
+            .read_to_string(&mut contents)?;
+        assert_eq!(contents, "This is the first file.\n");
+        contents.clear();
+        // This one straddles the two volumes.
+        archive.by_name("big.bin")?.read_to_string(&mut contents)?;
+        let expected: String = (0..1600)
+            .map(|i| format!("Line {i:05} of a file that spans two volumes.\n"))
+            .collect();
+        assert_eq!(contents, expected);
+        contents.clear();
+        archive.by_name("last.txt")?.read_to_string(&mut contents)?;
+        assert_eq!(
+            contents,
+            "This is the last file, after the volume boundary.\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn open_split_archive_with_limits() -> ZipResult<()> {
+        use super::{Config, Limits};
+        use crate::result::ZipError;
+
+        let volumes = || {
+            vec![
+                Cursor::new(&include_bytes!("../tests/data/split.z01")[..]),
+                Cursor::new(&include_bytes!("../tests/data/split.zip")[..]),
+            ]
+        };
+        let limits = Limits {
+            reject_overlapping_entries: true,
+            ..Default::default()
+        };
+        let archive = ZipArchive::open_split_with_limits(Config::default(), limits, volumes())?;
+        assert_eq!(archive.len(), 3);
+        let limits = Limits {
+            max_entries: Some(2),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ZipArchive::open_split_with_limits(Config::default(), limits, volumes()),
+            Err(ZipError::LimitExceeded(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn zip64_extra_field_disk_number() -> ZipResult<()> {
+        use super::parse_single_extra_field;
+        use crate::types::ZipFileData;
+
+        let mut field = vec![0x01, 0x00, 28, 0x00];
+        field.extend_from_slice(&1u64.to_le_bytes());
+        field.extend_from_slice(&2u64.to_le_bytes());
+        field.extend_from_slice(&3u64.to_le_bytes());
+        field.extend_from_slice(&4u32.to_le_bytes());
+        // The disk number is only taken from the field when the header's is 0xFFFF.
+        let mut file = ZipFileData {
+            disk_number: 5,
+            ..Default::default()
+        };
+        parse_single_extra_field(&mut file, &mut &field[..], 0, false)?;
+        assert_eq!(
+            (
+                file.uncompressed_size,
+                file.compressed_size,
+                file.header_start
+            ),
+            (1, 2, 3)
+        );
+        assert_eq!(file.disk_number, 5);
+        file.disk_number = u16::MAX.into();
+        parse_single_extra_field(&mut file, &mut &field[..], 0, false)?;
+        assert_eq!(file.disk_number, 4);
+
+        // A field that holds only the disk number.
+        let mut field = vec![0x01, 0x00, 4, 0x00];
+        field.extend_from_slice(&6u32.to_le_bytes());
+        file.disk_number = u16::MAX.into();
+        parse_single_extra_field(&mut file, &mut &field[..], 0, false)?;
+        assert_eq!(file.disk_number, 6);
+        assert_eq!(file.header_start, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn open_split_archive_missing_volume() {
+        let volumes = vec![Cursor::new(&include_bytes!("../tests/data/split.zip")[..])];
+        assert!(ZipArchive::open_split(volumes).is_err());
+        assert!(
+            ZipArchive::new(Cursor::new(&include_bytes!("../tests/data/split.zip")[..])).is_err()
+        );
+    }
+
+    #[test]
+    fn open_split_cut_archive() -> ZipResult<()> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(Stored);
+        for i in 0..10 {
+            writer.start_file(format!("{i}.txt"), options)?;
+            writer.write_all(i.to_string().repeat(20).as_bytes())?;
+        }
+        let bytes = writer.finish()?.into_inner();
+
+        // Cutting a single-volume archive into pieces leaves every disk number at 0, with offsets
+        // counted from the start of the first piece.
+        let volumes = bytes.chunks(100).map(Cursor::new).collect::<Vec<_>>();
+        assert!(volumes.len() > 2);
+        let mut archive = ZipArchive::open_split(volumes)?;
+        assert_eq!(archive.len(), 10);
+        for i in 0..10 {
+            let mut contents = String::new();
+            archive.by_index(i)?.read_to_string(&mut contents)?;
+            assert_eq!(contents, i.to_string().repeat(20));
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "_deflate-any")]
+    fn extract_limits() -> ZipResult<()> {
+        use super::{Config, Limits};
+        use crate::result::ZipError;
+        use crate::CompressionMethod::Deflated;
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(Deflated);
+        writer.start_file("zeros", options)?;
+        writer.write_all(&[0; 1 << 20])?;
+        let mut bytes = writer.finish()?.into_inner();
+        // Understate the uncompressed size in both headers, which extraction mustn't rely on.
+        let central = bytes
+            .windows(4)
+            .position(|window| window == b"PK\x01\x02")
+            .unwrap();
+        bytes[22..26].copy_from_slice(&10u32.to_le_bytes());
+        bytes[central + 24..central + 28].copy_from_slice(&10u32.to_le_bytes());
+
+        let extract = |limits: Limits| {
+            let tempdir = TempDir::new("extract_limits")?;
+            ZipArchive::with_limits(Config::default(), limits, Cursor::new(&bytes))?
+                .extract(&tempdir)
+        };
+        assert!(matches!(
+            extract(Limits {
+                max_total_size: Some(1000),
+                ..Default::default()
+            }),
+            Err(ZipError::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            extract(Limits {
+                max_compression_ratio: Some(100),
+                ..Default::default()
+            }),
+            Err(ZipError::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            extract(Limits {
+                max_name_length: Some(4),
+                ..Default::default()
+            }),
+            Err(ZipError::LimitExceeded(_))
+        ));
+        extract(Limits {
+            max_total_size: Some(1 << 20),
+            max_compression_ratio: Some(2000),
+            max_entries: Some(1),
+            max_name_length: Some(5),
+            max_depth: Some(1),
+            reject_overlapping_entries: false,
+        })?;
+        Ok(())
+    }
+
+    #[test]
+    fn entry_count_limit() -> ZipResult<()> {
+        use super::{Config, Limits};
+        use crate::result::ZipError;
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..3 {
+            writer.add_directory(format!("{i}"), SimpleFileOptions::default())?;
+        }
+        let bytes = writer.finish()?.into_inner();
+        let limits = Limits {
+            max_entries: Some(2),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ZipArchive::with_limits(Config::default(), limits, Cursor::new(&bytes)),
+            Err(ZipError::LimitExceeded(_))
+        ));
+        Ok(())
+    }
 }
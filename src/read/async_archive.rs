@@ -0,0 +1,953 @@
+//! Reading archives from [`tokio`] readers.
+//!
+//! The central directory is parsed by the same code as [`ZipArchive`], once it's been fetched
+//! along with the end records that locate it. Local headers aren't part of this: each one is read
+//! when its entry is opened, unless every one has to be checked for overlaps. Entries are
+//! decompressed, decrypted and checked by the same readers as [`ZipFile`], which are
+//! handed their compressed data a chunk at a time.
+
+use super::verify::reject_overlapping_entries;
+use super::zip_archive::Shared;
+use super::{
+    data_start_from_local_block, no_central_directory, open_entry, password_for, Config,
+    EntrySource, Limits, ZipArchive, ZipFile,
+};
+use crate::result::ZipError::InvalidArchive;
+use crate::result::{ZipError, ZipResult};
+use crate::spec::{FixedSizeBlock, Magic, Zip32CDEBlock, Zip32CentralDirectoryEnd};
+use crate::types::{ZipFileData, ZipLocalEntryBlock};
+use memchr::memmem::FinderRev;
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::future::poll_fn;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::mem::{self, size_of};
+use std::ops::Deref;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, ReadBuf};
+
+/// How much of the end of the archive is searched at once for the end of central directory
+/// record. The end record's comment is at most 64 KiB long, so it's nearly always in the last.
+const END_WINDOW_SIZE: usize = 64 * 1024;
+
+/// How much is fetched when locating the central directory reads something that hasn't been
+/// fetched yet, such as a ZIP64 end record outside the end of the archive searched first.
+const FETCH_LEN: u64 = 64 * 1024;
+
+/// How much of an entry's compressed data is handed to its decompressor at once.
+const CHUNK_LEN: u64 = 64 * 1024;
+
+/// A chunk never leaves less than this much of an entry's data for the next one, so that
+/// trailers that are read in one go (such as the AES authentication code) are never split.
+const MIN_REMAINDER: u64 = 64;
+
+/// ZIP archive reader for [`tokio`] readers.
+///
+/// This supports the same archives, compression methods and encryption as [`ZipArchive`], except
+/// for archives split across several files.
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> zip::result::ZipResult<()> {
+/// use tokio::io::AsyncReadExt;
+///
+/// let file = tokio::fs::File::open("tests/data/files_and_dirs.zip").await?;
+/// let mut archive = zip::AsyncZipArchive::new(file).await?;
+/// for i in 0..archive.len() {
+///     let mut file = archive.by_index(i).await?;
+///     let mut contents = Vec::new();
+///     file.read_to_end(&mut contents).await?;
+///     println!("{}: {} bytes", file.name(), contents.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncZipArchive<R> {
+    reader: R,
+    shared: Arc<Shared>,
+    comment: Arc<[u8]>,
+    chunk: ChunkReader,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncZipArchive<R> {
+    /// Read a ZIP archive, collecting the files it contains.
+    ///
+    /// This uses the central directory record of the ZIP file, and ignores local file headers.
+    ///
+    /// A default [`Config`] is used.
+    pub async fn new(reader: R) -> ZipResult<AsyncZipArchive<R>> {
+        Self::with_config(Default::default(), reader).await
+    }
+
+    /// Read a ZIP archive providing a read configuration, collecting the files it contains.
+    ///
+    /// This uses the central directory record of the ZIP file, and ignores local file headers.
+    pub async fn with_config(config: Config, reader: R) -> ZipResult<AsyncZipArchive<R>> {
+        Self::with_limits(config, Limits::default(), reader).await
+    }
+
+    /// Read a ZIP archive from an untrusted source, with a read configuration and [`Limits`] on
+    /// what it may contain.
+    ///
+    /// As with [`ZipArchive::with_limits`], the entry count is checked here, along with
+    /// overlapping entries if [`Limits::reject_overlapping_entries`] is set, which means reading
+    /// every local header rather than just the central directory. Xz entries are
+    /// fetched and decompressed into memory, so ones larger than [`Limits::max_total_size`] can't
+    /// be opened at all.
+    pub async fn with_limits(
+        config: Config,
+        limits: Limits,
+        mut reader: R,
+    ) -> ZipResult<AsyncZipArchive<R>> {
+        let mut prefetched = Prefetched::new(reader.seek(SeekFrom::End(0)).await?);
+        if prefetched.len < size_of::<Zip32CDEBlock>() as u64 {
+            return Err(InvalidArchive("Invalid zip header"));
+        }
+        // The end record is almost always in the last window, so the windows before it are only
+        // searched if none of the candidates found so far locates a central directory. Those found
+        // last are tried first either way, as with `ZipArchive`.
+        let mut cde_locations = Vec::new();
+        let mut window_end = Some(prefetched.len);
+        let mut error = InvalidArchive("Could not find central directory end");
+        let (footer, shared) = loop {
+            let Some(end) = window_end else {
+                return Err(error);
+            };
+            let found = cde_locations.len();
+            window_end =
+                find_central_directory_ends(&mut reader, &mut prefetched, end, &mut cde_locations)
+                    .await?;
+            if cde_locations.len() == found {
+                continue;
+            }
+            match read_central_directory(
+                &mut reader,
+                &mut prefetched,
+                config,
+                limits,
+                &cde_locations,
+            )
+            .await
+            {
+                Ok(directory) => break directory,
+                Err(e @ ZipError::LimitExceeded(_)) => return Err(e),
+                Err(e) => error = no_central_directory(e),
+            }
+        };
+        drop(prefetched);
+        if limits.reject_overlapping_entries {
+            for data in shared.files.values() {
+                find_data_start(&mut reader, data).await?;
+            }
+            // Every local header has been read, so there's nothing left to read from.
+            reject_overlapping_entries(&shared, &mut io::Cursor::new([]))?;
+        }
+        Ok(AsyncZipArchive {
+            reader,
+            shared: shared.into(),
+            comment: footer.zip_file_comment.into(),
+            chunk: ChunkReader::default(),
+        })
+    }
+
+    /// Search for a file entry by name
+    pub async fn by_name(&mut self, name: &str) -> ZipResult<AsyncZipFile<'_, R>> {
+        let index = self.index_for_name(name).ok_or(ZipError::FileNotFound)?;
+        self.by_index_with_optional_password(index, None).await
+    }
+
+    /// Search for a file entry by name, decrypt with given password
+    ///
+    /// The same warnings apply as for [`ZipArchive::by_name_decrypt`].
+    pub async fn by_name_decrypt(
+        &mut self,
+        name: &str,
+        password: &[u8],
+    ) -> ZipResult<AsyncZipFile<'_, R>> {
+        let index = self.index_for_name(name).ok_or(ZipError::FileNotFound)?;
+        self.by_index_with_optional_password(index, Some(password))
+            .await
+    }
+
+    /// Get a contained file by index
+    pub async fn by_index(&mut self, file_number: usize) -> ZipResult<AsyncZipFile<'_, R>> {
+        self.by_index_with_optional_password(file_number, None)
+            .await
+    }
+
+    /// Get a contained file by index, decrypt with given password
+    ///
+    /// The same warnings apply as for [`ZipArchive::by_index_decrypt`].
+    pub async fn by_index_decrypt(
+        &mut self,
+        file_number: usize,
+        password: &[u8],
+    ) -> ZipResult<AsyncZipFile<'_, R>> {
+        self.by_index_with_optional_password(file_number, Some(password))
+            .await
+    }
+
+    async fn by_index_with_optional_password(
+        &mut self,
+        file_number: usize,
+        password: Option<&[u8]>,
+    ) -> ZipResult<AsyncZipFile<'_, R>> {
+        let (_, data) = self
+            .shared
+            .files
+            .get_index(file_number)
+            .ok_or(ZipError::FileNotFound)?;
+        self.shared.limits.check_in_memory_entry(data, true)?;
+        let password = password_for(data, password)?;
+        let data_start = find_data_start(&mut self.reader, data).await?;
+        self.reader.seek(SeekFrom::Start(data_start)).await?;
+
+        // The XZ decoder can't resume partway through its headers, so those entries are fetched
+        // whole; the first chunk of any other holds the encryption header, if there is one.
+        #[cfg(feature = "xz")]
+        let fetch_whole = data.compression_method == crate::CompressionMethod::Xz;
+        #[cfg(not(feature = "xz"))]
+        let fetch_whole = false;
+        *self.chunk.lock() = Chunk::new(data.compressed_size);
+        poll_fn(|cx| self.chunk.poll_fill(&mut self.reader, cx, fetch_whole)).await?;
+
+        let chunk = self.chunk.clone();
+        let source: &mut (dyn Read + Send) = &mut self.chunk;
+        let limit_reader = EntrySource::Reader(source).take(data.compressed_size);
+        Ok(AsyncZipFile {
+            file: open_entry(data, limit_reader, password, &self.shared.limits)?,
+            reader: &mut self.reader,
+            chunk,
+            fetch_whole,
+        })
+    }
+}
+
+impl<R> AsyncZipArchive<R> {
+    /// Number of files contained in this zip.
+    pub fn len(&self) -> usize {
+        self.shared.files.len()
+    }
+
+    /// Whether this zip archive contains no files
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the offset from the beginning of the underlying reader that this zip begins at, in bytes.
+    ///
+    /// Normally this value is zero, but if the zip has arbitrary data prepended to it, then this value will be the size
+    /// of that prepended data.
+    pub fn offset(&self) -> u64 {
+        self.shared.offset
+    }
+
+    /// Get the comment of the zip archive.
+    pub fn comment(&self) -> &[u8] {
+        &self.comment
+    }
+
+    /// Returns an iterator over all the file and directory names in this archive.
+    pub fn file_names(&self) -> impl Iterator<Item = &str> {
+        self.shared.files.keys().map(|s| s.as_ref())
+    }
+
+    /// Get the index of a file entry by name, if it's present.
+    pub fn index_for_name(&self, name: &str) -> Option<usize> {
+        self.shared.files.get_index_of(name)
+    }
+
+    /// Get the name of a file entry, if it's present.
+    pub fn name_for_index(&self, index: usize) -> Option<&str> {
+        self.shared
+            .files
+            .get_index(index)
+            .map(|(name, _)| name.as_ref())
+    }
+
+    /// Unwrap and return the inner reader object
+    ///
+    /// The position of the reader is undefined.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// A file in an [`AsyncZipArchive`], whose decompressed contents are read through [`AsyncRead`].
+///
+/// Its metadata is available through the methods of [`ZipFile`]. As with [`ZipFile`], reading
+/// fails if the CRC32 of the contents doesn't match once they've all been read.
+pub struct AsyncZipFile<'a, R> {
+    file: ZipFile<'a, dyn Read + Send + 'a>,
+    reader: &'a mut R,
+    chunk: ChunkReader,
+    fetch_whole: bool,
+}
+
+impl<R> Debug for AsyncZipFile<'_, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncZipFile")
+            .field("name", &self.file.name())
+            .field("chunk", &self.chunk)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, R> Deref for AsyncZipFile<'a, R> {
+    type Target = ZipFile<'a, dyn Read + Send + 'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.file
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncZipFile<'_, R> {
+    /// Reads the compressed data as it's stored, without decrypting or decompressing it, like
+    /// [`ZipFile::get_raw_reader`]. Used to copy entries into an [`crate::AsyncZipWriter`].
+    pub(crate) fn poll_read_raw(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            match self.file.get_raw_reader().read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    ready!(self.chunk.poll_fill(self.reader, cx, self.fetch_whole))?;
+                }
+                result => return Poll::Ready(result),
+            }
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncZipFile<'_, R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            match this.file.read(buf.initialize_unfilled()) {
+                Ok(count) => {
+                    buf.advance(count);
+                    return Poll::Ready(Ok(()));
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    ready!(this.chunk.poll_fill(this.reader, cx, this.fetch_whole))?;
+                }
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+        }
+    }
+}
+
+/// The part of an entry's compressed data that has been fetched but not yet decompressed.
+#[derive(Default)]
+struct Chunk {
+    data: Vec<u8>,
+    /// How much of `data` has been fetched; it's only read once it's all there.
+    filled: usize,
+    /// How much of `data` has been read.
+    position: usize,
+    /// How much of the entry is left to fetch after `data`.
+    unfetched: u64,
+}
+
+impl Chunk {
+    fn new(compressed_size: u64) -> Chunk {
+        Chunk {
+            unfetched: compressed_size,
+            ..Default::default()
+        }
+    }
+}
+
+/// Hands an entry's compressed data to the synchronous readers as it's fetched. Reading past what
+/// has been fetched fails with [`io::ErrorKind::WouldBlock`], which these readers pass on without
+/// losing their place.
+#[derive(Clone, Default)]
+struct ChunkReader(Arc<Mutex<Chunk>>);
+
+impl ChunkReader {
+    fn lock(&self) -> MutexGuard<'_, Chunk> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Fetches the next chunk, unless the current one hasn't been read yet.
+    fn poll_fill<R: AsyncRead + Unpin>(
+        &self,
+        reader: &mut R,
+        cx: &mut Context<'_>,
+        whole: bool,
+    ) -> Poll<io::Result<()>> {
+        let mut chunk = self.lock();
+        let chunk = &mut *chunk;
+        if chunk.filled == chunk.data.len() {
+            if chunk.position < chunk.filled || chunk.unfetched == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            let mut len = chunk.unfetched.min(CHUNK_LEN);
+            if whole || chunk.unfetched - len < MIN_REMAINDER {
+                len = chunk.unfetched;
+            }
+            let len = usize::try_from(len)
+                .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "Entry is too large"))?;
+            chunk.data.clear();
+            chunk.data.resize(len, 0);
+            chunk.filled = 0;
+            chunk.position = 0;
+            chunk.unfetched -= len as u64;
+        }
+        while chunk.filled < chunk.data.len() {
+            let mut buf = ReadBuf::new(&mut chunk.data[chunk.filled..]);
+            ready!(Pin::new(&mut *reader).poll_read(cx, &mut buf))?;
+            let count = buf.filled().len();
+            if count == 0 {
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            chunk.filled += count;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut chunk = self.lock();
+        if chunk.filled == chunk.data.len() {
+            if chunk.position < chunk.filled {
+                let count = (chunk.filled - chunk.position).min(buf.len());
+                buf[..count].copy_from_slice(&chunk.data[chunk.position..][..count]);
+                chunk.position += count;
+                return Ok(count);
+            }
+            if chunk.unfetched == 0 {
+                return Ok(0);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::WouldBlock,
+            "Entry data hasn't been fetched yet",
+        ))
+    }
+}
+
+impl Debug for ChunkReader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let chunk = self.lock();
+        f.debug_struct("ChunkReader")
+            .field("len", &chunk.data.len())
+            .field("filled", &chunk.filled)
+            .field("position", &chunk.position)
+            .field("unfetched", &chunk.unfetched)
+            .finish()
+    }
+}
+
+/// Finds where an entry's data starts from its local header, reading just the fixed-size part.
+async fn find_data_start<R: AsyncRead + AsyncSeek + Unpin>(
+    reader: &mut R,
+    data: &ZipFileData,
+) -> ZipResult<u64> {
+    if let Some(data_start) = data.data_start.get() {
+        return Ok(*data_start);
+    }
+    reader.seek(SeekFrom::Start(data.header_start)).await?;
+    let mut block = [0; size_of::<ZipLocalEntryBlock>()];
+    reader.read_exact(&mut block).await?;
+    Ok(data_start_from_local_block(
+        data,
+        &ZipLocalEntryBlock::interpret(&block)?,
+    ))
+}
+
+/// The parts of an archive fetched so far, which the central directory is located and parsed from
+/// as if they were the whole archive. Reading anything else fails, and is recorded so it can be
+/// fetched before trying again.
+struct Prefetched {
+    len: u64,
+    /// Fetched ranges, by where they start. They never overlap.
+    ranges: BTreeMap<u64, Vec<u8>>,
+    position: u64,
+    /// Where each range still to be fetched starts, and how long it is.
+    misses: Vec<(u64, u64)>,
+}
+
+impl Prefetched {
+    fn new(len: u64) -> Prefetched {
+        Prefetched {
+            len,
+            ranges: BTreeMap::new(),
+            position: 0,
+            misses: Vec::new(),
+        }
+    }
+
+    /// The parts of the `len` bytes from `position` that haven't been fetched, as `(start, end)`.
+    fn gaps(&self, position: u64, len: u64) -> Vec<(u64, u64)> {
+        let end = position.saturating_add(len).min(self.len);
+        let mut gaps = Vec::new();
+        if position >= end {
+            return gaps;
+        }
+        let mut covered = position;
+        if let Some((&start, range)) = self.ranges.range(..position).next_back() {
+            covered = covered.max(start + range.len() as u64);
+        }
+        for (&start, range) in self.ranges.range(position..end) {
+            if start > covered {
+                gaps.push((covered, start));
+            }
+            covered = covered.max(start + range.len() as u64);
+        }
+        if covered < end {
+            gaps.push((covered, end));
+        }
+        gaps
+    }
+
+    /// Fails unless the `len` bytes from `position` have all been fetched, recording them to be
+    /// fetched if they haven't.
+    fn require(&mut self, position: u64, len: u64) -> io::Result<()> {
+        if self.gaps(position, len).is_empty() {
+            return Ok(());
+        }
+        self.misses.push((position, len));
+        Err(not_fetched())
+    }
+
+    /// Fetches whatever hasn't been fetched yet of the `len` bytes from `position`.
+    async fn fetch<R: AsyncRead + AsyncSeek + Unpin>(
+        &mut self,
+        reader: &mut R,
+        position: u64,
+        len: u64,
+    ) -> io::Result<()> {
+        for (start, end) in self.gaps(position, len) {
+            let len = usize::try_from(end - start).map_err(|_| {
+                io::Error::new(io::ErrorKind::OutOfMemory, "Central directory is too large")
+            })?;
+            let mut data = vec![0; len];
+            reader.seek(SeekFrom::Start(start)).await?;
+            reader.read_exact(&mut data).await?;
+            self.ranges.insert(start, data);
+        }
+        Ok(())
+    }
+}
+
+fn not_fetched() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::WouldBlock,
+        "Archive data hasn't been fetched yet",
+    )
+}
+
+impl Read for Prefetched {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+        if let Some((&start, range)) = self.ranges.range(..=self.position).next_back() {
+            let offset = (self.position - start) as usize;
+            if offset < range.len() {
+                let count = (range.len() - offset).min(buf.len());
+                buf[..count].copy_from_slice(&range[offset..][..count]);
+                self.position += count as u64;
+                return Ok(count);
+            }
+        }
+        self.misses.push((self.position, FETCH_LEN));
+        Err(not_fetched())
+    }
+}
+
+impl Seek for Prefetched {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+/// Reads the central directory located by one of the end records in `cde_locations`, fetching
+/// it once it's been found, along with anything else needed to find it. Each candidate is tried
+/// with what's been fetched so far, and anything missing is fetched before trying them all again,
+/// so the central directory itself is only parsed once it's all there.
+async fn read_central_directory<R: AsyncRead + AsyncSeek + Unpin>(
+    reader: &mut R,
+    prefetched: &mut Prefetched,
+    config: Config,
+    limits: Limits,
+    cde_locations: &[(Zip32CentralDirectoryEnd, u64)],
+) -> ZipResult<(Zip32CentralDirectoryEnd, Shared)> {
+    loop {
+        let candidates = cde_locations
+            .iter()
+            .map(|(footer, cde_start_pos)| (Rc::new(footer.clone()), *cde_start_pos))
+            .collect();
+        let mut rejected_ends = Vec::new();
+        let result = ZipArchive::select_central_directory(
+            config,
+            prefetched,
+            &[],
+            candidates,
+            |dir_info, prefetched| {
+                let len = dir_info
+                    .cde_position
+                    .saturating_sub(dir_info.directory_start)
+                    .min(dir_info.directory_size);
+                prefetched.require(dir_info.directory_start, len)?;
+                ZipArchive::read_central_header(dir_info, config, limits, prefetched, &[], false)
+            },
+            |shared| shared.files.len(),
+            &mut rejected_ends,
+        );
+        let misses = mem::take(&mut prefetched.misses);
+        if misses.is_empty() {
+            let (footer, mut shared) = result?;
+            shared.tolerated.rejected_ends = rejected_ends;
+            return Ok((footer, shared.build()));
+        }
+        drop(result);
+        for (position, len) in misses {
+            prefetched.fetch(reader, position, len).await?;
+        }
+    }
+}
+
+/// Searches the window of the archive ending at `window_end` for candidates for the end of central
+/// directory record, like [`Zip32CentralDirectoryEnd::find_and_parse`], and adds them to
+/// `results`. The last window is kept in `prefetched`, since the central directory usually ends
+/// there. Returns where the next window ends, unless this was the first.
+async fn find_central_directory_ends<R: AsyncRead + AsyncSeek + Unpin>(
+    reader: &mut R,
+    prefetched: &mut Prefetched,
+    window_end: u64,
+    results: &mut Vec<(Zip32CentralDirectoryEnd, u64)>,
+) -> ZipResult<Option<u64>> {
+    const SIG_BYTES: [u8; size_of::<Magic>()] =
+        Magic::CENTRAL_DIRECTORY_END_SIGNATURE.to_le_bytes();
+    let finder = FinderRev::new(&SIG_BYTES);
+
+    let window_start = window_end.saturating_sub(END_WINDOW_SIZE as u64);
+    let mut window = vec![0; (window_end - window_start) as usize];
+    reader.seek(SeekFrom::Start(window_start)).await?;
+    reader.read_exact(&mut window).await?;
+    for offset in finder.rfind_iter(&window) {
+        let cde_start_pos = window_start + offset as u64;
+        // Drop any headers that don't parse.
+        if let Ok(footer) = parse_central_directory_end(reader, cde_start_pos).await {
+            results.push((footer, cde_start_pos));
+        }
+    }
+    if window_end == prefetched.len {
+        prefetched.ranges.insert(window_start, window);
+    }
+    if window_start == 0 {
+        return Ok(None);
+    }
+    // Overlap the windows so that a signature across their boundary is found once.
+    Ok(Some(window_start + SIG_BYTES.len() as u64 - 1))
+}
+
+async fn parse_central_directory_end<R: AsyncRead + AsyncSeek + Unpin>(
+    reader: &mut R,
+    cde_start_pos: u64,
+) -> ZipResult<Zip32CentralDirectoryEnd> {
+    let mut bytes = vec![0; size_of::<Zip32CDEBlock>()];
+    reader.seek(SeekFrom::Start(cde_start_pos)).await?;
+    reader.read_exact(&mut bytes).await?;
+    // The comment length is the last field of the block.
+    let comment_length = u16::from_le_bytes([bytes[bytes.len() - 2], bytes[bytes.len() - 1]]);
+    bytes.resize(bytes.len() + comment_length as usize, 0);
+    reader
+        .read_exact(&mut bytes[size_of::<Zip32CDEBlock>()..])
+        .await?;
+    Zip32CentralDirectoryEnd::parse(&mut io::Cursor::new(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::AsyncZipArchive;
+    use crate::read::{Config, Limits};
+    use crate::result::{ZipError, ZipResult};
+    use crate::write::SimpleFileOptions;
+    use crate::{CompressionMethod, ZipArchive, ZipWriter};
+    use std::io::{self, Cursor, Read, SeekFrom, Write};
+    use std::pin::Pin;
+    use std::task::{ready, Context, Poll};
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, ReadBuf};
+
+    /// Returns at most a few hundred bytes when it's polled, and only every other time.
+    struct Trickle {
+        inner: Cursor<Vec<u8>>,
+        ready: bool,
+        /// How many bytes have been read.
+        fetched: u64,
+    }
+
+    impl Trickle {
+        fn new(bytes: Vec<u8>) -> Trickle {
+            Trickle {
+                inner: Cursor::new(bytes),
+                ready: false,
+                fetched: 0,
+            }
+        }
+    }
+
+    impl AsyncRead for Trickle {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            if !std::mem::replace(&mut self.ready, false) {
+                self.ready = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let mut bytes = [0; 300];
+            let len = buf.remaining().min(bytes.len());
+            let count = Read::read(&mut self.inner, &mut bytes[..len])?;
+            buf.put_slice(&bytes[..count]);
+            self.fetched += count as u64;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncSeek for Trickle {
+        fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+            Pin::new(&mut self.inner).start_seek(position)
+        }
+
+        fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+            let position = ready!(Pin::new(&mut self.inner).poll_complete(cx))?;
+            Poll::Ready(Ok(position))
+        }
+    }
+
+    /// Checks that every entry reads the same as with [`ZipArchive`].
+    async fn assert_same_as_sync(bytes: &[u8], password: Option<&[u8]>) -> ZipResult<()> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        let mut async_archive = AsyncZipArchive::new(Trickle::new(bytes.to_vec())).await?;
+        assert_eq!(async_archive.len(), archive.len());
+        assert_eq!(async_archive.comment(), archive.comment());
+        for i in 0..archive.len() {
+            let mut expected = Vec::new();
+            let mut actual = Vec::new();
+            let encrypted = archive.shared.files[i].encrypted;
+            match password.filter(|_| encrypted) {
+                Some(password) => {
+                    archive
+                        .by_index_decrypt(i, password)?
+                        .read_to_end(&mut expected)?;
+                    let mut file = async_archive.by_index_decrypt(i, password).await?;
+                    file.read_to_end(&mut actual).await?;
+                    assert_eq!(file.name(), archive.name_for_index(i).unwrap());
+                }
+                None => {
+                    archive.by_index(i)?.read_to_end(&mut expected)?;
+                    let mut file = async_archive.by_index(i).await?;
+                    file.read_to_end(&mut actual).await?;
+                    assert_eq!(file.name(), archive.name_for_index(i).unwrap());
+                }
+            }
+            assert_eq!(actual, expected);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_written_archive() -> ZipResult<()> {
+        let contents: Vec<u8> = (0..300_000u32)
+            .flat_map(|i| (i % 1000).to_le_bytes())
+            .collect();
+        #[allow(unused_mut)]
+        let mut methods = vec![CompressionMethod::Stored];
+        #[cfg(feature = "_deflate-any")]
+        methods.push(CompressionMethod::Deflated);
+        #[cfg(feature = "bzip2")]
+        methods.push(CompressionMethod::Bzip2);
+        #[cfg(feature = "zstd")]
+        methods.push(CompressionMethod::Zstd);
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.set_comment("async");
+        for method in methods {
+            let options = SimpleFileOptions::default().compression_method(method);
+            writer.start_file(format!("{method}"), options)?;
+            writer.write_all(&contents)?;
+            writer.start_file(
+                format!("{method} ZipCrypto"),
+                options.with_deprecated_encryption(b"password"),
+            )?;
+            writer.write_all(&contents)?;
+            #[cfg(feature = "aes-crypto")]
+            {
+                writer.start_file(
+                    format!("{method} AES"),
+                    options.with_aes_encryption(crate::AesMode::Aes256, "password"),
+                )?;
+                writer.write_all(&contents)?;
+            }
+        }
+        writer.add_directory("dir/", SimpleFileOptions::default())?;
+        let bytes = writer.finish()?.into_inner();
+        assert_same_as_sync(&bytes, Some(b"password")).await
+    }
+
+    #[tokio::test]
+    async fn read_large_central_directory() -> ZipResult<()> {
+        // Much larger than the end of the archive that's searched first.
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..5000 {
+            writer.start_file(
+                format!("directory/file_{i:05}.txt"),
+                SimpleFileOptions::default(),
+            )?;
+            writer.write_all(i.to_string().as_bytes())?;
+        }
+        let bytes = writer.finish()?.into_inner();
+
+        let mut archive = AsyncZipArchive::new(Trickle::new(bytes)).await?;
+        assert_eq!(archive.len(), 5000);
+        let mut contents = String::new();
+        archive
+            .by_name("directory/file_04321.txt")
+            .await?
+            .read_to_string(&mut contents)
+            .await?;
+        assert_eq!(contents, "4321");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn open_fetches_little_more_than_the_central_directory() -> ZipResult<()> {
+        // Several megabytes of entries, with a central directory larger than the window that's
+        // searched for the end record.
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        for i in 0..3000u32 {
+            writer.start_file(format!("directory/file_{i:05}.txt"), options)?;
+            writer.write_all(&[i as u8; 2000])?;
+        }
+        let bytes = writer.finish()?.into_inner();
+        let dir_start = ZipArchive::new(Cursor::new(&bytes))?.shared.dir_start;
+        let directory_len = bytes.len() as u64 - dir_start;
+        assert!(directory_len > super::END_WINDOW_SIZE as u64);
+
+        let limits = Limits::default();
+        let mut archive =
+            AsyncZipArchive::with_limits(Config::default(), limits, Trickle::new(bytes.clone()))
+                .await?;
+        // The end record is read again to parse it.
+        assert!(archive.reader.fetched < directory_len + 100);
+        let mut contents = Vec::new();
+        archive
+            .by_name("directory/file_02999.txt")
+            .await?
+            .read_to_end(&mut contents)
+            .await?;
+        assert_eq!(contents, [2999u32 as u8; 2000]);
+
+        // Every local header is read, but nothing else of the entries.
+        let limits = limits.with_reject_overlapping_entries(true);
+        let archive =
+            AsyncZipArchive::with_limits(Config::default(), limits, Trickle::new(bytes)).await?;
+        let local_headers_len = 3000 * 30;
+        assert!(archive.reader.fetched < directory_len + local_headers_len + 100);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_test_files() -> ZipResult<()> {
+        assert_same_as_sync(include_bytes!("../../tests/data/files_and_dirs.zip"), None).await?;
+        assert_same_as_sync(include_bytes!("../../tests/data/comment_garbage.zip"), None).await?;
+        assert_same_as_sync(include_bytes!("../../tests/data/zip64_demo.zip"), None).await?;
+        #[cfg(feature = "deflate64")]
+        assert_same_as_sync(include_bytes!("../../tests/data/deflate64.zip"), None).await?;
+        #[cfg(feature = "lzma")]
+        assert_same_as_sync(include_bytes!("../../tests/data/lzma.zip"), None).await?;
+        #[cfg(feature = "xz")]
+        assert_same_as_sync(include_bytes!("../../tests/data/xz.zip"), None).await?;
+        #[cfg(feature = "aes-crypto")]
+        assert_same_as_sync(
+            include_bytes!("../../tests/data/aes_archive.zip"),
+            Some(b"helloworld"),
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_corrupt_entry() -> ZipResult<()> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.start_file("file", options)?;
+        writer.write_all(b"Hello, World!")?;
+        let mut bytes = writer.finish()?.into_inner();
+        // Flip a bit of the contents, after the 30-byte local header and 4-byte name.
+        bytes[34] ^= 1;
+
+        let mut archive = AsyncZipArchive::new(Trickle::new(bytes)).await?;
+        let mut file = archive.by_name("file").await?;
+        let mut contents = Vec::new();
+        let error = file.read_to_end(&mut contents).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        drop(file);
+        assert!(matches!(
+            archive.by_name("missing").await,
+            Err(ZipError::FileNotFound)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reject_overlapping_entries() -> ZipResult<()> {
+        let limits = Limits::default().with_reject_overlapping_entries(true);
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        // Spread the local headers out, so that they're fetched separately.
+        for i in 0..20 {
+            writer.start_file(format!("{i}"), SimpleFileOptions::default())?;
+            writer.write_all(&[i; 10_000])?;
+        }
+        let bytes = writer.finish()?.into_inner();
+        let archive =
+            AsyncZipArchive::with_limits(Default::default(), limits, Trickle::new(bytes)).await?;
+        assert_eq!(archive.shared.files.len(), 20);
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("a", SimpleFileOptions::default())?;
+        writer.write_all(b"Hello, World!")?;
+        writer.shallow_copy_file("a", "b")?;
+        let bytes = writer.finish()?.into_inner();
+        assert!(matches!(
+            AsyncZipArchive::with_limits(Default::default(), limits, Trickle::new(bytes)).await,
+            Err(ZipError::InvalidArchive("Overlapping entries"))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn entries_are_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<AsyncZipArchive<tokio::fs::File>>();
+        assert_send::<super::AsyncZipFile<'_, tokio::fs::File>>();
+    }
+}
@@ -20,3 +20,109 @@ pub enum ArchiveOffset {
     /// Specify a fixed archive offset.
     Known(u64),
 }
+
+/// Limits on what an archive may contain, to guard against archives crafted to exhaust disk
+/// space or memory when they're extracted ("zip bombs").
+///
+/// Every limit is off by default. Sizes are counted as data is actually decompressed, so an
+/// archive can't get past them by understating sizes in its headers. A breach is reported as
+/// [`ZipError::LimitExceeded`](crate::result::ZipError::LimitExceeded).
+///
+/// They're given to an archive with [`ZipArchive::with_limits`](crate::ZipArchive::with_limits),
+/// or to a stream with
+/// [`ZipStreamReader::with_limits`](crate::unstable::stream::ZipStreamReader::with_limits), and
+/// are checked by [`ZipArchive::extract`](crate::ZipArchive::extract),
+/// [`ZipStreamReader::extract`](crate::unstable::stream::ZipStreamReader::extract) and, when
+/// available, `split_extract` and `pool_extract`. The entry count is also checked when the central
+/// directory is read.
+///
+/// More limits may be added, so they're set with the `with_*` methods rather than built as a
+/// struct:
+///
+/// ```
+/// use zip::read::Limits;
+///
+/// let limits = Limits::default()
+///     .with_max_total_size(1 << 30)
+///     .with_max_compression_ratio(1100)
+///     .with_max_entries(100_000);
+/// assert_eq!(limits.max_entries, Some(100_000));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Limits {
+    /// The most bytes that may be decompressed in total, over all entries extracted.
+    pub max_total_size: Option<u64>,
+    /// The most bytes any single entry may decompress to for each byte of its compressed data.
+    ///
+    /// Very small entries can legitimately compress well, so this should leave some room:
+    /// Deflate can't exceed a ratio of about 1032.
+    ///
+    /// Where the compressed size has to be known before decompressing, as when `split_extract`
+    /// checks entries up front, it's taken from the headers, which an archive can overstate.
+    /// What bounds the output is [`max_total_size`](Self::max_total_size), which is counted
+    /// against the bytes actually decompressed.
+    pub max_compression_ratio: Option<u64>,
+    /// The most entries an archive may contain.
+    pub max_entries: Option<usize>,
+    /// The longest name in bytes an entry may have.
+    pub max_name_length: Option<usize>,
+    /// The most path components an entry's name may have, so `a/b/c.txt` has a depth of 3.
+    pub max_depth: Option<usize>,
+    /// Whether to reject archives where an entry's local header and data overlap those of
+    /// another entry, or run into the central directory.
+    ///
+    /// Several central directory headers pointing at the same compressed data are how
+    /// "overlapping file" zip bombs get a high ratio without nesting archives. Checking for this
+    /// reads every local header when the archive is opened, so it's only done by
+    /// [`ZipArchive`](crate::ZipArchive) and `AsyncZipArchive`. Whether or not it's enabled,
+    /// [`ZipArchive::overlapping_entries`](crate::ZipArchive::overlapping_entries) lists the
+    /// entries that overlap.
+    pub reject_overlapping_entries: bool,
+}
+
+impl Limits {
+    /// Set the most bytes that may be decompressed in total.
+    #[must_use]
+    pub const fn with_max_total_size(mut self, max: u64) -> Self {
+        self.max_total_size = Some(max);
+        self
+    }
+
+    /// Set the most bytes any single entry may decompress to for each byte of its compressed
+    /// data.
+    #[must_use]
+    pub const fn with_max_compression_ratio(mut self, max: u64) -> Self {
+        self.max_compression_ratio = Some(max);
+        self
+    }
+
+    /// Set the most entries an archive may contain.
+    #[must_use]
+    pub const fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = Some(max);
+        self
+    }
+
+    /// Set the longest name in bytes an entry may have.
+    #[must_use]
+    pub const fn with_max_name_length(mut self, max: usize) -> Self {
+        self.max_name_length = Some(max);
+        self
+    }
+
+    /// Set the most path components an entry's name may have.
+    #[must_use]
+    pub const fn with_max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    /// Set whether to reject archives whose entries overlap, as described for
+    /// [`reject_overlapping_entries`](Self::reject_overlapping_entries).
+    #[must_use]
+    pub const fn with_reject_overlapping_entries(mut self, reject: bool) -> Self {
+        self.reject_overlapping_entries = reject;
+        self
+    }
+}
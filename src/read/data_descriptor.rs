@@ -0,0 +1,782 @@
+//! Reading entries from a stream when their sizes are only given in a data descriptor after the
+//! compressed data (bit 3 of the general purpose flags).
+//!
+//! Since the compressed size isn't known up front, the end of the data has to be found some other
+//! way: compressed formats mark their own end, so they're decompressed until the decoder finishes,
+//! while stored data is scanned for a descriptor signature that agrees with the CRC and length of
+//! what has been read so far. Either way, nothing past the descriptor may be consumed, because it
+//! belongs to the next entry.
+
+use crate::compression::CompressionMethod;
+use crate::result::{ZipError, ZipResult};
+use crate::spec;
+use crate::unstable::LittleEndianReadExt;
+use crc32fast::Hasher;
+use std::io::{self, BufRead, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[cfg(feature = "deflate-flate2")]
+use flate2::bufread::DeflateDecoder;
+
+#[cfg(feature = "deflate64")]
+use deflate64::Deflate64Decoder;
+
+#[cfg(feature = "bzip2")]
+use bzip2::bufread::BzDecoder;
+
+#[cfg(feature = "zstd")]
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+#[cfg(feature = "xz")]
+use crate::read::xz::XzDecoder;
+
+const SIGNATURE: [u8; 4] = spec::Magic::DATA_DESCRIPTOR_SIGNATURE.to_le_bytes();
+const DESCRIPTOR_LEN: usize = 16;
+const ZIP64_DESCRIPTOR_LEN: usize = 24;
+
+/// The stream an entry is read from.
+pub(crate) enum StreamSource<'a> {
+    /// Already buffered, so decoders can look ahead without losing anything.
+    Buffered(&'a mut dyn BufRead),
+    /// Unbuffered, so lookahead is limited to a single byte. This byte is kept here until it's
+    /// consumed, and the data descriptor is read through [`Read`] to pick it up.
+    Unbuffered {
+        inner: &'a mut dyn Read,
+        peeked: Option<u8>,
+    },
+}
+
+impl<'a> StreamSource<'a> {
+    pub(crate) fn unbuffered(inner: &'a mut dyn Read) -> Self {
+        StreamSource::Unbuffered {
+            inner,
+            peeked: None,
+        }
+    }
+}
+
+impl Read for StreamSource<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            StreamSource::Buffered(inner) => inner.read(buf),
+            StreamSource::Unbuffered { inner, peeked } => match (peeked.take(), buf.first_mut()) {
+                (Some(byte), Some(first)) => {
+                    *first = byte;
+                    Ok(1)
+                }
+                (byte, _) => {
+                    *peeked = byte;
+                    inner.read(buf)
+                }
+            },
+        }
+    }
+}
+
+impl BufRead for StreamSource<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            StreamSource::Buffered(inner) => inner.fill_buf(),
+            StreamSource::Unbuffered { inner, peeked } => {
+                if peeked.is_none() {
+                    let mut byte = [0u8];
+                    loop {
+                        match inner.read(&mut byte) {
+                            Ok(0) => return Ok(&[]),
+                            Ok(_) => break,
+                            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    *peeked = Some(byte[0]);
+                }
+                Ok(std::slice::from_ref(peeked.as_ref().unwrap()))
+            }
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            StreamSource::Buffered(inner) => inner.consume(amt),
+            StreamSource::Unbuffered { peeked, .. } => {
+                if amt > 0 {
+                    debug_assert_eq!(amt, 1);
+                    *peeked = None;
+                }
+            }
+        }
+    }
+}
+
+/// Counts the bytes consumed from the stream, which add up to the compressed size. The count is
+/// shared with the [`ZipFile`](crate::read::ZipFile) the entry is read through, which can't look
+/// inside the reader.
+pub(crate) struct CountingSource<'a> {
+    inner: StreamSource<'a>,
+    count: Arc<AtomicU64>,
+}
+
+impl CountingSource<'_> {
+    fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+impl Read for CountingSource<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.count.fetch_add(count as u64, Ordering::Relaxed);
+        Ok(count)
+    }
+}
+
+impl BufRead for CountingSource<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.count.fetch_add(amt as u64, Ordering::Relaxed);
+    }
+}
+
+enum Decoder<'a> {
+    Stored {
+        source: CountingSource<'a>,
+        /// Bytes starting with a possible descriptor signature, which turned out to be data if
+        /// they don't match what's been read so far.
+        window: Vec<u8>,
+    },
+    #[cfg(feature = "deflate-flate2")]
+    Deflated(DeflateDecoder<CountingSource<'a>>),
+    #[cfg(feature = "deflate64")]
+    Deflate64(Deflate64Decoder<CountingSource<'a>>),
+    #[cfg(feature = "bzip2")]
+    Bzip2(BzDecoder<CountingSource<'a>>),
+    #[cfg(feature = "zstd")]
+    Zstd(ZstdDecoder<'static, CountingSource<'a>>),
+    #[cfg(feature = "xz")]
+    Xz(XzDecoder<CountingSource<'a>>),
+    /// lzma-rs can only decompress a whole stream at once, so this happens on the first read,
+    /// failing once the output passes `max_size`.
+    #[cfg(feature = "lzma")]
+    Lzma {
+        source: CountingSource<'a>,
+        output: Option<io::Cursor<Vec<u8>>>,
+        max_size: Option<u64>,
+    },
+}
+
+impl<'a> Decoder<'a> {
+    /// The compressed data, for codecs that find the end of it themselves.
+    fn compressed_source(&mut self) -> Option<&mut CountingSource<'a>> {
+        match self {
+            Decoder::Stored { .. } => None,
+            #[cfg(feature = "deflate-flate2")]
+            Decoder::Deflated(r) => Some(r.get_mut()),
+            #[cfg(feature = "deflate64")]
+            Decoder::Deflate64(r) => Some(r.get_mut()),
+            #[cfg(feature = "bzip2")]
+            Decoder::Bzip2(r) => Some(r.get_mut()),
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(r) => Some(r.get_mut()),
+            #[cfg(feature = "xz")]
+            Decoder::Xz(r) => Some(r.get_mut()),
+            #[cfg(feature = "lzma")]
+            Decoder::Lzma { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Decompresses an entry followed by a data descriptor, then consumes and checks the descriptor.
+pub(crate) struct DataDescriptorReader<'a> {
+    decoder: Decoder<'a>,
+    compressed_size: Arc<AtomicU64>,
+    hasher: Hasher,
+    uncompressed_size: u64,
+    /// Whether the local header has a ZIP64 extra field, meaning the descriptor has 8-byte sizes.
+    zip64: bool,
+    finished: bool,
+}
+
+impl<'a> DataDescriptorReader<'a> {
+    /// `max_buffered` bounds the size of entries that have to be decompressed whole into memory
+    /// before any of them can be read, which is the case for LZMA and, a block at a time, Xz.
+    pub(crate) fn new(
+        source: StreamSource<'a>,
+        compression_method: CompressionMethod,
+        zip64: bool,
+        #[allow(unused_variables)] max_buffered: Option<u64>,
+    ) -> ZipResult<Self> {
+        let compressed_size = Arc::new(AtomicU64::new(0));
+        let source = CountingSource {
+            inner: source,
+            count: compressed_size.clone(),
+        };
+        let decoder = match compression_method {
+            CompressionMethod::Stored => Decoder::Stored {
+                source,
+                window: Vec::with_capacity(ZIP64_DESCRIPTOR_LEN),
+            },
+            #[cfg(feature = "deflate-flate2")]
+            CompressionMethod::Deflated => Decoder::Deflated(DeflateDecoder::new(source)),
+            #[cfg(feature = "deflate64")]
+            CompressionMethod::Deflate64 => {
+                Decoder::Deflate64(Deflate64Decoder::with_buffer(source))
+            }
+            #[cfg(feature = "bzip2")]
+            CompressionMethod::Bzip2 => Decoder::Bzip2(BzDecoder::new(source)),
+            #[cfg(feature = "zstd")]
+            CompressionMethod::Zstd => {
+                Decoder::Zstd(ZstdDecoder::with_buffer(source)?.single_frame())
+            }
+            #[cfg(feature = "xz")]
+            CompressionMethod::Xz => {
+                Decoder::Xz(XzDecoder::new(source, max_buffered.unwrap_or(u64::MAX)))
+            }
+            #[cfg(feature = "lzma")]
+            CompressionMethod::Lzma => Decoder::Lzma {
+                source,
+                output: None,
+                max_size: max_buffered,
+            },
+            _ => {
+                return Err(ZipError::UnsupportedArchive(
+                    "Compression method not supported for entries with a data descriptor",
+                ))
+            }
+        };
+        Ok(DataDescriptorReader {
+            decoder,
+            compressed_size,
+            hasher: Hasher::new(),
+            uncompressed_size: 0,
+            zip64,
+            finished: false,
+        })
+    }
+
+    /// The compressed bytes consumed so far, which keeps counting as the entry is read.
+    pub(crate) fn compressed_size(&self) -> Arc<AtomicU64> {
+        self.compressed_size.clone()
+    }
+
+    /// Reads the descriptor following compressed data, whose signature is optional.
+    fn finish_compressed(&mut self) -> io::Result<()> {
+        let Some(source) = self.decoder.compressed_source() else {
+            return Ok(());
+        };
+        let compressed_size = source.count();
+        let mut crc32 = source.read_u32_le()?;
+        if crc32.to_le_bytes() == SIGNATURE {
+            crc32 = source.read_u32_le()?;
+        }
+        let (compressed, uncompressed) = if self.zip64 {
+            (source.read_u64_le()?, source.read_u64_le()?)
+        } else {
+            (source.read_u32_le()?.into(), source.read_u32_le()?.into())
+        };
+        if crc32 != self.hasher.clone().finalize() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid checksum",
+            ));
+        }
+        if compressed != compressed_size || uncompressed != self.uncompressed_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Data descriptor sizes don't match the entry",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `window` is a descriptor (with signature) for `uncompressed_size` stored bytes with
+/// the checksum `crc32`.
+fn is_stored_descriptor(window: &[u8], zip64: bool, crc32: u32, uncompressed_size: u64) -> bool {
+    let descriptor_len = if zip64 {
+        ZIP64_DESCRIPTOR_LEN
+    } else {
+        DESCRIPTOR_LEN
+    };
+    if window.len() != descriptor_len || window[..4] != SIGNATURE {
+        return false;
+    }
+    let mut fields = &window[4..];
+    let Ok(descriptor_crc32) = fields.read_u32_le() else {
+        return false;
+    };
+    let sizes = if zip64 {
+        (fields.read_u64_le(), fields.read_u64_le())
+    } else {
+        (
+            fields.read_u32_le().map(u64::from),
+            fields.read_u32_le().map(u64::from),
+        )
+    };
+    descriptor_crc32 == crc32 && matches!(sizes, (Ok(c), Ok(u)) if c == uncompressed_size && u == c)
+}
+
+/// Reads stored data up to the descriptor, which is recognized by matching what came before it.
+/// Returns 0 once the descriptor has been consumed.
+fn read_stored(
+    source: &mut CountingSource<'_>,
+    window: &mut Vec<u8>,
+    buf: &mut [u8],
+    zip64: bool,
+    is_descriptor: impl FnOnce(&[u8]) -> bool,
+) -> io::Result<usize> {
+    let descriptor_len = if zip64 {
+        ZIP64_DESCRIPTOR_LEN
+    } else {
+        DESCRIPTOR_LEN
+    };
+    if window.is_empty() {
+        let available = source.fill_buf()?;
+        if available.is_empty() {
+            return Err(missing_descriptor());
+        }
+        let data_len = memchr::memchr(SIGNATURE[0], available).unwrap_or(available.len());
+        if data_len > 0 {
+            let count = data_len.min(buf.len());
+            buf[..count].copy_from_slice(&available[..count]);
+            source.consume(count);
+            return Ok(count);
+        }
+        window.push(available[0]);
+        source.consume(1);
+    }
+    // Pull in just enough to tell whether the window holds the descriptor.
+    while window.len() < descriptor_len && SIGNATURE.starts_with(&window[..window.len().min(4)]) {
+        let available = source.fill_buf()?;
+        if available.is_empty() {
+            return Err(missing_descriptor());
+        }
+        let count = available.len().min(descriptor_len - window.len());
+        window.extend_from_slice(&available[..count]);
+        source.consume(count);
+    }
+    if is_descriptor(window) {
+        window.clear();
+        return Ok(0);
+    }
+    // Not a descriptor, so at least the first byte is data; keep the rest for next time.
+    let data_len = memchr::memchr(SIGNATURE[0], &window[1..]).map_or(window.len(), |i| i + 1);
+    let count = data_len.min(buf.len());
+    buf[..count].copy_from_slice(&window[..count]);
+    window.drain(..count);
+    Ok(count)
+}
+
+fn missing_descriptor() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "Stream ended before the data descriptor",
+    )
+}
+
+impl Read for DataDescriptorReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.finished || buf.is_empty() {
+            return Ok(0);
+        }
+        let count = match &mut self.decoder {
+            Decoder::Stored { source, window } => {
+                let (zip64, hasher, uncompressed_size) =
+                    (self.zip64, &self.hasher, self.uncompressed_size);
+                read_stored(source, window, buf, zip64, |window| {
+                    is_stored_descriptor(
+                        window,
+                        zip64,
+                        hasher.clone().finalize(),
+                        uncompressed_size,
+                    )
+                })?
+            }
+            #[cfg(feature = "deflate-flate2")]
+            Decoder::Deflated(r) => r.read(buf)?,
+            #[cfg(feature = "deflate64")]
+            Decoder::Deflate64(r) => r.read(buf)?,
+            #[cfg(feature = "bzip2")]
+            Decoder::Bzip2(r) => r.read(buf)?,
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(r) => r.read(buf)?,
+            #[cfg(feature = "xz")]
+            Decoder::Xz(r) => r.read(buf)?,
+            #[cfg(feature = "lzma")]
+            Decoder::Lzma {
+                source,
+                output,
+                max_size,
+            } => {
+                if output.is_none() {
+                    *output = Some(io::Cursor::new(decompress_lzma(source, *max_size)?));
+                }
+                output.as_mut().unwrap().read(buf)?
+            }
+        };
+        if count > 0 {
+            self.hasher.update(&buf[..count]);
+            self.uncompressed_size += count as u64;
+        } else {
+            self.finish_compressed()?;
+            self.finished = true;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(feature = "lzma")]
+fn decompress_lzma(source: &mut CountingSource<'_>, max_size: Option<u64>) -> io::Result<Vec<u8>> {
+    use lzma_rs::decompress::{Options, UnpackedSize};
+
+    // The LZMA SDK version, then the size of the properties that start the stream proper.
+    let _version = source.read_u16_le()?;
+    let properties_size = source.read_u16_le()?;
+    if properties_size != 5 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Unexpected LZMA properties size",
+        ));
+    }
+    // Without the size up front, the stream has to end with an end-of-stream marker.
+    let options = Options {
+        unpacked_size: UnpackedSize::UseProvided(None),
+        memlimit: None,
+        allow_incomplete: false,
+    };
+    let mut output = BoundedOutput {
+        data: Vec::new(),
+        max_size,
+    };
+    match lzma_rs::lzma_decompress_with_options(
+        &mut WithoutLookahead(source),
+        &mut output,
+        &options,
+    ) {
+        Ok(()) => Ok(output.data),
+        // Keep errors from the output as they are, so that a breached limit can be told apart.
+        Err(lzma_rs::error::Error::IoError(e)) => Err(e),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Collects decompressed data, failing once there's more than `max_size` of it.
+#[cfg(feature = "lzma")]
+struct BoundedOutput {
+    data: Vec<u8>,
+    max_size: Option<u64>,
+}
+
+#[cfg(feature = "lzma")]
+impl io::Write for BoundedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.max_size {
+            Some(max) if (self.data.len() + buf.len()) as u64 > max => {
+                Err(ZipError::LimitExceeded("Total uncompressed size too large").into())
+            }
+            _ => {
+                self.data.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hides what follows the LZMA stream from lzma-rs, which reads its input through [`Read`] and
+/// only looks ahead to check that nothing follows the end-of-stream marker; here, the descriptor
+/// does.
+#[cfg(feature = "lzma")]
+struct WithoutLookahead<'s, 'a>(&'s mut CountingSource<'a>);
+
+#[cfg(feature = "lzma")]
+impl Read for WithoutLookahead<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+#[cfg(feature = "lzma")]
+impl BufRead for WithoutLookahead<'_, '_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(&[])
+    }
+
+    fn consume(&mut self, _amt: usize) {}
+}
+
+/// Whether a local header's extra field includes the ZIP64 extended information field, in which
+/// case the data descriptor's sizes are 8 bytes long (APPNOTE 4.3.9.2).
+pub(crate) fn has_zip64_extra_field(mut extra_field: &[u8]) -> bool {
+    while let (Ok(kind), Ok(len)) = (extra_field.read_u16_le(), extra_field.read_u16_le()) {
+        if kind == 0x0001 {
+            return true;
+        }
+        let Some(rest) = extra_field.get(len as usize..) else {
+            break;
+        };
+        extra_field = rest;
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::read_zipfile_from_stream;
+    use crate::read::stream::{ZipStreamFileMetadata, ZipStreamReader, ZipStreamVisitor};
+    use crate::result::{ZipError, ZipResult};
+    use crate::unstable::LittleEndianWriteExt;
+    use crate::write::SimpleFileOptions;
+    use crate::{CompressionMethod, ZipWriter};
+    use std::io::{Cursor, Read, Write};
+
+    /// Data that starts with a descriptor signature which doesn't match what precedes it.
+    const TRICKY_DATA: &[u8] =
+        b"PPK\x07\x08PK\x07\x08\x00\x00\x00\x00\x05\x00\x00\x00\x05\x00\x00\x00PK";
+
+    fn read_all_from_stream(mut reader: impl Read) -> ZipResult<Vec<(String, Vec<u8>)>> {
+        let mut entries = Vec::new();
+        while let Some(mut file) = read_zipfile_from_stream(&mut reader)? {
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            entries.push((file.name().to_owned(), data));
+        }
+        Ok(entries)
+    }
+
+    fn stream_archive(method: CompressionMethod, large_file: bool) -> ZipResult<Vec<u8>> {
+        let options = SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(large_file);
+        let mut writer = ZipWriter::new_stream(Vec::new());
+        writer.start_file("tricky", options)?;
+        writer.write_all(TRICKY_DATA)?;
+        writer.start_file("empty", options)?;
+        writer.add_directory("dir/", SimpleFileOptions::default())?;
+        writer.start_file("dir/hello", options)?;
+        writer.write_all(b"hello")?;
+        Ok(writer.finish()?.into_inner())
+    }
+
+    /// A single entry whose local header has bit 3 set and no sizes, followed by its descriptor
+    /// and the start of a central directory.
+    fn entry_with_descriptor(method: u16, data: &[u8], crc32: u32, size: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.write_u32_le(0x04034b50).unwrap();
+        bytes.write_u16_le(20).unwrap();
+        bytes.write_u16_le(1 << 3).unwrap();
+        bytes.write_u16_le(method).unwrap();
+        bytes.write_u16_le(0).unwrap();
+        bytes.write_u16_le(0x21).unwrap();
+        bytes.write_all(&[0; 12]).unwrap();
+        bytes.write_u16_le(4).unwrap();
+        bytes.write_u16_le(0).unwrap();
+        bytes.write_all(b"data").unwrap();
+        bytes.write_all(data).unwrap();
+        bytes.write_u32_le(0x08074b50).unwrap();
+        bytes.write_u32_le(crc32).unwrap();
+        bytes.write_u32_le(data.len() as u32).unwrap();
+        bytes.write_u32_le(size as u32).unwrap();
+        bytes.write_u32_le(0x02014b50).unwrap();
+        bytes.write_all(&[0; 42]).unwrap();
+        bytes
+    }
+
+    fn check_stream_archive(bytes: &[u8]) -> ZipResult<()> {
+        let expected = [
+            ("tricky".to_owned(), TRICKY_DATA.to_vec()),
+            ("empty".to_owned(), vec![]),
+            ("dir/".to_owned(), vec![]),
+            ("dir/hello".to_owned(), b"hello".to_vec()),
+        ];
+        // Unbuffered, so the reader must not consume anything past each entry.
+        assert_eq!(read_all_from_stream(bytes)?, expected);
+        assert_eq!(read_all_from_stream(Cursor::new(bytes))?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn stored_stream() -> ZipResult<()> {
+        check_stream_archive(&stream_archive(CompressionMethod::Stored, false)?)
+    }
+
+    #[test]
+    fn stored_stream_zip64_descriptor() -> ZipResult<()> {
+        check_stream_archive(&stream_archive(CompressionMethod::Stored, true)?)
+    }
+
+    #[test]
+    #[cfg(feature = "deflate-flate2")]
+    fn deflated_stream() -> ZipResult<()> {
+        check_stream_archive(&stream_archive(CompressionMethod::Deflated, false)?)?;
+        check_stream_archive(&stream_archive(CompressionMethod::Deflated, true)?)
+    }
+
+    #[test]
+    #[cfg(feature = "bzip2")]
+    fn bzip2_stream() -> ZipResult<()> {
+        check_stream_archive(&stream_archive(CompressionMethod::Bzip2, false)?)
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn zstd_stream() -> ZipResult<()> {
+        check_stream_archive(&stream_archive(CompressionMethod::Zstd, false)?)
+    }
+
+    #[test]
+    fn zip_stream_reader_visits_descriptor_entries() -> ZipResult<()> {
+        #[derive(Default)]
+        struct V(Vec<(String, Vec<u8>)>);
+        impl ZipStreamVisitor for V {
+            fn visit_file(&mut self, file: &mut crate::read::ZipFile<'_>) -> ZipResult<()> {
+                let mut data = Vec::new();
+                file.read_to_end(&mut data)?;
+                self.0.push((file.name().to_owned(), data));
+                Ok(())
+            }
+
+            fn visit_additional_metadata(
+                &mut self,
+                _metadata: &ZipStreamFileMetadata,
+            ) -> ZipResult<()> {
+                Ok(())
+            }
+        }
+
+        let bytes = stream_archive(CompressionMethod::Stored, false)?;
+        let mut visitor = V::default();
+        ZipStreamReader::new(&bytes[..]).visit(&mut visitor)?;
+        assert_eq!(visitor.0.len(), 4);
+        assert_eq!(visitor.0[0].1, TRICKY_DATA);
+        assert_eq!(visitor.0[3].1, b"hello");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "deflate-flate2")]
+    fn corrupt_descriptor_checksum() -> ZipResult<()> {
+        let mut bytes = stream_archive(CompressionMethod::Deflated, false)?;
+        let crc = crc32fast::hash(TRICKY_DATA).to_le_bytes();
+        let position = bytes
+            .windows(4)
+            .position(|window| window == crc)
+            .expect("descriptor should be present");
+        bytes[position] ^= 1;
+        let mut reader = &bytes[..];
+        let mut file = read_zipfile_from_stream(&mut reader)?.unwrap();
+        file.read_to_end(&mut Vec::new()).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn encrypted_descriptor_entry_needs_password() -> ZipResult<()> {
+        let mut writer = ZipWriter::new_stream(Vec::new());
+        let options = SimpleFileOptions::default().with_deprecated_encryption(b"password");
+        writer.start_file("secret", options)?;
+        writer.write_all(b"hello")?;
+        let bytes = writer.finish()?.into_inner();
+        let mut reader = &bytes[..];
+        let result = read_zipfile_from_stream(&mut reader).map(|file| file.is_some());
+        match result {
+            Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED)) => Ok(()),
+            Err(e) => Err(e),
+            Ok(_) => panic!("an encrypted entry was read without a password"),
+        }
+    }
+
+    #[test]
+    fn stored_stream_without_descriptor() {
+        let mut bytes = entry_with_descriptor(0, b"data", crc32fast::hash(b"data"), 4);
+        bytes.truncate(30 + 4 + 4);
+        let mut reader = &bytes[..];
+        let mut file = read_zipfile_from_stream(&mut reader).unwrap().unwrap();
+        file.read_to_end(&mut Vec::new()).unwrap_err();
+    }
+
+    #[test]
+    #[cfg(feature = "_deflate-any")]
+    fn data_descriptor_from_stream() -> ZipResult<()> {
+        let mut reader = &include_bytes!("../../tests/data/data_descriptor.zip")[..];
+        let mut file = read_zipfile_from_stream(&mut reader)?.unwrap();
+        let mut data = String::new();
+        file.read_to_string(&mut data)?;
+        assert_eq!(data, "Hello World\n");
+        drop(file);
+        assert!(read_zipfile_from_stream(&mut reader)?.is_none());
+        Ok(())
+    }
+
+    /// Re-frames the first entry of an archive as one followed by a data descriptor.
+    #[cfg(any(feature = "xz", feature = "deflate64"))]
+    fn reframe_first_entry(archive: &[u8]) -> ZipResult<(Vec<u8>, Vec<u8>)> {
+        let mut archive = crate::ZipArchive::new(Cursor::new(archive))?;
+        let mut raw = Vec::new();
+        let (method, crc32, size) = {
+            let mut file = archive.by_index_raw(0)?;
+            file.read_to_end(&mut raw)?;
+            (file.compression(), file.crc32(), file.size())
+        };
+        let mut expected = Vec::new();
+        archive.by_index(0)?.read_to_end(&mut expected)?;
+        #[allow(deprecated)]
+        let method = method.to_u16();
+        Ok((entry_with_descriptor(method, &raw, crc32, size), expected))
+    }
+
+    #[test]
+    #[cfg(feature = "xz")]
+    fn xz_stream() -> ZipResult<()> {
+        let (bytes, expected) = reframe_first_entry(include_bytes!("../../tests/data/xz.zip"))?;
+        assert_eq!(read_all_from_stream(&bytes[..])?[0].1, expected);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "deflate64")]
+    fn deflate64_stream() -> ZipResult<()> {
+        let (bytes, expected) =
+            reframe_first_entry(include_bytes!("../../tests/data/deflate64.zip"))?;
+        assert_eq!(read_all_from_stream(&bytes[..])?[0].1, expected);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "lzma")]
+    fn lzma_stream() -> ZipResult<()> {
+        use crate::read::read_zipfile_from_buffered_stream;
+
+        let data = b"Hello, LZMA! Hello, LZMA! Hello, LZMA!";
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress(&mut &data[..], &mut compressed)?;
+        // The zip LZMA header is the SDK version and the size of the properties, which are
+        // followed by the stream without its (unknown) size, so it ends with a marker.
+        let mut framed = vec![9, 20, 5, 0];
+        framed.extend_from_slice(&compressed[..5]);
+        framed.extend_from_slice(&compressed[13..]);
+        let bytes = entry_with_descriptor(14, &framed, crc32fast::hash(data), data.len() as u64);
+        assert_eq!(read_all_from_stream(&bytes[..])?[0].1, data);
+
+        // The entry is decompressed whole before any of it is read, so that's where it's bounded.
+        let read = |max_buffered| -> ZipResult<Vec<u8>> {
+            let mut reader = &bytes[..];
+            let mut file = read_zipfile_from_buffered_stream(&mut reader, max_buffered)?.unwrap();
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
+            Ok(contents)
+        };
+        assert_eq!(read(Some(data.len() as u64))?, data);
+        assert!(matches!(
+            read(Some(data.len() as u64 - 1)),
+            Err(ZipError::LimitExceeded(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,814 @@
+//! Extracting a whole archive into a directory.
+
+use super::limits::Budget;
+use super::{ZipArchive, ZipFile};
+use crate::result::{ZipError, ZipResult};
+#[cfg(windows)]
+use crate::spec::is_dir;
+use crate::types::ZipFileData;
+use std::collections::{HashMap, HashSet};
+#[cfg(unix)]
+use std::ffi::OsString;
+use std::fmt::{self, Debug, Formatter};
+use std::fs;
+use std::io::{self, Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+#[cfg(feature = "filetime")]
+pub(crate) use filetime::{
+    set_file_handle_times, set_file_times, set_symlink_file_times, FileTime,
+};
+#[cfg(not(feature = "filetime"))]
+pub(crate) use no_filetime::{
+    set_file_handle_times, set_file_times, set_symlink_file_times, FileTime,
+};
+
+/// What [`ZipArchive::extract_with`] does when a file it would write already exists.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OverwritePolicy {
+    /// Replace the existing file.
+    #[default]
+    Overwrite,
+    /// Fail with an error of kind [`io::ErrorKind::AlreadyExists`].
+    Error,
+    /// Leave the existing file alone, and carry on with the next entry.
+    Skip,
+    /// Replace the existing file only if the entry was modified more recently.
+    KeepNewer,
+}
+
+impl OverwritePolicy {
+    /// Whether an entry last modified at `modified` should be written to `path`.
+    fn replaces(self, path: &Path, modified: Option<SystemTime>) -> ZipResult<bool> {
+        let existing = match fs::symlink_metadata(path) {
+            Ok(existing) => existing,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(true),
+            Err(e) => return Err(e.into()),
+        };
+        match self {
+            OverwritePolicy::Overwrite => Ok(true),
+            OverwritePolicy::Error => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            )
+            .into()),
+            OverwritePolicy::Skip => Ok(false),
+            OverwritePolicy::KeepNewer => Ok(match (existing.modified(), modified) {
+                (Ok(existing), Some(modified)) => existing < modified,
+                _ => true,
+            }),
+        }
+    }
+}
+
+/// What [`ZipArchive::extract_with`] does with a symbolic link whose target is outside the
+/// extraction directory.
+///
+/// Targets are resolved relative to the directory containing the link, following any links
+/// created earlier in the same extraction, as the operating system would. Whatever the policy,
+/// no entry is ever written through a link created by an earlier entry; that's always an error.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SymlinkPolicy {
+    /// Fail with [`ZipError::InvalidArchive`] if the target is absolute or escapes the
+    /// directory. Links that stay inside it are created with the target as stored.
+    #[default]
+    Reject,
+    /// Treat the extraction directory as the root of the filesystem, so that absolute targets
+    /// start from it and `..` can't climb out of it, and create a relative link to the result.
+    Rewrite,
+}
+
+/// The most links followed while resolving a single target, as with `ELOOP` on Linux.
+const MAX_LINKS_FOLLOWED: usize = 40;
+
+/// The symbolic links created so far in an extraction, keyed by their paths relative to the
+/// extraction directory, with the targets they were created with.
+#[derive(Debug, Default)]
+pub(crate) struct Links {
+    targets: HashMap<PathBuf, PathBuf>,
+    /// Paths that a link's target, as created, leaves with `..`. A link created at one of these
+    /// later would change where that target leads, possibly to outside the extraction directory.
+    climbed: HashSet<PathBuf>,
+}
+
+impl Links {
+    /// Whether writing to `path` would replace or go through a link created earlier.
+    pub(crate) fn intercept(&self, path: &Path) -> bool {
+        path.ancestors()
+            .any(|ancestor| self.targets.contains_key(ancestor))
+    }
+
+    /// Checks the target of a link at `path` against `policy`, and returns the target to create
+    /// the link with. The link is then recorded for later entries.
+    pub(crate) fn add(
+        &mut self,
+        path: &Path,
+        target: &Path,
+        policy: SymlinkPolicy,
+    ) -> ZipResult<PathBuf> {
+        if self.climbed.contains(path) {
+            return Err(ZipError::InvalidArchive(
+                "Symlink would change the target of an earlier symlink",
+            ));
+        }
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let mut resolved = parent.to_path_buf();
+        let mut climbed = Vec::new();
+        let clamp = policy == SymlinkPolicy::Rewrite;
+        if !self.resolve(&mut resolved, target, clamp, &mut climbed, 0) {
+            return Err(ZipError::InvalidArchive(
+                "Symlink target is outside the extraction directory",
+            ));
+        }
+        let target = match policy {
+            SymlinkPolicy::Reject => {
+                self.climbed.extend(climbed);
+                target.to_path_buf()
+            }
+            // The rewritten target only climbs out of real directories, which can't become links.
+            SymlinkPolicy::Rewrite => relative_path(parent, &resolved),
+        };
+        self.targets.insert(path.to_path_buf(), target.clone());
+        Ok(target)
+    }
+
+    /// Resolves `target` onto `resolved`, which starts as the directory holding the link, and adds
+    /// the paths it leaves with `..` to `climbed`. Returns false if it leaves the extraction
+    /// directory and `clamp` isn't set, or if too many links are followed.
+    fn resolve(
+        &self,
+        resolved: &mut PathBuf,
+        target: &Path,
+        clamp: bool,
+        climbed: &mut Vec<PathBuf>,
+        followed: usize,
+    ) -> bool {
+        for component in target.components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir => {
+                    if !clamp {
+                        return false;
+                    }
+                    *resolved = PathBuf::new();
+                }
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    climbed.extend(
+                        resolved
+                            .ancestors()
+                            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                            .map(Path::to_path_buf),
+                    );
+                    if !resolved.pop() && !clamp {
+                        return false;
+                    }
+                }
+                Component::Normal(name) => {
+                    resolved.push(name);
+                    if let Some(next) = self.targets.get(resolved.as_path()) {
+                        resolved.pop();
+                        if followed == MAX_LINKS_FOLLOWED
+                            || !self.resolve(resolved, next, clamp, climbed, followed + 1)
+                        {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// The relative path from the directory `from` to `to`, both relative to the same root and free
+/// of `.` and `..`.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut path: PathBuf = from
+        .components()
+        .skip(common)
+        .map(|_| Component::ParentDir)
+        .chain(to.components().skip(common))
+        .collect();
+    if path.as_os_str().is_empty() {
+        path.push(Component::CurDir);
+    }
+    path
+}
+
+type EntryFilter<'a> = dyn FnMut(&ZipFile<'_>) -> bool + 'a;
+
+/// Options for [`ZipArchive::extract_with`].
+///
+/// The defaults match [`ZipArchive::extract`].
+///
+/// ```no_run
+/// use zip::read::{ExtractOptions, OverwritePolicy};
+///
+/// # fn main() -> zip::result::ZipResult<()> {
+/// let mut archive = zip::ZipArchive::new(std::fs::File::open("release.zip")?)?;
+/// let options = ExtractOptions::default()
+///     .overwrite(OverwritePolicy::KeepNewer)
+///     .filter(|file| !file.name().ends_with(".pdb"))
+///     .strip_components(1);
+/// archive.extract_with("out", options)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ExtractOptions<'a> {
+    overwrite: OverwritePolicy,
+    filter: Option<Box<EntryFilter<'a>>>,
+    strip_components: usize,
+    unix_permissions: bool,
+    create_symlinks: bool,
+    symlink_policy: SymlinkPolicy,
+    restore_times: bool,
+}
+
+impl Default for ExtractOptions<'_> {
+    fn default() -> Self {
+        ExtractOptions {
+            overwrite: OverwritePolicy::default(),
+            filter: None,
+            strip_components: 0,
+            unix_permissions: true,
+            create_symlinks: true,
+            symlink_policy: SymlinkPolicy::default(),
+            restore_times: true,
+        }
+    }
+}
+
+impl Debug for ExtractOptions<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractOptions")
+            .field("overwrite", &self.overwrite)
+            .field("filter", &self.filter.as_ref().map(|_| ".."))
+            .field("strip_components", &self.strip_components)
+            .field("unix_permissions", &self.unix_permissions)
+            .field("create_symlinks", &self.create_symlinks)
+            .field("symlink_policy", &self.symlink_policy)
+            .field("restore_times", &self.restore_times)
+            .finish()
+    }
+}
+
+impl<'a> ExtractOptions<'a> {
+    /// Set what to do about files that already exist.
+    ///
+    /// The default is [`OverwritePolicy::Overwrite`].
+    #[must_use]
+    pub const fn overwrite(mut self, policy: OverwritePolicy) -> Self {
+        self.overwrite = policy;
+        self
+    }
+
+    /// Only extract the entries for which `filter` returns true.
+    ///
+    /// The filter sees each entry as it's stored, before [`strip_components`](Self::strip_components)
+    /// is applied, and before it's decrypted or decompressed, so entries it skips needn't be
+    /// readable at all. Reading from it gives the raw compressed data, as with
+    /// [`ZipArchive::by_index_raw`]. Directories that are filtered out are still created if an
+    /// entry inside them is extracted.
+    #[must_use]
+    pub fn filter(mut self, filter: impl FnMut(&ZipFile<'_>) -> bool + 'a) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Remove this many leading components from each entry's path, like `tar --strip-components`.
+    ///
+    /// Entries with no more components than this, such as the top-level directory itself, are
+    /// skipped. The default is 0.
+    #[must_use]
+    pub const fn strip_components(mut self, count: usize) -> Self {
+        self.strip_components = count;
+        self
+    }
+
+    /// Set whether to give extracted files the unix permissions stored in the archive.
+    ///
+    /// This has no effect on other platforms. The default is true.
+    #[must_use]
+    pub const fn unix_permissions(mut self, apply: bool) -> Self {
+        self.unix_permissions = apply;
+        self
+    }
+
+    /// Set whether to create symbolic links, or write them as regular files containing the path
+    /// they point to.
+    ///
+    /// Links are always written as regular files on platforms other than Unix and Windows. The
+    /// default is true.
+    #[must_use]
+    pub const fn create_symlinks(mut self, create: bool) -> Self {
+        self.create_symlinks = create;
+        self
+    }
+
+    /// Set what to do with links that point outside the extraction directory.
+    ///
+    /// The default is [`SymlinkPolicy::Reject`].
+    #[must_use]
+    pub const fn symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlink_policy = policy;
+        self
+    }
+
+    /// Set whether to give extracted files, directories and links the times stored in the
+    /// archive, rather than the time they were extracted.
+    ///
+    /// The modification time comes from the
+    /// [`ExtendedTimestamp`](crate::extra_fields::ExtendedTimestamp) field if there is one, or
+    /// else from the MS-DOS time, read as UTC. The access time also comes from that field if it's
+    /// there, which it usually is only in local headers, and is otherwise the same as the
+    /// modification time. The default is true.
+    ///
+    /// Times are only restored with the `filetime` feature, which is enabled by default.
+    #[must_use]
+    pub const fn restore_times(mut self, restore: bool) -> Self {
+        self.restore_times = restore;
+        self
+    }
+}
+
+/// The access and modification times to give an extracted entry, if it has any.
+#[cfg(feature = "filetime")]
+pub(crate) fn entry_times(data: &ZipFileData) -> Option<(FileTime, FileTime)> {
+    let modified = FileTime::from_system_time(data.modified_time()?);
+    let accessed = data
+        .accessed_time()
+        .map_or(modified, FileTime::from_system_time);
+    Some((accessed, modified))
+}
+
+/// Times are never restored without the `filetime` feature.
+#[cfg(not(feature = "filetime"))]
+pub(crate) fn entry_times(_data: &ZipFileData) -> Option<(FileTime, FileTime)> {
+    None
+}
+
+/// Stand-ins for the parts of `filetime` used here, so that extraction code needn't care whether
+/// the feature is enabled. No [`FileTime`] can be made, so none of them are ever called.
+#[cfg(not(feature = "filetime"))]
+mod no_filetime {
+    use std::fs::File;
+    use std::io;
+    use std::path::Path;
+
+    #[derive(Clone, Copy)]
+    pub(crate) enum FileTime {}
+
+    pub(crate) fn set_file_handle_times(
+        _file: &File,
+        _accessed: Option<FileTime>,
+        _modified: Option<FileTime>,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub(crate) fn set_file_times(
+        _path: impl AsRef<Path>,
+        accessed: FileTime,
+        _modified: FileTime,
+    ) -> io::Result<()> {
+        match accessed {}
+    }
+
+    pub(crate) fn set_symlink_file_times(
+        _path: impl AsRef<Path>,
+        accessed: FileTime,
+        _modified: FileTime,
+    ) -> io::Result<()> {
+        match accessed {}
+    }
+}
+
+/// Removes `count` leading components from a path, after resolving any `..` in it.
+fn strip_components(path: PathBuf, count: usize) -> Option<PathBuf> {
+    if count == 0 {
+        return Some(path);
+    }
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name),
+            Component::ParentDir => {
+                components.pop();
+            }
+            _ => {}
+        }
+    }
+    if components.len() <= count {
+        return None;
+    }
+    Some(components[count..].iter().collect())
+}
+
+impl<R: Read + Seek> ZipArchive<R> {
+    /// Extract a Zip archive into a directory, overwriting files if they
+    /// already exist. Paths are sanitized with [`ZipFile::enclosed_name`].
+    ///
+    /// Extraction is not atomic. If an error is encountered, some of the files
+    /// may be left on disk. However, on Unix targets, no newly-created directories with part but
+    /// not all of their contents extracted will be readable, writable or usable as process working
+    /// directories by any non-root user except you.
+    ///
+    /// On Unix and Windows, symbolic links are extracted correctly, and extraction fails if one
+    /// would point outside the directory or if a later entry would be written through one. On
+    /// other platforms such as WebAssembly, symbolic links aren't supported, so they're extracted
+    /// as normal files containing the target path in UTF-8.
+    ///
+    /// Files, directories and links are given the modification times stored in the archive, as
+    /// described for [`ExtractOptions::restore_times`].
+    ///
+    /// The [`Limits`](super::Limits) this archive was read with are enforced as each entry is
+    /// decompressed.
+    pub fn extract<P: AsRef<Path>>(&mut self, directory: P) -> ZipResult<()> {
+        self.extract_with(directory, ExtractOptions::default())
+    }
+
+    /// Extract a Zip archive into a directory, as [`extract`](Self::extract) does, with the
+    /// given options.
+    pub fn extract_with<P: AsRef<Path>>(
+        &mut self,
+        directory: P,
+        mut options: ExtractOptions<'_>,
+    ) -> ZipResult<()> {
+        let directory = directory.as_ref();
+        let budget = Budget::new(self.shared.limits);
+        #[cfg(unix)]
+        let mut files_by_unix_mode = Vec::new();
+        let mut dirs_with_times = Vec::new();
+        let mut links = Links::default();
+        for i in 0..self.len() {
+            if let Some(filter) = &mut options.filter {
+                if !filter(&self.by_index_raw(i)?) {
+                    continue;
+                }
+            }
+            let mut file = self.by_index(i)?;
+            budget.limits().check_name(file.name())?;
+            let filepath = file
+                .enclosed_name()
+                .ok_or(ZipError::InvalidArchive("Invalid file path"))?;
+            let Some(filepath) = strip_components(filepath, options.strip_components) else {
+                continue;
+            };
+            if links.intercept(&filepath) {
+                return Err(ZipError::InvalidArchive(
+                    "Entry would be written through a symlink",
+                ));
+            }
+
+            let outpath = directory.join(&filepath);
+            let times = entry_times(&file.data).filter(|_| options.restore_times);
+
+            if file.is_dir() {
+                Self::make_writable_dir_all(&outpath)?;
+                if let Some(times) = times {
+                    dirs_with_times.push((outpath, times));
+                }
+                continue;
+            }
+            if !options
+                .overwrite
+                .replaces(&outpath, file.data.modified_time())?
+            {
+                continue;
+            }
+            let symlink_target =
+                if file.is_symlink() && options.create_symlinks && (cfg!(unix) || cfg!(windows)) {
+                    let mut target = Vec::new();
+                    budget
+                        .reader(&mut file, |file| file.compressed_bytes_read())
+                        .read_to_end(&mut target)?;
+                    Some(target)
+                } else {
+                    None
+                };
+            drop(file);
+            if let Some(p) = outpath.parent() {
+                Self::make_writable_dir_all(p)?;
+            }
+            if let Some(target) = symlink_target {
+                // Links can't be created over anything, so replace whatever the policy allowed.
+                match fs::remove_file(&outpath) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                    _ => {}
+                }
+                #[cfg(unix)]
+                {
+                    use std::os::unix::ffi::OsStringExt;
+                    let target = OsString::from_vec(target);
+                    let target_path =
+                        links.add(&filepath, Path::new(&target), options.symlink_policy)?;
+                    std::os::unix::fs::symlink(target_path, outpath.as_path())?;
+                }
+                #[cfg(windows)]
+                {
+                    let Ok(target) = String::from_utf8(target) else {
+                        return Err(ZipError::InvalidArchive("Invalid UTF-8 as symlink target"));
+                    };
+                    let target = target.into_boxed_str();
+                    let target_is_dir_from_archive =
+                        self.shared.files.contains_key(&target) && is_dir(&target);
+                    let target_path = links.add(
+                        &filepath,
+                        Path::new(target.as_ref()),
+                        options.symlink_policy,
+                    )?;
+                    let target_is_dir = if target_is_dir_from_archive {
+                        true
+                    } else if let Some(meta) = outpath
+                        .parent()
+                        .and_then(|parent| std::fs::metadata(parent.join(&target_path)).ok())
+                    {
+                        meta.is_dir()
+                    } else {
+                        false
+                    };
+                    if target_is_dir {
+                        std::os::windows::fs::symlink_dir(target_path, outpath.as_path())?;
+                    } else {
+                        std::os::windows::fs::symlink_file(target_path, outpath.as_path())?;
+                    }
+                }
+                if let Some((accessed, modified)) = times {
+                    set_symlink_file_times(&outpath, accessed, modified)?;
+                }
+                continue;
+            }
+            let mut file = self.by_index(i)?;
+            let mut outfile = fs::File::create(&outpath)?;
+            io::copy(
+                &mut budget.reader(&mut file, |file| file.compressed_bytes_read()),
+                &mut outfile,
+            )?;
+            if let Some((accessed, modified)) = times {
+                set_file_handle_times(&outfile, Some(accessed), Some(modified))?;
+            }
+            #[cfg(unix)]
+            {
+                // Check for real permissions, which we'll set in a second pass
+                if let Some(mode) = file.unix_mode().filter(|_| options.unix_permissions) {
+                    files_by_unix_mode.push((outpath.clone(), mode));
+                }
+            }
+        }
+        #[cfg(unix)]
+        {
+            use std::cmp::Reverse;
+            use std::os::unix::fs::PermissionsExt;
+
+            if files_by_unix_mode.len() > 1 {
+                // Ensure we update children's permissions before making a parent unwritable
+                files_by_unix_mode.sort_by_key(|(path, _)| Reverse(path.clone()));
+            }
+            for (path, mode) in files_by_unix_mode.into_iter() {
+                fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+            }
+        }
+        // Creating anything in a directory changes its times, so they're set last, deepest first.
+        dirs_with_times.sort_by(|(a, _), (b, _)| b.cmp(a));
+        for (path, (accessed, modified)) in dirs_with_times {
+            set_file_times(path, accessed, modified)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ExtractOptions, OverwritePolicy, SymlinkPolicy};
+    use crate::result::{ZipError, ZipResult};
+    use crate::write::SimpleFileOptions;
+    use crate::{DateTime, ZipArchive, ZipWriter};
+    use std::fs;
+    use std::io::{self, Cursor, Write};
+    use std::path::Path;
+    use tempdir::TempDir;
+
+    fn archive(files: &[(&str, SimpleFileOptions)]) -> ZipResult<ZipArchive<Cursor<Vec<u8>>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, options) in files {
+            if name.ends_with('/') {
+                writer.add_directory(*name, *options)?;
+            } else {
+                writer.start_file(*name, *options)?;
+                writer.write_all(name.as_bytes())?;
+            }
+        }
+        writer.finish_into_readable()
+    }
+
+    #[test]
+    fn overwrite_policies() -> ZipResult<()> {
+        let options = SimpleFileOptions::default();
+        let newer =
+            options.last_modified_time(DateTime::from_date_and_time(2107, 12, 31, 23, 59, 58)?);
+        let mut archive = archive(&[("old", options), ("new", newer)])?;
+        let tempdir = TempDir::new("overwrite_policies")?;
+        let changed = |name: &str| fs::write(tempdir.path().join(name), "changed");
+        let read = |name: &str| fs::read_to_string(tempdir.path().join(name)).unwrap();
+        archive.extract(&tempdir)?;
+
+        changed("old")?;
+        changed("new")?;
+        let error = archive
+            .extract_with(
+                &tempdir,
+                ExtractOptions::default().overwrite(OverwritePolicy::Error),
+            )
+            .unwrap_err();
+        assert!(matches!(error, ZipError::Io(e) if e.kind() == io::ErrorKind::AlreadyExists));
+        archive.extract_with(
+            &tempdir,
+            ExtractOptions::default().overwrite(OverwritePolicy::Skip),
+        )?;
+        assert_eq!(
+            (read("old"), read("new")),
+            ("changed".into(), "changed".into())
+        );
+        archive.extract_with(
+            &tempdir,
+            ExtractOptions::default().overwrite(OverwritePolicy::KeepNewer),
+        )?;
+        assert_eq!((read("old"), read("new")), ("changed".into(), "new".into()));
+        archive.extract_with(&tempdir, ExtractOptions::default())?;
+        assert_eq!((read("old"), read("new")), ("old".into(), "new".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn filter_and_strip_components() -> ZipResult<()> {
+        let options = SimpleFileOptions::default();
+        let mut archive = archive(&[
+            ("top/", options),
+            ("top/a.txt", options),
+            ("top/sub/b.txt", options),
+            ("top/c.log", options),
+            ("top/../d.txt", options),
+        ])?;
+        let tempdir = TempDir::new("filter_and_strip_components")?;
+        let options = ExtractOptions::default()
+            .filter(|file| !file.name().ends_with(".log"))
+            .strip_components(1);
+        archive.extract_with(&tempdir, options)?;
+        let path = tempdir.path();
+        assert_eq!(fs::read_to_string(path.join("a.txt"))?, "top/a.txt");
+        assert_eq!(fs::read_to_string(path.join("sub/b.txt"))?, "top/sub/b.txt");
+        assert!(!path.join("c.log").exists());
+        assert!(!path.join("top").exists());
+        assert!(!path.join("d.txt").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn filter_skips_unreadable_entries() -> ZipResult<()> {
+        let options = SimpleFileOptions::default();
+        let encrypted = options.with_deprecated_encryption(b"password");
+        let bytes = archive(&[("secret", encrypted), ("plain", options)])?
+            .into_inner()
+            .into_inner();
+        let limits = crate::read::Limits::default().with_max_name_length(5);
+        let mut archive = ZipArchive::with_limits(Default::default(), limits, Cursor::new(bytes))?;
+        let tempdir = TempDir::new("filter_skips_unreadable_entries")?;
+        let options = ExtractOptions::default().filter(|file| file.name() == "plain");
+        archive.extract_with(&tempdir, options)?;
+        assert_eq!(fs::read_to_string(tempdir.path().join("plain"))?, "plain");
+        assert!(!tempdir.path().join("secret").exists());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn without_unix_permissions() -> ZipResult<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let options = SimpleFileOptions::default().unix_permissions(0o444);
+        let mut archive = archive(&[("read-only", options)])?;
+        let tempdir = TempDir::new("without_unix_permissions")?;
+        archive.extract_with(&tempdir, ExtractOptions::default().unix_permissions(false))?;
+        let mode = fs::metadata(tempdir.path().join("read-only"))?
+            .permissions()
+            .mode();
+        assert_ne!(mode & 0o200, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn symlinks_as_files() -> ZipResult<()> {
+        let mut archive = ZipArchive::new(Cursor::new(
+            &include_bytes!("../../tests/data/symlink.zip")[..],
+        ))?;
+        let tempdir = TempDir::new("symlinks_as_files")?;
+        archive.extract_with(&tempdir, ExtractOptions::default().create_symlinks(false))?;
+        let path = tempdir.path().join("bar");
+        assert!(!path.is_symlink());
+        assert_eq!(fs::read_to_string(path)?, "foo");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "filetime")]
+    fn restore_times() -> ZipResult<()> {
+        let time = DateTime::from_date_and_time(1999, 1, 2, 3, 4, 6)?;
+        let options = SimpleFileOptions::default().last_modified_time(time);
+        let mut archive = archive(&[("dir/", options), ("dir/file", options)])?;
+        let modified = |path: &std::path::Path| fs::metadata(path).unwrap().modified().unwrap();
+
+        let tempdir = TempDir::new("restore_times")?;
+        archive.extract(&tempdir)?;
+        let path = tempdir.path();
+        assert_eq!(modified(&path.join("dir/file")), time.to_system_time());
+        assert_eq!(modified(&path.join("dir")), time.to_system_time());
+
+        let tempdir = TempDir::new("restore_times")?;
+        archive.extract_with(&tempdir, ExtractOptions::default().restore_times(false))?;
+        assert!(modified(&tempdir.path().join("dir/file")) > time.to_system_time());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "filetime")]
+    fn restore_extended_timestamp() -> ZipResult<()> {
+        use std::time::{Duration, SystemTime};
+
+        let mut archive = ZipArchive::new(Cursor::new(
+            &include_bytes!("../../tests/data/extended_timestamp.zip")[..],
+        ))?;
+        let tempdir = TempDir::new("restore_extended_timestamp")?;
+        archive.extract(&tempdir)?;
+        let modified = fs::metadata(tempdir.path().join("test.txt"))?.modified()?;
+        assert_eq!(
+            modified,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1714635025)
+        );
+        Ok(())
+    }
+
+    fn symlinks(links: &[(&str, &str)]) -> ZipResult<ZipArchive<Cursor<Vec<u8>>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, target) in links {
+            writer.add_symlink(*name, *target, SimpleFileOptions::default())?;
+        }
+        writer.finish_into_readable()
+    }
+
+    #[test]
+    fn symlink_policies() -> ZipResult<()> {
+        let contained = [("a/b/root", "../.."), ("a/b/sibling", "../c")];
+        let mut archive = symlinks(&contained)?;
+        let tempdir = TempDir::new("symlink_policies")?;
+        archive.extract(&tempdir)?;
+        for (name, target) in contained {
+            assert_eq!(fs::read_link(tempdir.path().join(name))?, Path::new(target));
+        }
+
+        for (links, rewritten) in [
+            (&[("a/up", "../../outside")][..], "../outside"),
+            (&[("abs", "/etc/passwd")][..], "etc/passwd"),
+            // `a/b/root/..` is the parent of the extraction directory, though it looks like `a/b`.
+            (&[("a/b/root", "../.."), ("a/b/up", "root/..")][..], "../.."),
+            // `b/x/..` would become the parent of the extraction directory once `b` is created.
+            (&[("e/x", ".."), ("a", "b/x/.."), ("b", "e")][..], "e"),
+        ] {
+            let mut archive = symlinks(links)?;
+            let tempdir = TempDir::new("symlink_policies")?;
+            let error = archive.extract(&tempdir).unwrap_err();
+            assert!(matches!(error, ZipError::InvalidArchive(_)));
+
+            let tempdir = TempDir::new("symlink_policies")?;
+            let options = ExtractOptions::default().symlink_policy(SymlinkPolicy::Rewrite);
+            archive.extract_with(&tempdir, options)?;
+            let (name, _) = links[links.len() - 1];
+            assert_eq!(
+                fs::read_link(tempdir.path().join(name))?,
+                Path::new(rewritten)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn nothing_written_through_symlinks() -> ZipResult<()> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.add_directory("real/", SimpleFileOptions::default())?;
+        writer.add_symlink("link", "real", SimpleFileOptions::default())?;
+        writer.start_file("link/file", SimpleFileOptions::default())?;
+        let mut archive = writer.finish_into_readable()?;
+        let tempdir = TempDir::new("nothing_written_through_symlinks")?;
+        let error = archive.extract(&tempdir).unwrap_err();
+        assert!(matches!(error, ZipError::InvalidArchive(_)));
+        assert!(!tempdir.path().join("real/file").exists());
+        Ok(())
+    }
+}
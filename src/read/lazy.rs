@@ -0,0 +1,439 @@
+//! Archives whose central directory is only parsed entry by entry, as entries are looked up.
+
+use super::{
+    central_header_to_zip_file_inner, find_content, no_central_directory, open_entry, password_for,
+    CentralDirectoryInfo, Config, Limits, ZipArchive, ZipFile, ZipFileReader,
+};
+use crate::result::{ZipError, ZipResult};
+use crate::spec::{FixedSizeBlock, Zip32CentralDirectoryEnd};
+use crate::types::{ZipCentralEntryBlock, ZipFileData};
+use std::borrow::Cow;
+use std::collections::hash_map::RandomState;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::BuildHasher;
+use std::io::{Read, Seek, SeekFrom};
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// ZIP archive reader that opens archives with millions of entries quickly, by keeping their
+/// central directory as it's stored and parsing each entry's metadata only when it's read.
+///
+/// Opening an archive reads its central directory in one go, and indexes where each entry's
+/// header starts and a hash table of their names, taking a few bytes per entry on top of the
+/// directory itself. [`ZipArchive`] instead parses every entry and reads its local header up
+/// front, which makes looking through them cheaper once it's open.
+///
+/// Unlike [`ZipArchive`], entries with the same name are all counted, and looking one up by name
+/// finds the last of them. Split archives aren't supported, and only some [`Limits`] apply; see
+/// [`with_limits`](Self::with_limits).
+///
+/// ```no_run
+/// use std::io::prelude::*;
+/// fn print_entry(name: &str) -> zip::result::ZipResult<()> {
+///     let file = std::fs::File::open("dataset.zip")?;
+///     let mut zip = zip::read::LazyZipArchive::new(file)?;
+///     println!("{} files", zip.len());
+///     std::io::copy(&mut zip.by_name(name)?, &mut std::io::stdout())?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct LazyZipArchive<R> {
+    reader: R,
+    directory: Arc<LazyDirectory>,
+    comment: Arc<[u8]>,
+    limits: Limits,
+    /// The metadata of the entry being read, which it borrows.
+    current: Option<ZipFileData>,
+}
+
+impl<R> Debug for LazyZipArchive<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyZipArchive")
+            .field("len", &self.directory.offsets.len())
+            .field("dir_start", &self.directory.dir_start)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The central directory of an archive, and an index of its headers.
+struct LazyDirectory {
+    /// The central directory, exactly as it's stored.
+    bytes: Box<[u8]>,
+    /// Where each entry's header starts in `bytes`.
+    offsets: Box<[u32]>,
+    names: NameTable,
+    archive_offset: u64,
+    dir_start: u64,
+}
+
+/// Open-addressing hash table of entry indices, keyed by the names of the entries.
+struct NameTable {
+    hasher: RandomState,
+    /// An index plus one in each used slot, and 0 in the others. The length is a power of two.
+    slots: Box<[u32]>,
+}
+
+impl NameTable {
+    fn with_capacity(entries: usize) -> Self {
+        NameTable {
+            hasher: RandomState::new(),
+            // At most two thirds full, so that probe sequences stay short
+            slots: vec![0; (entries + entries / 2).max(1).next_power_of_two()].into_boxed_slice(),
+        }
+    }
+
+    /// Finds the slot holding the entry called `name`, according to `is_named`, or the empty slot
+    /// where it would go.
+    fn find(&self, name: &str, mut is_named: impl FnMut(usize) -> bool) -> usize {
+        let mask = self.slots.len() - 1;
+        let mut slot = self.hasher.hash_one(name.as_bytes()) as usize & mask;
+        loop {
+            match self.slots[slot] {
+                0 => return slot,
+                index if is_named(index as usize - 1) => return slot,
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+}
+
+/// Whether an extra field holds an Info-ZIP Unicode Path field, which replaces the entry's name.
+fn has_unicode_path(mut extra_field: &[u8]) -> bool {
+    while let [kind_low, kind_high, len_low, len_high, rest @ ..] = extra_field {
+        if u16::from_le_bytes([*kind_low, *kind_high]) == 0x7075 {
+            return true;
+        }
+        let len = u16::from_le_bytes([*len_low, *len_high]) as usize;
+        extra_field = rest.get(len..).unwrap_or_default();
+    }
+    false
+}
+
+impl LazyDirectory {
+    /// Reads the central directory that `dir_info` describes, and indexes its headers.
+    fn read<R: Read + Seek>(
+        dir_info: CentralDirectoryInfo,
+        limits: Limits,
+        reader: &mut R,
+    ) -> ZipResult<Self> {
+        if dir_info.disk_number != dir_info.disk_with_central_directory {
+            return Err(ZipError::UnsupportedArchive(
+                "Multi-disk files can only be read with ZipArchive::open_split",
+            ));
+        }
+        limits.check_entry_count(dir_info.number_of_files)?;
+        if dir_info.number_of_files >= u32::MAX as usize {
+            return Err(ZipError::UnsupportedArchive(
+                "Too many entries to index lazily",
+            ));
+        }
+        // The directory runs up to the end records, even if its recorded size says otherwise
+        let len = dir_info
+            .cde_position
+            .saturating_sub(dir_info.directory_start)
+            .max(dir_info.directory_size);
+        reader.seek(SeekFrom::Start(dir_info.directory_start))?;
+        let mut bytes = Vec::new();
+        reader.take(len).read_to_end(&mut bytes)?;
+
+        let header_len = size_of::<ZipCentralEntryBlock>();
+        let mut offsets =
+            Vec::with_capacity(dir_info.number_of_files.min(bytes.len() / header_len));
+        let mut position = 0;
+        for _ in 0..dir_info.number_of_files {
+            let block = bytes
+                .get(position..position + header_len)
+                .ok_or(ZipError::InvalidArchive("Central directory is truncated"))?;
+            let block = ZipCentralEntryBlock::interpret(block)?;
+            offsets.push(u32::try_from(position).map_err(|_| {
+                ZipError::UnsupportedArchive("Central directory is too large to index lazily")
+            })?);
+            position += header_len
+                + block.file_name_length as usize
+                + block.extra_field_length as usize
+                + block.file_comment_length as usize;
+        }
+        if position > bytes.len() {
+            return Err(ZipError::InvalidArchive("Central directory is truncated"));
+        }
+        bytes.truncate(position);
+
+        let mut directory = LazyDirectory {
+            bytes: bytes.into_boxed_slice(),
+            names: NameTable::with_capacity(offsets.len()),
+            offsets: offsets.into_boxed_slice(),
+            archive_offset: dir_info.archive_offset,
+            dir_start: dir_info.directory_start,
+        };
+        for index in 0..directory.offsets.len() {
+            let name = directory.name(index)?;
+            let slot = directory.slot_for_name(&name);
+            // A later entry with the same name replaces an earlier one, as in `ZipArchive`
+            directory.names.slots[slot] = index as u32 + 1;
+        }
+        Ok(directory)
+    }
+
+    /// The fixed-size part of an entry's header, and the bytes that follow it.
+    fn header(&self, index: usize) -> ZipResult<(ZipCentralEntryBlock, &[u8])> {
+        let (block, rest) =
+            self.bytes[self.offsets[index] as usize..].split_at(size_of::<ZipCentralEntryBlock>());
+        Ok((ZipCentralEntryBlock::interpret(block)?, rest))
+    }
+
+    fn entry(&self, index: usize) -> ZipResult<ZipFileData> {
+        let (block, mut rest) = self.header(index)?;
+        let central_header_start = self.dir_start + self.offsets[index] as u64;
+        central_header_to_zip_file_inner(
+            &mut rest,
+            self.archive_offset,
+            central_header_start,
+            block,
+        )
+    }
+
+    /// An entry's metadata, once it's checked to be in the archive.
+    fn entry_to_read(&self, index: usize) -> ZipResult<ZipFileData> {
+        if index >= self.offsets.len() {
+            return Err(ZipError::FileNotFound);
+        }
+        let data = self.entry(index)?;
+        if data.header_start >= data.central_header_start {
+            return Err(ZipError::InvalidArchive(
+                "A file can't start after its central-directory header",
+            ));
+        }
+        Ok(data)
+    }
+
+    /// An entry's name, which usually only needs its header to be located rather than parsed.
+    fn name(&self, index: usize) -> ZipResult<Cow<'_, str>> {
+        let (block, rest) = self.header(index)?;
+        let (name, rest) = rest.split_at(block.file_name_length as usize);
+        let extra_field = &rest[..block.extra_field_length as usize];
+        // Names that aren't UTF-8 are decoded as CP437, which only leaves ASCII as it is
+        let is_utf8 = block.flags & (1 << 11) != 0;
+        match std::str::from_utf8(name) {
+            Ok(name) if (is_utf8 || name.is_ascii()) && !has_unicode_path(extra_field) => {
+                Ok(Cow::Borrowed(name))
+            }
+            _ => Ok(Cow::Owned(self.entry(index)?.file_name.into_string())),
+        }
+    }
+
+    fn slot_for_name(&self, name: &str) -> usize {
+        self.names.find(name, |index| {
+            self.name(index).is_ok_and(|other| other == name)
+        })
+    }
+
+    fn index_for_name(&self, name: &str) -> Option<usize> {
+        match self.names.slots[self.slot_for_name(name)] {
+            0 => None,
+            index => Some(index as usize - 1),
+        }
+    }
+}
+
+impl<R: Read + Seek> LazyZipArchive<R> {
+    /// Read a ZIP archive's central directory, without parsing its entries yet.
+    ///
+    /// A default [`Config`] is used.
+    pub fn new(reader: R) -> ZipResult<Self> {
+        Self::with_config(Default::default(), reader)
+    }
+
+    /// Read a ZIP archive's central directory with the given configuration, without parsing its
+    /// entries yet.
+    pub fn with_config(config: Config, reader: R) -> ZipResult<Self> {
+        Self::with_limits(config, Limits::default(), reader)
+    }
+
+    /// Read a ZIP archive's central directory with the given configuration, without parsing its
+    /// entries yet, checking its entry count against `limits`.
+    ///
+    /// As with [`ZipArchive`], Xz entries larger than [`Limits::max_total_size`] can't be opened,
+    /// since they're decompressed in memory. The other limits are ignored:
+    /// [`Limits::reject_overlapping_entries`] because it means reading every local header, and the
+    /// rest because [`ZipArchive`] only checks them when extracting, which this type doesn't do.
+    pub fn with_limits(config: Config, limits: Limits, mut reader: R) -> ZipResult<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let (footer, directory) =
+            Self::read_directory(config, limits, &mut reader).map_err(no_central_directory)?;
+        Ok(LazyZipArchive {
+            reader,
+            directory: Arc::new(directory),
+            comment: footer.zip_file_comment.into(),
+            limits,
+            current: None,
+        })
+    }
+
+    fn read_directory(
+        config: Config,
+        limits: Limits,
+        reader: &mut R,
+    ) -> ZipResult<(Zip32CentralDirectoryEnd, LazyDirectory)> {
+        let cde_locations = Zip32CentralDirectoryEnd::find_and_parse(reader)?;
+        ZipArchive::<R>::select_central_directory(
+            config,
+            reader,
+            &[],
+            cde_locations,
+            |dir_info, reader| LazyDirectory::read(dir_info, limits, reader),
+            |directory| directory.offsets.len(),
+            &mut Vec::new(),
+        )
+    }
+
+    /// Number of files contained in this zip, counting every entry with the same name.
+    pub fn len(&self) -> usize {
+        self.directory.offsets.len()
+    }
+
+    /// Whether this zip archive contains no files
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the comment of the zip archive.
+    pub fn comment(&self) -> &[u8] {
+        &self.comment
+    }
+
+    /// Get the index of a file entry by name, if it's present. With several entries of that name,
+    /// this is the last one.
+    pub fn index_for_name(&self, name: &str) -> Option<usize> {
+        self.directory.index_for_name(name)
+    }
+
+    /// Get the name of a file entry, if it's present.
+    pub fn name_for_index(&self, index: usize) -> Option<Cow<'_, str>> {
+        if index >= self.len() {
+            return None;
+        }
+        self.directory.name(index).ok()
+    }
+
+    /// Search for a file entry by name
+    pub fn by_name(&mut self, name: &str) -> ZipResult<ZipFile<'_>> {
+        let index = self.index_for_name(name).ok_or(ZipError::FileNotFound)?;
+        self.by_index_with_optional_password(index, None)
+    }
+
+    /// Search for a file entry by name, decrypt with given password. See
+    /// [`ZipArchive::by_name_decrypt`] for the weaknesses of the encryption.
+    pub fn by_name_decrypt(&mut self, name: &str, password: &[u8]) -> ZipResult<ZipFile<'_>> {
+        let index = self.index_for_name(name).ok_or(ZipError::FileNotFound)?;
+        self.by_index_with_optional_password(index, Some(password))
+    }
+
+    /// Get a contained file by index
+    pub fn by_index(&mut self, file_number: usize) -> ZipResult<ZipFile<'_>> {
+        self.by_index_with_optional_password(file_number, None)
+    }
+
+    /// Get a contained file by index, decrypt with given password. See
+    /// [`ZipArchive::by_index_decrypt`] for the weaknesses of the encryption.
+    pub fn by_index_decrypt(
+        &mut self,
+        file_number: usize,
+        password: &[u8],
+    ) -> ZipResult<ZipFile<'_>> {
+        self.by_index_with_optional_password(file_number, Some(password))
+    }
+
+    /// Get a contained file by index without decompressing it
+    pub fn by_index_raw(&mut self, file_number: usize) -> ZipResult<ZipFile<'_>> {
+        let data = self
+            .current
+            .insert(self.directory.entry_to_read(file_number)?);
+        Ok(ZipFile {
+            crypto_reader: None,
+            reader: ZipFileReader::Raw(find_content(data, &mut self.reader)?),
+            data: Cow::Borrowed(data),
+        })
+    }
+
+    fn by_index_with_optional_password(
+        &mut self,
+        file_number: usize,
+        password: Option<&[u8]>,
+    ) -> ZipResult<ZipFile<'_>> {
+        let data = self
+            .current
+            .insert(self.directory.entry_to_read(file_number)?);
+        let password = password_for(data, password)?;
+        let limit_reader = find_content(data, &mut self.reader)?;
+        open_entry(data, limit_reader, password, &self.limits)
+    }
+
+    /// Unwrap and return the inner reader object
+    ///
+    /// The position of the reader is undefined.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LazyZipArchive;
+    use crate::result::{ZipError, ZipResult};
+    use crate::write::SimpleFileOptions;
+    use crate::{ZipArchive, ZipWriter};
+    use std::io::{Cursor, Read, Write};
+
+    #[test]
+    fn same_as_eager() -> ZipResult<()> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..2000 {
+            writer.start_file(format!("dir/{i}.txt"), SimpleFileOptions::default())?;
+            writer.write_all(format!("contents of {i}").as_bytes())?;
+        }
+        writer.set_comment("lazy");
+        let bytes = writer.finish()?.into_inner();
+
+        let mut eager = ZipArchive::new(Cursor::new(&bytes[..]))?;
+        let mut lazy = LazyZipArchive::new(Cursor::new(&bytes[..]))?;
+        assert_eq!(lazy.len(), eager.len());
+        assert_eq!(lazy.comment(), b"lazy");
+        for i in (0..2000).step_by(7) {
+            let name = format!("dir/{i}.txt");
+            assert_eq!(lazy.index_for_name(&name), eager.index_for_name(&name));
+            assert_eq!(lazy.name_for_index(i).as_deref(), Some(&*name));
+            let mut contents = String::new();
+            lazy.by_name(&name)?.read_to_string(&mut contents)?;
+            assert_eq!(contents, format!("contents of {i}"));
+            let lazy_file = lazy.by_index(i)?;
+            let eager_file = eager.by_index(i)?;
+            assert_eq!(lazy_file.name(), eager_file.name());
+            assert_eq!(lazy_file.crc32(), eager_file.crc32());
+            assert_eq!(lazy_file.data_start(), eager_file.data_start());
+        }
+        assert_eq!(lazy.index_for_name("dir/2000.txt"), None);
+        assert!(lazy.name_for_index(2000).is_none());
+        assert!(matches!(lazy.by_index(2000), Err(ZipError::FileNotFound)));
+        Ok(())
+    }
+
+    #[test]
+    fn decoded_names() -> ZipResult<()> {
+        for bytes in [
+            &include_bytes!("../../tests/data/non_utf8.zip")[..],
+            include_bytes!("../../tests/data/chinese.zip"),
+            include_bytes!("../../tests/data/files_and_dirs.zip"),
+        ] {
+            let eager = ZipArchive::new(Cursor::new(bytes))?;
+            let lazy = LazyZipArchive::new(Cursor::new(bytes))?;
+            assert_eq!(lazy.len(), eager.len());
+            for name in eager.file_names() {
+                let index = lazy.index_for_name(name);
+                assert_eq!(index, eager.index_for_name(name));
+                assert_eq!(lazy.name_for_index(index.unwrap()).as_deref(), Some(name));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+//! Enforcement of [`Limits`] during extraction.
+
+use super::Limits;
+use crate::compression::CompressionMethod;
+use crate::result::{ZipError, ZipResult};
+use crate::types::ZipFileData;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub(crate) const UNLIMITED: Limits = Limits {
+    max_total_size: None,
+    max_compression_ratio: None,
+    max_entries: None,
+    max_name_length: None,
+    max_depth: None,
+    reject_overlapping_entries: false,
+};
+
+impl Limits {
+    pub(crate) fn check_entry_count(&self, count: usize) -> ZipResult<()> {
+        match self.max_entries {
+            Some(max) if count > max => Err(ZipError::LimitExceeded("Too many entries")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks `size` decompressed bytes against `compressed_size`. For entries without a data
+    /// descriptor, callers pass the compressed size from the headers, which is trusted as is: an
+    /// archive that overstates it gets a higher ratio past this check. The [`Budget`] each
+    /// extraction counts decompressed bytes against is the real bound on its output.
+    pub(crate) fn check_compression_ratio(&self, size: u64, compressed_size: u64) -> ZipResult<()> {
+        match self.max_compression_ratio {
+            // An entry that decompresses to anything from nothing at all still counts as a byte.
+            Some(max) if size > compressed_size.max(1).saturating_mul(max) => {
+                Err(ZipError::LimitExceeded("Compression ratio too high"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Xz entries are decompressed into memory a block at a time, up to their recorded size, and a
+    /// block may hold the whole entry, so ones larger than the total size allowed are refused
+    /// before anything is read. `compressed_in_memory` is for readers that also fetch all of their
+    /// compressed data at once.
+    pub(crate) fn check_in_memory_entry(
+        &self,
+        data: &ZipFileData,
+        compressed_in_memory: bool,
+    ) -> ZipResult<()> {
+        if data.compression_method != CompressionMethod::XZ {
+            return Ok(());
+        }
+        let mut size = data.uncompressed_size;
+        if compressed_in_memory {
+            size = size.max(data.compressed_size);
+        }
+        match self.max_total_size {
+            Some(max) if size > max => Err(ZipError::LimitExceeded(
+                "Entry too large to decompress in memory",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the length and depth of a name, counting both `/` and `\` as separators.
+    pub(crate) fn check_name(&self, name: &str) -> ZipResult<()> {
+        if self.max_name_length.is_some_and(|max| name.len() > max) {
+            return Err(ZipError::LimitExceeded("Entry name too long"));
+        }
+        if let Some(max) = self.max_depth {
+            let depth = name
+                .split(['/', '\\'])
+                .filter(|component| !component.is_empty() && *component != ".")
+                .count();
+            if depth > max {
+                return Err(ZipError::LimitExceeded("Entry nested too deeply"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The bytes decompressed so far by one extraction, which may be shared between threads.
+#[derive(Debug)]
+pub(crate) struct Budget {
+    limits: Limits,
+    total_size: AtomicU64,
+}
+
+impl Budget {
+    pub(crate) const fn new(limits: Limits) -> Self {
+        Budget {
+            limits,
+            total_size: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) const fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Wraps the decompressed contents of an entry. `compressed_size` gives the compressed bytes
+    /// the reader may have consumed so far, which is only known as it goes for entries with a
+    /// data descriptor.
+    pub(crate) fn reader<R, F>(&self, inner: R, compressed_size: F) -> LimitReader<'_, R, F>
+    where
+        R: Read,
+        F: FnMut(&mut R) -> u64,
+    {
+        LimitReader {
+            inner,
+            budget: self,
+            compressed_size,
+            size: 0,
+        }
+    }
+
+    /// Counts bytes that were copied without decompressing them.
+    pub(crate) fn consume(&self, count: u64) -> ZipResult<()> {
+        let total_size = self
+            .total_size
+            .fetch_add(count, Ordering::Relaxed)
+            .saturating_add(count);
+        match self.limits.max_total_size {
+            Some(max) if total_size > max => {
+                Err(ZipError::LimitExceeded("Total uncompressed size too large"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Counts the bytes read from an entry against a [`Budget`], failing as soon as a limit is
+/// exceeded.
+pub(crate) struct LimitReader<'b, R, F> {
+    inner: R,
+    budget: &'b Budget,
+    compressed_size: F,
+    /// The bytes read from this entry.
+    size: u64,
+}
+
+impl<R, F> Read for LimitReader<'_, R, F>
+where
+    R: Read,
+    F: FnMut(&mut R) -> u64,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.size += count as u64;
+        if self.budget.limits.max_compression_ratio.is_some() {
+            let compressed_size = (self.compressed_size)(&mut self.inner);
+            self.budget
+                .limits
+                .check_compression_ratio(self.size, compressed_size)?;
+        }
+        self.budget.consume(count as u64)?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names() {
+        let limits = Limits {
+            max_name_length: Some(9),
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        assert!(limits.check_name("a/b.txt").is_ok());
+        assert!(limits.check_name("./a//b/").is_ok());
+        assert!(limits.check_name("a\\b\\c").is_err());
+        assert!(limits.check_name("abcdefghij").is_err());
+        assert!(UNLIMITED.check_name("a/b/c/d/e/f/g/h/i/j/k").is_ok());
+    }
+
+    #[test]
+    fn sizes() {
+        let budget = Budget::new(Limits {
+            max_total_size: Some(100),
+            max_compression_ratio: Some(10),
+            ..Default::default()
+        });
+        let mut reader = budget.reader(&[0u8; 60][..], |_| 6);
+        assert_eq!(io::copy(&mut reader, &mut io::sink()).unwrap(), 60);
+        let mut reader = budget.reader(&[0u8; 60][..], |_| 5);
+        let error = ZipError::from(io::copy(&mut reader, &mut io::sink()).unwrap_err());
+        assert!(matches!(error, ZipError::LimitExceeded(_)));
+        let mut reader = budget.reader(&[0u8; 60][..], |_| 60);
+        let error = ZipError::from(io::copy(&mut reader, &mut io::sink()).unwrap_err());
+        assert!(matches!(error, ZipError::LimitExceeded(_)));
+    }
+}
@@ -1,6 +1,6 @@
 use lzma_rs::decompress::{Options, Stream, UnpackedSize};
 use std::collections::VecDeque;
-use std::io::{copy, Error, Read, Result, Write};
+use std::io::{copy, Error, ErrorKind, Read, Result, Write};
 
 const COMPRESSED_BYTES_TO_BUFFER: usize = 4096;
 
@@ -35,7 +35,12 @@ impl<R: Read> Read for LzmaDecoder<R> {
         let mut bytes_read = self.stream.get_output_mut().unwrap().read(buf)?;
         while bytes_read < buf.len() {
             let mut next_compressed = [0u8; COMPRESSED_BYTES_TO_BUFFER];
-            let compressed_bytes_read = self.compressed_reader.read(&mut next_compressed)?;
+            let compressed_bytes_read = match self.compressed_reader.read(&mut next_compressed) {
+                Ok(count) => count,
+                // Return what's been decompressed already; the caller will try again.
+                Err(e) if bytes_read > 0 && e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            };
             if compressed_bytes_read == 0 {
                 break;
             }
@@ -0,0 +1,327 @@
+//! Queries over the names of an archive's entries.
+
+use super::ZipArchive;
+use crate::types::ZipFileData;
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// An entry's name with `/` separators. Some Windows tools write names with `\` instead, which is
+/// taken to be the separator only in names that have no `/`.
+fn with_slashes(name: &str) -> Cow<'_, str> {
+    if name.contains('\\') && !name.contains('/') {
+        Cow::Owned(name.replace('\\', "/"))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Indices of the names in an archive, built the first time they're needed.
+#[derive(Debug)]
+pub(crate) struct NameIndex {
+    /// The index of every entry, sorted by its name with `/` separators.
+    sorted: Box<[usize]>,
+    /// The index of the first entry with each name, lowercased and with `/` separators.
+    folded: HashMap<Box<str>, usize>,
+}
+
+impl NameIndex {
+    fn new(files: &IndexMap<Box<str>, ZipFileData>) -> Self {
+        let name = |index: usize| with_slashes(files.get_index(index).unwrap().0);
+        let mut sorted: Box<[usize]> = (0..files.len()).collect();
+        // Stable, so that entries whose names only differ in their separators stay in order
+        sorted.sort_by(|a, b| name(*a).cmp(&name(*b)));
+        let mut folded = HashMap::with_capacity(files.len());
+        for index in 0..files.len() {
+            folded
+                .entry(name(index).to_lowercase().into_boxed_str())
+                .or_insert(index);
+        }
+        NameIndex { sorted, folded }
+    }
+}
+
+impl<R> ZipArchive<R> {
+    fn name_index(&self) -> &NameIndex {
+        self.shared
+            .name_index
+            .get_or_init(|| NameIndex::new(&self.shared.files))
+    }
+
+    /// Get the indices of the entries whose names start with `prefix`, in the order of the
+    /// central directory.
+    ///
+    /// Names that use `\` as their separator are matched as if they used `/`. The first query
+    /// sorts the names, which later ones on this archive or its clones reuse.
+    pub fn entries_with_prefix(&self, prefix: &str) -> Vec<usize> {
+        let prefix = with_slashes(prefix);
+        let name = |index: usize| with_slashes(self.shared.files.get_index(index).unwrap().0);
+        let sorted = &self.name_index().sorted;
+        let start = sorted.partition_point(|index| *name(*index) < *prefix);
+        let mut found: Vec<usize> = sorted[start..]
+            .iter()
+            .copied()
+            .take_while(|index| name(*index).starts_with(&*prefix))
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// Get the indices of the entries whose names match a glob pattern, such as
+    /// `assets/**/*.png`, in the order of the central directory.
+    ///
+    /// Within a path component, `*` matches any run of characters, `?` matches any one character
+    /// and `[...]` matches one of the characters or ranges like `a-z` it lists, or any other with
+    /// `[!...]`. A component that's just `**` matches any number of components, including none.
+    /// As with [`ZipArchive::entries_with_prefix`], names that use `\` as their separator are
+    /// matched as if they used `/`.
+    ///
+    /// ```
+    /// # fn main() -> zip::result::ZipResult<()> {
+    /// use std::io::Cursor;
+    ///
+    /// let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    /// for name in ["assets/logo.png", "assets/icons/small.png", "assets/readme.txt", "logo.png"] {
+    ///     zip.start_file(name, zip::write::SimpleFileOptions::default())?;
+    /// }
+    /// let archive = zip.finish_into_readable()?;
+    /// assert_eq!(archive.glob("assets/**/*.png"), [0, 1]);
+    /// assert_eq!(archive.glob("*.png"), [3]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn glob(&self, pattern: &str) -> Vec<usize> {
+        let pattern = with_slashes(pattern);
+        // Only names that start with the part of the pattern before any wildcard can match it. A
+        // `**` component can match nothing, so `a/**` also matches `a` itself and the `/` before
+        // it can't be part of the prefix.
+        let literal = match pattern.find(['*', '?', '[']) {
+            Some(end) if pattern[end..].split('/').next() == Some("**") => {
+                pattern[..end].strip_suffix('/').unwrap_or(&pattern[..end])
+            }
+            Some(end) => &pattern[..end],
+            None => &pattern,
+        };
+        let glob = Glob::new(&pattern);
+        self.entries_with_prefix(literal)
+            .into_iter()
+            .filter(|index| {
+                let name = self.shared.files.get_index(*index).unwrap().0;
+                glob.matches(&with_slashes(name))
+            })
+            .collect()
+    }
+
+    /// Get the index of a file entry by name, ignoring case and treating `\` in names that use it
+    /// as their separator as `/`. An entry with exactly this name comes first, and otherwise the
+    /// first entry in the central directory that matches.
+    pub fn index_for_name_ci(&self, name: &str) -> Option<usize> {
+        self.shared.files.get_index_of(name).or_else(|| {
+            let folded = with_slashes(name).to_lowercase();
+            self.name_index().folded.get(&*folded).copied()
+        })
+    }
+}
+
+/// A glob pattern, split into path components, with `None` for each `**`.
+struct Glob(Vec<Option<Vec<Token>>>);
+
+impl Glob {
+    fn new(pattern: &str) -> Self {
+        Glob(
+            pattern
+                .split('/')
+                .map(|component| match component {
+                    "**" => None,
+                    _ => Some(tokenize(&component.chars().collect::<Vec<_>>())),
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether a name with `/` separators matches.
+    fn matches(&self, name: &str) -> bool {
+        let components: Vec<&str> = name.split('/').collect();
+        wildcard_matches(
+            &self.0,
+            &components,
+            Option::is_none,
+            |tokens, component| {
+                let tokens = tokens.as_ref().unwrap();
+                let chars: Vec<char> = component.chars().collect();
+                wildcard_matches(tokens, &chars, |t| *t == Token::Star, |t, c| t.matches(*c))
+            },
+        )
+    }
+}
+
+/// Matches `items` against `pattern`, where each wildcard matches any run of items and every other
+/// part of the pattern matches one item. On a mismatch, the last wildcard takes one more item and
+/// matching goes on from there, which is enough since a later wildcard can take whatever an
+/// earlier one could.
+fn wildcard_matches<P, T>(
+    pattern: &[P],
+    items: &[T],
+    is_wildcard: impl Fn(&P) -> bool,
+    matches: impl Fn(&P, &T) -> bool,
+) -> bool {
+    let (mut p, mut i) = (0, 0);
+    let mut retry = None;
+    while i < items.len() {
+        match pattern.get(p) {
+            Some(part) if is_wildcard(part) => {
+                retry = Some((p, i));
+                p += 1;
+                continue;
+            }
+            Some(part) if matches(part, &items[i]) => {
+                p += 1;
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        let Some((wildcard, taken)) = retry else {
+            return false;
+        };
+        retry = Some((wildcard, taken + 1));
+        p = wildcard + 1;
+        i = taken + 1;
+    }
+    pattern[p..].iter().all(is_wildcard)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Star,
+    AnyChar,
+    Char(char),
+    /// The characters and ranges of a `[...]` class, and whether it's negated.
+    Class(Vec<(char, char)>, bool),
+}
+
+impl Token {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Token::Star | Token::AnyChar => true,
+            Token::Char(expected) => *expected == c,
+            Token::Class(ranges, negated) => {
+                ranges.iter().any(|(low, high)| (*low..=*high).contains(&c)) != *negated
+            }
+        }
+    }
+}
+
+/// Splits a component of a glob pattern into what each of its parts matches. A `[` that isn't
+/// closed matches itself.
+fn tokenize(pattern: &[char]) -> Vec<Token> {
+    let mut tokens = Vec::with_capacity(pattern.len());
+    let mut position = 0;
+    while position < pattern.len() {
+        let (token, len) = match pattern[position] {
+            '*' => (Token::Star, 1),
+            '?' => (Token::AnyChar, 1),
+            '[' => class(&pattern[position + 1..])
+                .map_or((Token::Char('['), 1), |(token, len)| (token, len + 1)),
+            c => (Token::Char(c), 1),
+        };
+        tokens.push(token);
+        position += len;
+    }
+    tokens
+}
+
+/// Parses a character class after its opening `[`, returning it and how many characters it takes
+/// up, including the closing `]`. A `]` right at the start is one of the characters.
+fn class(pattern: &[char]) -> Option<(Token, usize)> {
+    let negated = matches!(pattern.first(), Some('!' | '^'));
+    let mut position = negated as usize;
+    let mut ranges = Vec::new();
+    loop {
+        match pattern.get(position..)? {
+            [']', ..] if position > negated as usize => {
+                return Some((Token::Class(ranges, negated), position + 1))
+            }
+            [low, '-', high, ..] if *high != ']' => {
+                ranges.push((*low, *high));
+                position += 3;
+            }
+            [c, ..] => {
+                ranges.push((*c, *c));
+                position += 1;
+            }
+            [] => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Glob;
+    use crate::result::ZipResult;
+    use crate::write::SimpleFileOptions;
+    use crate::{ZipArchive, ZipWriter};
+    use std::io::Cursor;
+
+    fn glob_matches(pattern: &str, name: &str) -> bool {
+        Glob::new(pattern).matches(name)
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(glob_matches("*.png", "logo.png"));
+        assert!(!glob_matches("*.png", "assets/logo.png"));
+        assert!(glob_matches("assets/**/*.png", "assets/logo.png"));
+        assert!(glob_matches("assets/**/*.png", "assets/a/b/logo.png"));
+        assert!(!glob_matches("assets/**/*.png", "assets/a/b/logo.jpg"));
+        assert!(glob_matches("**", "a/b/"));
+        assert!(glob_matches("a/**/b/**/c", "a/x/b/y/b/c"));
+        assert!(glob_matches("file?.txt", "file1.txt"));
+        assert!(!glob_matches("file?.txt", "file10.txt"));
+        assert!(glob_matches("file[0-9].txt", "file7.txt"));
+        assert!(!glob_matches("file[!0-9].txt", "file7.txt"));
+        assert!(glob_matches("[]]", "]"));
+        assert!(glob_matches("a[b", "a[b"));
+        assert!(glob_matches("*a*b*c", "xaybzbc"));
+        assert!(!glob_matches("*a*b*c", "xaybzb"));
+        assert!(glob_matches("ü*", "über"));
+    }
+
+    fn archive(names: &[&str]) -> ZipResult<ZipArchive<Cursor<Vec<u8>>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for name in names {
+            writer.start_file(*name, SimpleFileOptions::default())?;
+        }
+        writer.finish_into_readable()
+    }
+
+    #[test]
+    fn queries() -> ZipResult<()> {
+        let archive = archive(&[
+            "src/main.rs",
+            "README.md",
+            "src\\windows.rs",
+            "srcs/other.rs",
+            "src/nested/lib.rs",
+            "Readme.md",
+            "assets/",
+            "assets",
+        ])?;
+        assert_eq!(archive.entries_with_prefix("src/"), [0, 2, 4]);
+        assert_eq!(archive.entries_with_prefix("src"), [0, 2, 3, 4]);
+        assert_eq!(archive.entries_with_prefix(""), (0..8).collect::<Vec<_>>());
+        assert!(archive.entries_with_prefix("missing").is_empty());
+        assert_eq!(archive.glob("src/*.rs"), [0, 2]);
+        assert_eq!(archive.glob("src/**/*.rs"), [0, 2, 4]);
+        assert_eq!(archive.glob("**/*.rs"), [0, 2, 3, 4]);
+        assert_eq!(archive.glob("*/"), [6]);
+        assert_eq!(archive.glob("README.md"), [1]);
+        assert_eq!(archive.glob("assets/**"), [6, 7]);
+        assert_eq!(archive.index_for_name_ci("readme.md"), Some(1));
+        assert_eq!(archive.index_for_name_ci("Readme.md"), Some(5));
+        assert_eq!(archive.index_for_name_ci("SRC/Windows.RS"), Some(2));
+        assert_eq!(archive.index_for_name_ci("src\\MAIN.rs"), Some(0));
+        assert_eq!(archive.index_for_name_ci("missing"), None);
+        Ok(())
+    }
+}
@@ -228,14 +228,14 @@ pub mod path_splitting {
             );
         }
 
-        #[test]
-        fn lex_trie() {
-            impl DirByMode for usize {
-                fn is_dir_by_mode(&self) -> bool {
-                    false
-                }
+        impl DirByMode for usize {
+            fn is_dir_by_mode(&self) -> bool {
+                false
             }
+        }
 
+        #[test]
+        fn lex_trie() {
             assert_eq!(
                 lexicographic_entry_trie([
                     ("a/b/", 1usize),
@@ -423,25 +423,25 @@ pub mod handle_creation {
         }
     }
 
-    /* TODO: figure out how to handle symlinks! These are especially difficult because:
-     * (1) windows symlinks files and directories differently, and only on newer windows versions,
-     * (2) later entries in the zip may refer to symlink paths from earlier in the zip.
-     *
-     * Of these issues, (2) is more difficult and intrinsic to the problem space. In order to
-     * correctly extract symlinks in a pipelined/parallel fashion, we need to identify these order
-     * dependencies and schedule the symlink dereference (reading the target value from the zip)
-     * before we create any directories or allocate any output file handles that dereference that
-     * symlink. This is less of a problem with the synchronous in-order extraction because it
-     * creates any symlinks immediately (it imposes a total ordering dependency over all entries).
-     */
+    /* Symlinks are created here along with everything else, from targets which were read out of
+     * the zip beforehand. No entry can be extracted through a symlink, because the lex entry trie
+     * rejects any entry underneath a file path, so links never need to exist before any other
+     * handle is allocated. Windows would need to know whether each target is a file or directory,
+     * but split extraction is only supported on unix for now. */
     pub(crate) struct AllocatedHandles<'a> {
         pub file_handle_mapping: HashMap<ZipDataHandle<'a>, fs::File>,
         pub perms_todo: Vec<(PathBuf, fs::Permissions)>,
+        /// Directories with entries of their own, deepest first, which may have times to set.
+        pub dirs_todo: Vec<(PathBuf, &'a ZipFileData)>,
+        /// Symlinks which were created, which may have times to set.
+        pub links_todo: Vec<(PathBuf, &'a ZipFileData)>,
     }
 
+    #[allow(clippy::mutable_key_type)]
     pub(crate) fn transform_entries_to_allocated_handles<'a>(
         top_level_extraction_dir: &Path,
         lex_entry_trie: impl IntoIterator<Item = (&'a str, Box<FSEntry<'a, &'a ZipFileData>>)>,
+        symlink_targets: &HashMap<ZipDataHandle<'a>, PathBuf>,
     ) -> Result<AllocatedHandles<'a>, HandleCreationError> {
         #[cfg(unix)]
         use std::os::unix::fs::PermissionsExt;
@@ -464,12 +464,29 @@ pub mod handle_creation {
                 })
                 .collect();
         let mut perms_todo: Vec<(PathBuf, fs::Permissions)> = Vec::new();
+        let mut dirs_todo: Vec<(PathBuf, &'a ZipFileData)> = Vec::new();
+        let mut links_todo: Vec<(PathBuf, &'a ZipFileData)> = Vec::new();
 
         while let Some((path, entry)) = entry_queue.pop_front() {
             match *entry {
                 FSEntry::File(data) => {
                     let key = ZipDataHandle::wrap(data);
 
+                    if let Some(target) = symlink_targets.get(&key) {
+                        /* Links can't be created over an existing file, and their own perms are
+                         * meaningless, so don't queue any. */
+                        match fs::remove_file(&path) {
+                            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                            _ => (),
+                        }
+                        #[cfg(unix)]
+                        std::os::unix::fs::symlink(target, &path)?;
+                        #[cfg(not(unix))]
+                        fs::write(&path, target.to_string_lossy().as_bytes())?;
+                        links_todo.push((path, data));
+                        continue;
+                    }
+
                     if let Some(mode) = data.unix_mode() {
                         /* TODO: consider handling the readonly bit on windows. We don't currently
                          * do this in normal extraction, so we don't need to do this yet for
@@ -502,6 +519,9 @@ pub mod handle_creation {
                     if let Some(perms_to_set) = properties.and_then(|data| data.unix_mode()) {
                         perms_todo.push((path.clone(), fs::Permissions::from_mode(perms_to_set)));
                     }
+                    if let Some(data) = properties {
+                        dirs_todo.push((path.clone(), data));
+                    }
                     /* (2) Generate sub-entries by constructing full paths. */
                     for (sub_name, entry) in children.into_iter() {
                         let full_name = path.join(sub_name);
@@ -514,10 +534,13 @@ pub mod handle_creation {
         /* NB: Iterate in *REVERSE* so that child directories are set before parents! Setting
          * a parent readonly would stop us from setting child perms. */
         perms_todo.reverse();
+        dirs_todo.reverse();
 
         Ok(AllocatedHandles {
             file_handle_mapping,
             perms_todo,
+            dirs_todo,
+            links_todo,
         })
     }
 
@@ -565,7 +588,8 @@ pub mod handle_creation {
             let AllocatedHandles {
                 file_handle_mapping,
                 perms_todo,
-            } = transform_entries_to_allocated_handles(td.path(), trie).unwrap();
+                ..
+            } = transform_entries_to_allocated_handles(td.path(), trie, &HashMap::new()).unwrap();
 
             let mut files: Vec<_> = file_handle_mapping.into_iter().collect();
             assert_eq!(1, files.len());
@@ -596,47 +620,44 @@ pub mod handle_creation {
     }
 }
 
-#[cfg(unix)]
-pub mod split_extraction {
+/// Extraction across a pool of threads, and what it shares with the pipelined
+/// `split_extract()`.
+pub mod extraction {
     use displaydoc::Display;
     use thiserror::Error;
 
+    use std::borrow::Cow;
+    use std::collections::{HashMap, VecDeque};
+    use std::fmt;
     use std::fs;
     use std::io;
-    use std::mem::{self, MaybeUninit};
-    use std::path::Path;
-    use std::sync::mpsc;
+    use std::mem;
+    use std::path::{Path, PathBuf};
+    use std::sync::{mpsc, Arc, Mutex};
     use std::thread;
+    use std::time::{Duration, Instant};
 
-    use crate::compression::CompressionMethod;
-    use crate::read::ZipArchive;
-    use crate::read::{make_crypto_reader, make_reader};
-    use crate::result::ZipError;
-    use crate::spec::FixedSizeBlock;
-    use crate::types::{ZipFileData, ZipLocalEntryBlock};
-
-    #[cfg(not(target_os = "linux"))]
-    use crate::read::split::pipe::unix::{PipeReadBufferSplicer, PipeWriteBufferSplicer};
-    #[cfg(target_os = "linux")]
-    use crate::read::split::{
-        file::linux::FileCopy,
-        pipe::linux::{PipeReadSplicer, PipeWriteSplicer},
+    use crate::read::extract::{
+        entry_times, set_file_handle_times, set_file_times, set_symlink_file_times, Links,
+        SymlinkPolicy,
     };
-    use crate::read::split::{
-        file::{
-            unix::{FileBufferCopy, FileInput, FileOutput},
-            CopyRange, InputFile,
-        },
-        pipe::{unix::create_pipe, ReadSplicer, WriteSplicer},
-        util::{copy_via_buf, TakeWrite},
+    use crate::read::limits::Budget;
+    use crate::read::positional::find_content_at;
+    use crate::read::split::util::{copy_via_buf, TakeWrite};
+    use crate::read::zip_archive::Shared;
+    use crate::read::{
+        make_crypto_reader, make_reader, password_for, EntrySource, ReadAt, ZipArchive,
+        ZipFileReader,
     };
+    use crate::result::ZipError;
+    use crate::types::ZipFileData;
 
     use super::{
         handle_creation::{
             transform_entries_to_allocated_handles, AllocatedHandles, HandleCreationError,
             ZipDataHandle,
         },
-        path_splitting::{lexicographic_entry_trie, PathSplitError},
+        path_splitting::{lexicographic_entry_trie, normalize_parent_dirs, PathSplitError},
     };
 
     /// Errors encountered during the split pipelined extraction process.
@@ -652,48 +673,190 @@ pub mod split_extraction {
         HandleCreation(#[from] HandleCreationError),
     }
 
-    /* TODO: make this share code with find_data_start()! */
-    fn get_or_find_data_start<InF>(data: &ZipFileData, input_file: InF) -> Result<u64, ZipError>
-    where
-        InF: InputFile,
-    {
-        // TODO: use .get_or_try_init() once stabilized to provide a closure returning a Result!
-        if let Some(data_start) = data.data_start.get() {
-            return Ok(*data_start);
-        }
+    type PasswordCallback = dyn Fn(&str) -> Option<Vec<u8>> + Send + Sync;
 
-        let block = {
-            let block: MaybeUninit<[u8; mem::size_of::<ZipLocalEntryBlock>()]> =
-                MaybeUninit::uninit();
-            let mut block: [MaybeUninit<u8>; mem::size_of::<ZipLocalEntryBlock>()] =
-                unsafe { mem::transmute(block) };
+    /// The password used by [`pool_extract()`] and `split_extract()` to decrypt encrypted entries.
+    ///
+    /// Keys are derived from it on the decompression threads, so the expensive key derivation for
+    /// AES entries runs in parallel.
+    #[derive(Clone)]
+    pub enum ExtractionPassword {
+        /// Decrypt every encrypted entry with the same password.
+        Fixed(Vec<u8>),
+        /// Look up the password for each encrypted entry by its name. Returning `None` fails the
+        /// extraction, as it would if no password were given.
+        ///
+        /// This is called from the decompression threads, possibly from several at once, and
+        /// never for entries that aren't encrypted.
+        PerEntry(Arc<PasswordCallback>),
+    }
 
-            input_file.pread_all(data.header_start, &mut block[..])?;
+    impl ExtractionPassword {
+        fn for_entry(&self, name: &str) -> Option<Cow<'_, [u8]>> {
+            match self {
+                Self::Fixed(password) => Some(Cow::Borrowed(password)),
+                Self::PerEntry(callback) => callback(name).map(Cow::Owned),
+            }
+        }
+    }
 
-            let block: MaybeUninit<[u8; mem::size_of::<ZipLocalEntryBlock>()]> =
-                unsafe { mem::transmute(block) };
-            unsafe { block.assume_init() }
-        };
-        // Parse static-sized fields and check the magic value.
-        let block = ZipLocalEntryBlock::interpret(block.as_ref())?;
+    impl fmt::Debug for ExtractionPassword {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            /* Don't print the password itself. */
+            match self {
+                Self::Fixed(_) => f.write_str("Fixed(..)"),
+                Self::PerEntry(_) => f.write_str("PerEntry(..)"),
+            }
+        }
+    }
 
-        // Calculate the end of the local header from the fields we just parsed.
-        let variable_fields_len: u64 =
-            // Each of these fields must be converted to u64 before adding, as the result may
-            // easily overflow a u16.
-            block.file_name_length as u64 + block.extra_field_length as u64;
-        let local_entry_block_size: u64 = mem::size_of::<ZipLocalEntryBlock>().try_into().unwrap();
-        let data_start: u64 = data.header_start + local_entry_block_size + variable_fields_len;
+    type SelectionCallback = dyn Fn(&str) -> bool + Send + Sync;
 
-        // Set the value so we don't have to read it again.
-        match data.data_start.set(data_start) {
-            Ok(()) => (),
-            // If the value was already set in the meantime, ensure it matches.
-            Err(_) => {
-                assert_eq!(*data.data_start.get().unwrap(), data_start);
+    /// Chooses which entries [`pool_extract()`] and `split_extract()` extract, by name.
+    ///
+    /// Directories that aren't selected are still created if an entry inside them is.
+    #[derive(Clone)]
+    pub struct EntrySelector(Arc<SelectionCallback>);
+
+    impl EntrySelector {
+        /// Select the entries whose names `select` returns true for.
+        pub fn new(select: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+            Self(Arc::new(select))
+        }
+
+        /// Select the entries under the directory `prefix`, such as `"docs/"`. The trailing slash
+        /// is added if it's missing, so `"docs"` doesn't also select `"docs2/"` or `"docsfile"`.
+        pub fn subtree(prefix: impl Into<String>) -> Self {
+            let mut prefix = prefix.into();
+            if !prefix.is_empty() && !prefix.ends_with('/') {
+                prefix.push('/');
             }
+            Self::new(move |name| name.starts_with(&prefix))
+        }
+
+        fn selects(&self, name: &str) -> bool {
+            (self.0)(name)
+        }
+    }
+
+    impl fmt::Debug for EntrySelector {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("EntrySelector(..)")
+        }
+    }
+
+    /// How [`pool_extract()`] or `split_extract()` extracted an entry.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum ExtractionMethod {
+        /// A directory, created before any file contents were written.
+        Directory,
+        /// A symlink, created from a target read before any file contents were written.
+        Symlink,
+        /// A stored entry, copied from the archive with `copy_file_range()` so that its contents
+        /// never passed through userspace.
+        CopyFileRange,
+        /// A stored entry, copied from the archive through a buffer.
+        BufferCopy,
+        /// A stored entry, written into the output file straight from an archive in memory.
+        MemoryCopy,
+        /// A compressed or encrypted entry, spliced or written through a pipe to a decompression
+        /// thread, and from there through another pipe into the output file.
+        Decompression,
+        /// An entry read, decrypted, decompressed and written to its output file by a single
+        /// worker of [`pool_extract()`].
+        Pooled,
+    }
+
+    /// What happened to a single entry in [`pool_extract()`] or `split_extract()`.
+    #[derive(Debug)]
+    pub struct ExtractedEntry {
+        /// The entry's index in the archive.
+        pub index: usize,
+        /// The entry's name in the archive.
+        pub name: String,
+        /// How the entry was extracted, or `None` if it failed before it was started.
+        pub method: Option<ExtractionMethod>,
+        /// The number of bytes written for a file, or the length of a symlink's target. This is 0
+        /// if the entry failed.
+        pub bytes_written: u64,
+        /// How long the entry took, from when its data started to be read until it was complete.
+        pub duration: Duration,
+        /// The error this entry failed with, if
+        /// [`ExtractionParameters::continue_on_error`] let extraction carry on past it.
+        pub error: Option<SplitExtractionError>,
+    }
+
+    /// The results of [`pool_extract()`] or `split_extract()` for each selected entry, in the order of the archive's
+    /// central directory.
+    #[derive(Debug, Default)]
+    pub struct ExtractionReport {
+        /// One report per selected entry.
+        pub entries: Vec<ExtractedEntry>,
+    }
+
+    impl ExtractionReport {
+        /// The reports for entries that failed.
+        pub fn failures(&self) -> impl Iterator<Item = &ExtractedEntry> {
+            self.entries.iter().filter(|entry| entry.error.is_some())
+        }
+
+        /// Whether every selected entry was extracted.
+        pub fn is_complete(&self) -> bool {
+            self.failures().next().is_none()
+        }
+    }
+
+    /// How an entry turned out, as sent back from the extraction threads.
+    pub(super) struct EntryOutcome {
+        method: Option<ExtractionMethod>,
+        bytes_written: u64,
+        duration: Duration,
+        error: Option<SplitExtractionError>,
+    }
+
+    pub(super) struct Reporter<'a> {
+        sender: mpsc::Sender<(&'a ZipFileData, EntryOutcome)>,
+        continue_on_error: bool,
+    }
+
+    /// Where a [`Reporter`] sends how each entry turned out.
+    pub(super) type ReportReceiver<'a> = mpsc::Receiver<(&'a ZipFileData, EntryOutcome)>;
+
+    impl<'a> Reporter<'a> {
+        pub(super) fn new(continue_on_error: bool) -> (Self, ReportReceiver<'a>) {
+            let (sender, receiver) = mpsc::channel();
+            let reporter = Reporter {
+                sender,
+                continue_on_error,
+            };
+            (reporter, receiver)
+        }
+
+        /// Records how an entry turned out, or returns its error if extraction should stop.
+        pub(super) fn finish(
+            &self,
+            entry: &'a ZipFileData,
+            method: Option<ExtractionMethod>,
+            started: Instant,
+            result: Result<u64, SplitExtractionError>,
+        ) -> Result<(), SplitExtractionError> {
+            let (bytes_written, error) = match result {
+                Ok(bytes_written) => (bytes_written, None),
+                Err(e) if self.continue_on_error => (0, Some(e)),
+                Err(e) => return Err(e),
+            };
+            let outcome = EntryOutcome {
+                method,
+                bytes_written,
+                duration: started.elapsed(),
+                error,
+            };
+            /* The receiver is only dropped early if extraction has already failed, in which case
+             * there's nothing left to report to. */
+            let _ = self.sender.send((entry, outcome));
+            Ok(())
         }
-        Ok(data_start)
     }
 
     /// Parameters to control the degree of parallelism used for extraction.
@@ -701,9 +864,9 @@ pub mod split_extraction {
     pub struct ExtractionParameters {
         /// Number of threads used for decompression.
         ///
-        /// Default value: 4.
+        /// Default value: 4. Zero is treated as one, so that entries are always extracted.
         ///
-        /// Note that multiple times this many threads will be spawned by [`split_extract()`] as
+        /// Note that multiple times this many threads will be spawned by `split_extract()` as
         /// part of the pipelined process. Only this many threads will be used to perform
         /// decompression in rust code, but other threads will be used to wait on I/O from
         /// the kernel.
@@ -717,6 +880,8 @@ pub mod split_extraction {
         /// Used on non-Linux platforms without
         /// [`copy_file_range()`](https://www.gnu.org/software/libc/manual/html_node/Copying-File-Data.html),
         /// as well as on Linux when the input and output file handles are on separate devices.
+        /// On Linux, it's also used to write entries from a
+        /// [`PositionalSource`](crate::read::PositionalSource) into the decompression pipes.
         ///
         /// Default value: 1MB.
         pub file_range_copy_buffer_length: usize,
@@ -734,6 +899,47 @@ pub mod split_extraction {
         /// Default value: 1MB.
         #[cfg(not(target_os = "linux"))]
         pub splice_write_buffer_length: usize,
+        /// Whether to finish output files through
+        /// [io_uring](https://man7.org/linux/man-pages/man7/io_uring.7.html) on a single thread,
+        /// instead of on the threads of each pipeline.
+        ///
+        /// That thread copies in stored entries, unless `copy_file_range()` can, then sets the
+        /// length of every output file and syncs it, with up to 32 files in flight on one ring.
+        /// This helps most with archives of many small files. Extraction fails if the ring can't
+        /// be set up, as on kernels older than 5.6 or where io_uring has been disabled, and file
+        /// lengths are set with a system call of their own on kernels older than 6.9.
+        ///
+        /// Default value: false.
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        pub io_uring: bool,
+        /// Whether to give extracted files and directories the times stored in the archive, as
+        /// described for [`ExtractOptions::restore_times`](crate::read::ExtractOptions::restore_times).
+        ///
+        /// Default value: true.
+        pub restore_times: bool,
+        /// The password to decrypt encrypted entries with.
+        ///
+        /// Default value: None, so extracting an encrypted entry fails.
+        pub password: Option<ExtractionPassword>,
+        /// What to do with symlinks that point outside the extraction directory, as described
+        /// for [`ExtractOptions::symlink_policy`](crate::read::ExtractOptions::symlink_policy).
+        ///
+        /// Default value: [`SymlinkPolicy::Reject`].
+        pub symlink_policy: SymlinkPolicy,
+        /// Which entries to extract.
+        ///
+        /// Default value: None, so every entry is extracted.
+        pub select: Option<EntrySelector>,
+        /// Whether to carry on after an entry fails, recording its error in the
+        /// [`ExtractionReport`] and removing anything written for it.
+        ///
+        /// This covers entries that can't be decrypted or decompressed, that exceed the
+        /// [`Limits`](crate::read::Limits), or whose symlink target is rejected. Errors that
+        /// affect the whole archive, such as failing to read it or entries with overlapping
+        /// paths, still stop extraction.
+        ///
+        /// Default value: false.
+        pub continue_on_error: bool,
     }
 
     impl Default for ExtractionParameters {
@@ -746,11 +952,94 @@ pub mod split_extraction {
                 splice_read_buffer_length: 1024 * 1024,
                 #[cfg(not(target_os = "linux"))]
                 splice_write_buffer_length: 1024 * 1024,
+                #[cfg(all(target_os = "linux", feature = "io-uring"))]
+                io_uring: false,
+                restore_times: true,
+                password: None,
+                symlink_policy: SymlinkPolicy::default(),
+                select: None,
+                continue_on_error: false,
             }
         }
     }
 
-    fn wrap_spawn_err<'scope>(
+    /// Decrypts and decompresses an entry's data from `reader`.
+    pub(super) fn open_entry_reader<'r>(
+        entry: &ZipFileData,
+        reader: io::Take<EntrySource<'r>>,
+        password: Option<&ExtractionPassword>,
+    ) -> Result<ZipFileReader<'r>, ZipError> {
+        let password = password
+            .filter(|_| entry.encrypted)
+            .and_then(|password| password.for_entry(&entry.file_name));
+        let crypto_reader = make_crypto_reader(
+            entry.compression_method,
+            entry.crc32,
+            entry.last_modified_time,
+            entry.using_data_descriptor,
+            reader,
+            password_for(entry, password.as_deref())?,
+            entry.aes_mode,
+            #[cfg(feature = "aes-crypto")]
+            entry.compressed_size,
+        )?;
+        make_reader(
+            entry.compression_method,
+            entry.crc32,
+            entry.uncompressed_size,
+            crypto_reader,
+        )
+    }
+
+    /// Errors from reading decompressed data may wrap a [`ZipError`], such as an exceeded limit.
+    pub(super) fn read_error(e: io::Error) -> SplitExtractionError {
+        match ZipError::from(e) {
+            ZipError::Io(e) => SplitExtractionError::Io(e),
+            e => SplitExtractionError::Zip(e),
+        }
+    }
+
+    /// The longest symlink target that will be read, matching `PATH_MAX` on Linux.
+    pub(super) const MAX_SYMLINK_TARGET_LEN: u64 = 4096;
+
+    /// Reads the target of a symlink entry into memory.
+    fn read_symlink_target(
+        entry: &ZipFileData,
+        input: &dyn ReadAt,
+        password: Option<&ExtractionPassword>,
+        budget: &Budget,
+    ) -> Result<Vec<u8>, SplitExtractionError> {
+        use io::Read;
+
+        let limited_reader = find_content_at(entry, input)?;
+        let mut target = Vec::new();
+        budget
+            .reader(open_entry_reader(entry, limited_reader, password)?, |_| {
+                entry.compressed_size
+            })
+            .take(MAX_SYMLINK_TARGET_LEN + 1)
+            .read_to_end(&mut target)
+            .map_err(read_error)?;
+        if target.len() as u64 > MAX_SYMLINK_TARGET_LEN {
+            return Err(ZipError::InvalidArchive("Symlink target is too long").into());
+        }
+        Ok(target)
+    }
+
+    /// Interprets the bytes of a symlink target as a path.
+    fn target_path(target: &[u8]) -> Result<&Path, ZipError> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            Ok(Path::new(std::ffi::OsStr::from_bytes(target)))
+        }
+        #[cfg(not(unix))]
+        std::str::from_utf8(target)
+            .map(Path::new)
+            .map_err(|_| ZipError::InvalidArchive("Invalid UTF-8 as symlink target"))
+    }
+
+    pub(super) fn wrap_spawn_err<'scope>(
         err_sender: mpsc::Sender<SplitExtractionError>,
         f: impl FnOnce() -> Result<(), SplitExtractionError> + Send + 'scope,
     ) -> impl FnOnce() + Send + 'scope {
@@ -766,110 +1055,1017 @@ pub mod split_extraction {
         }
     }
 
-    /// Extract all entries in parallel using a pipelined strategy.
-    pub fn split_extract(
-        archive: &ZipArchive<fs::File>,
+    /// Selects the entries to extract and checks them against the limits, then reads symlink
+    /// targets and creates the directories, symlinks and empty files that entries will be
+    /// extracted into.
+    pub(super) fn prepare_entries<'a>(
+        shared: &'a Shared,
+        input: &dyn ReadAt,
+        top_level_extraction_dir: &Path,
+        params: &ExtractionParameters,
+        reporter: &Reporter<'a>,
+        budget: &Budget,
+    ) -> Result<(Vec<&'a ZipFileData>, AllocatedHandles<'a>), SplitExtractionError> {
+        let ExtractionParameters {
+            ref password,
+            symlink_policy,
+            ref select,
+            ..
+        } = *params;
+
+        /* (1) Select entries, and check limits against the sizes the output files will be
+         *     allocated with. */
+        let limits = shared.limits;
+        let declared = Budget::new(limits);
+        let mut selected: Vec<&ZipFileData> = Vec::new();
+        for data in shared.files.values() {
+            if select
+                .as_ref()
+                .is_some_and(|select| !select.selects(&data.file_name))
+            {
+                continue;
+            }
+            let checked = limits.check_name(&data.file_name).and_then(|()| {
+                limits.check_compression_ratio(data.uncompressed_size, data.compressed_size)
+            });
+            if let Err(e) = checked {
+                reporter.finish(data, None, Instant::now(), Err(e.into()))?;
+                continue;
+            }
+            declared.consume(data.uncompressed_size)?;
+            selected.push(data);
+        }
+
+        /* (2) Read and check symlink targets in order, as ZipArchive::extract() would. */
+        #[allow(clippy::mutable_key_type)]
+        let mut symlink_targets: HashMap<ZipDataHandle, PathBuf> = HashMap::new();
+        let mut links = Links::default();
+        for &entry in selected.iter().filter(|entry| entry.is_symlink()) {
+            let started = Instant::now();
+            let (components, _) = normalize_parent_dirs(&entry.file_name)?;
+            let path: PathBuf = components.into_iter().collect();
+            let target =
+                read_symlink_target(entry, input, password.as_ref(), budget).and_then(|target| {
+                    let target = target_path(&target)?;
+                    Ok(links.add(&path, target, symlink_policy)?)
+                });
+            let result = target.map(|target| {
+                let len = target.as_os_str().len().try_into().unwrap();
+                symlink_targets.insert(ZipDataHandle::wrap(entry), target);
+                len
+            });
+            reporter.finish(entry, Some(ExtractionMethod::Symlink), started, result)?;
+        }
+        /* Links which failed are left out, like any other failed entry. */
+        selected.retain(|entry| {
+            !entry.is_symlink() || symlink_targets.contains_key(&ZipDataHandle::wrap(entry))
+        });
+
+        /* (3) Create lex entry trie, then generate handles. */
+        let trie =
+            lexicographic_entry_trie(selected.iter().map(|data| (data.file_name.as_ref(), *data)))?;
+        let handles = transform_entries_to_allocated_handles(
+            top_level_extraction_dir,
+            trie,
+            &symlink_targets,
+        )?;
+        for &entry in selected.iter() {
+            if entry.is_dir() || entry.is_dir_by_mode() {
+                let method = Some(ExtractionMethod::Directory);
+                reporter.finish(entry, method, Instant::now(), Ok(0))?;
+            }
+        }
+        Ok((selected, handles))
+    }
+
+    /// Collects the report, removes the output files of any entries that failed, and sets the
+    /// permissions and times that have to wait until everything has been written.
+    pub(super) fn finish_extraction<'a>(
+        shared: &'a Shared,
+        top_level_extraction_dir: &Path,
+        selected: &[&'a ZipFileData],
+        report_receiver: ReportReceiver<'a>,
+        handles: AllocatedHandles<'a>,
+        restore_times: bool,
+    ) -> Result<ExtractionReport, SplitExtractionError> {
+        /* (10) Collect the report, and remove the output files of any entries that failed. */
+        #[allow(clippy::mutable_key_type)]
+        let mut outcomes: HashMap<ZipDataHandle, EntryOutcome> = HashMap::new();
+        for (entry, outcome) in report_receiver.try_iter() {
+            /* An entry's error takes precedence over the writer thread's report that it
+             * finished writing out the filler that replaced its data. */
+            let handle = ZipDataHandle::wrap(entry);
+            if outcomes.get(&handle).map_or(true, |o| o.error.is_none()) {
+                outcomes.insert(handle, outcome);
+            }
+        }
+        let mut removed: Vec<PathBuf> = Vec::new();
+        for &entry in selected.iter() {
+            let failed = outcomes
+                .get(&ZipDataHandle::wrap(entry))
+                .is_some_and(|o| o.error.is_some());
+            if failed && !entry.is_symlink() {
+                let (components, _) = normalize_parent_dirs(&entry.file_name)?;
+                let path = top_level_extraction_dir.join(components.iter().collect::<PathBuf>());
+                fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+        let mut report = ExtractionReport::default();
+        for (index, entry) in shared.files.values().enumerate() {
+            if let Some(outcome) = outcomes.remove(&ZipDataHandle::wrap(entry)) {
+                report.entries.push(ExtractedEntry {
+                    index,
+                    name: entry.file_name.to_string(),
+                    method: outcome.method,
+                    bytes_written: outcome.bytes_written,
+                    duration: outcome.duration,
+                    error: outcome.error,
+                });
+            }
+        }
+
+        /* (11) Set permissions on specified entries. */
+        /* TODO: consider parallelizing this with rayon's parallel iterators. */
+        for (entry_path, perms) in handles.perms_todo.into_iter() {
+            if !removed.contains(&entry_path) {
+                fs::set_permissions(entry_path, perms)?;
+            }
+        }
+
+        /* (12) Set times on links and directories, now that nothing more will be created or
+         *      removed in them. */
+        if restore_times {
+            for (link_path, entry) in handles.links_todo.into_iter() {
+                if let Some((accessed, modified)) = entry_times(entry) {
+                    set_symlink_file_times(link_path, accessed, modified)?;
+                }
+            }
+            for (dir_path, entry) in handles.dirs_todo.into_iter() {
+                if let Some((accessed, modified)) = entry_times(entry) {
+                    set_file_times(dir_path, accessed, modified)?;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Reads, decrypts and decompresses an entry, writing it out to `output_file`.
+    fn extract_whole_entry(
+        entry: &ZipFileData,
+        input: &dyn ReadAt,
+        output_file: &fs::File,
+        password: Option<&ExtractionPassword>,
+        budget: &Budget,
+        buf: &mut [u8],
+    ) -> Result<u64, SplitExtractionError> {
+        let compressed_entry = find_content_at(entry, input)?;
+        let compressed_size = entry.compressed_size;
+        let mut decompressing_reader = budget.reader(
+            open_entry_reader(entry, compressed_entry, password)?,
+            move |_| compressed_size,
+        );
+        let mut limited_writer = TakeWrite::take(output_file, entry.uncompressed_size);
+        copy_via_buf(&mut decompressing_reader, &mut limited_writer, buf).map_err(read_error)
+    }
+
+    /// Extract all entries in parallel across a pool of worker threads.
+    ///
+    /// This is a simpler alternative to `split_extract()` which doesn't use pipes or any
+    /// platform-specific copying, so it works wherever threads do: each worker takes the next
+    /// entry, reads its data from the archive by offset through [`ReadAt`], and decompresses it
+    /// straight into the output file. Directories, symlinks, selection, limits, passwords and the
+    /// report all work as they do for `split_extract()`, except that symlinks are written as files
+    /// holding their target on platforms other than unix.
+    ///
+    /// [`ExtractionParameters::decompression_threads`] sets the number of workers, at least one,
+    /// and each copies through a buffer of
+    /// [`ExtractionParameters::decompression_copy_buffer_length`]. The other buffer lengths, and
+    /// `io_uring` where available, are ignored.
+    pub fn pool_extract<R: ReadAt + Sync>(
+        archive: &ZipArchive<R>,
         top_level_extraction_dir: &Path,
         params: ExtractionParameters,
-    ) -> Result<(), SplitExtractionError> {
+    ) -> Result<ExtractionReport, SplitExtractionError> {
         let ZipArchive {
-            reader: ref input_file,
+            reader: ref input,
             ref shared,
             ..
         } = archive;
+        let (reporter, report_receiver) = Reporter::new(params.continue_on_error);
+        let budget = &Budget::new(shared.limits);
+
+        let (selected, mut handles) = prepare_entries(
+            shared,
+            input,
+            top_level_extraction_dir,
+            &params,
+            &reporter,
+            budget,
+        )?;
         let ExtractionParameters {
             decompression_threads,
             decompression_copy_buffer_length,
-            file_range_copy_buffer_length,
-            #[cfg(not(target_os = "linux"))]
-            splice_read_buffer_length,
-            #[cfg(not(target_os = "linux"))]
-            splice_write_buffer_length,
+            restore_times,
+            ref password,
+            ..
         } = params;
 
-        /* (1) Create lex entry trie. */
-        let trie = lexicographic_entry_trie(
-            shared
-                .files
+        /* Queue up every file in order, along with the handle it'll be written to. */
+        let queue: Mutex<VecDeque<(&ZipFileData, fs::File)>> = Mutex::new(
+            selected
                 .iter()
-                .map(|(name, data)| (name.as_ref(), data)),
-        )?;
-        /* (2) Generate handles. */
-        let AllocatedHandles {
-            file_handle_mapping,
-            perms_todo,
-        } = transform_entries_to_allocated_handles(top_level_extraction_dir, trie)?;
-
-        /* (3) Create a wrapper over the input file which uses pread() to read from multiple
-         *     sections in parallel across a thread pool. */
-        let input_file = FileInput::new(input_file)?;
+                .filter(|entry| !(entry.is_dir() || entry.is_dir_by_mode() || entry.is_symlink()))
+                .map(|&entry| {
+                    let output_file = handles
+                        .file_handle_mapping
+                        .remove(&ZipDataHandle::wrap(entry))
+                        .unwrap();
+                    (entry, output_file)
+                })
+                .collect(),
+        );
+        assert!(handles.file_handle_mapping.is_empty());
+        let queue = &queue;
+        let reporter = &reporter;
 
-        thread::scope(move |scope| {
-            /* (4) Create n parallel consumer pipelines. Threads are spawned into the scope, so
-             *     panics get propagated automatically, and all threads are joined at the end of the
-             *     scope. wrap_spawn_err() is used to enable thread closures to return a Result and
-             *     asynchronously propagate the error back up to the main scope thread. */
+        thread::scope(|scope| {
             let (err_sender, err_receiver) = mpsc::channel::<SplitExtractionError>();
-            /* This channel is used to notify the zip-input-reader thread when a consumer has
-             * completed decompressing/copying an entry, and is ready to receive new input. This is
-             * neither round-robin nor LRU: no thread is prioritized over any other, and new
-             * entries are sent off to workers in order of when they notify the zip-input-reader
-             * thread of their readiness. */
-            let (queue_sender, queue_receiver) = mpsc::channel::<usize>();
-            let input_writer_infos: Vec<mpsc::Sender<(&ZipFileData, u64, FileOutput)>> = (0
-                ..decompression_threads)
-                .map(|consumer_index| {
-                    /* Create pipes to write entries through. */
-                    let (compressed_read_end, compressed_write_end) = create_pipe()?;
-                    let (uncompressed_read_end, uncompressed_write_end) = create_pipe()?;
+            for worker_index in 0..decompression_threads.max(1) {
+                thread::Builder::new()
+                    .name(format!("zip-worker-{}", worker_index))
+                    .spawn_scoped(
+                        scope,
+                        wrap_spawn_err(err_sender.clone(), move || {
+                            let mut buf: Box<[u8]> =
+                                vec![0u8; decompression_copy_buffer_length].into_boxed_slice();
+                            /* Take entries one at a time until there are none left. The lock is
+                             * released before each entry is extracted. */
+                            while let Some((entry, output_file)) = {
+                                let next = queue.lock().unwrap().pop_front();
+                                next
+                            } {
+                                let started = Instant::now();
+                                let result = extract_whole_entry(
+                                    entry,
+                                    input,
+                                    &output_file,
+                                    password.as_ref(),
+                                    budget,
+                                    &mut buf,
+                                )
+                                .and_then(|bytes_written| {
+                                    if restore_times {
+                                        if let Some((accessed, modified)) = entry_times(entry) {
+                                            set_file_handle_times(
+                                                &output_file,
+                                                Some(accessed),
+                                                Some(modified),
+                                            )?;
+                                        }
+                                    }
+                                    output_file.sync_data()?;
+                                    Ok(bytes_written)
+                                });
+                                let method = Some(ExtractionMethod::Pooled);
+                                if let Err(e) = reporter.finish(entry, method, started, result) {
+                                    /* Nobody else needs to start on another entry. */
+                                    queue.lock().unwrap().clear();
+                                    return Err(e);
+                                }
+                            }
+                            Ok(())
+                        }),
+                    )?;
+            }
+            mem::drop(err_sender);
 
-                    /* Create channels to send entries through. */
-                    let (read_send, read_recv) = mpsc::channel::<(&ZipFileData, u64, FileOutput)>();
-                    let (compressed_sender, compressed_receiver) =
-                        mpsc::channel::<(&ZipFileData, FileOutput)>();
-                    let (uncompressed_sender, uncompressed_receiver) =
-                        mpsc::channel::<(&ZipFileData, FileOutput)>();
+            if let Some(err) = err_receiver.iter().next() {
+                return Err(err);
+            }
+            Ok(())
+        })?;
+
+        finish_extraction(
+            shared,
+            top_level_extraction_dir,
+            &selected,
+            report_receiver,
+            handles,
+            restore_times,
+        )
+    }
 
-                    /* Send this consumer pipeline's index to the zip-input-reader thread when it's
-                     * ready to receive new input. */
-                    let queue_sender = queue_sender.clone();
-                    #[allow(clippy::single_match)]
-                    let notify_readiness = move || match queue_sender.send(consumer_index) {
-                        Ok(()) => (),
-                        /* Disconnected; this is expected to occur at the end of extraction. */
-                        Err(mpsc::SendError(_)) => (),
-                    };
+    #[cfg(test)]
+    mod test {
+        use tempdir::TempDir;
 
-                    /* (8) Write decompressed entries to the preallocated output handles. */
-                    thread::Builder::new()
-                        .name(format!("zip-output-writer-{}", consumer_index))
-                        .spawn_scoped(
-                            scope,
-                            wrap_spawn_err(err_sender.clone(), move || {
-                                let uncompressed_receiver = uncompressed_receiver;
-                                let mut uncompressed_read_end = uncompressed_read_end;
+        use std::io::{prelude::*, Cursor};
 
-                                #[cfg(target_os = "linux")]
-                                let mut s = PipeReadSplicer;
-                                #[cfg(not(target_os = "linux"))]
-                                let mut splice_buf: Box<[u8]> =
-                                    vec![0u8; splice_read_buffer_length].into_boxed_slice();
-                                #[cfg(not(target_os = "linux"))]
-                                let mut s = PipeReadBufferSplicer::new(&mut splice_buf);
+        use crate::compression::CompressionMethod;
+        use crate::write::{SimpleFileOptions, ZipWriter};
 
-                                for (entry, mut output_file) in uncompressed_receiver.iter() {
-                                    s.splice_to_file_all(
-                                        &mut uncompressed_read_end,
-                                        (&mut output_file, 0),
-                                        entry.uncompressed_size.try_into().unwrap(),
-                                    )?;
-                                    let output_file = output_file.into_file();
-                                    output_file.sync_data()?;
-                                    mem::drop(output_file);
-                                }
+        use super::*;
 
-                                Ok(())
-                            }),
+        #[test]
+        fn subtree_selects_only_the_directory() {
+            for prefix in ["docs", "docs/"] {
+                let selector = EntrySelector::subtree(prefix);
+                assert!(selector.selects("docs/"));
+                assert!(selector.selects("docs/a/b"));
+                assert!(!selector.selects("docs"));
+                assert!(!selector.selects("docs2/a"));
+                assert!(!selector.selects("docsfile"));
+            }
+            assert!(EntrySelector::subtree("").selects("docsfile"));
+        }
+
+        #[test]
+        fn pool_extraction() {
+            #[cfg(unix)]
+            use std::os::unix::fs::PermissionsExt;
+
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            let opts = SimpleFileOptions::default();
+            zip.add_directory("a/b", opts.unix_permissions(0o700))
+                .unwrap();
+            zip.start_file("a/b/c", opts.unix_permissions(0o640))
+                .unwrap();
+            zip.write_all(&[1; 10000]).unwrap();
+            zip.start_file("d", opts.compression_method(CompressionMethod::Stored))
+                .unwrap();
+            zip.write_all(b"stored").unwrap();
+            zip.start_file("e", opts.with_deprecated_encryption(b"password"))
+                .unwrap();
+            zip.write_all(b"encrypted").unwrap();
+            zip.add_symlink("f", "a/b/c", opts).unwrap();
+            let zip = ZipArchive::new(Cursor::new(zip.finish().unwrap().into_inner())).unwrap();
+
+            let td = TempDir::new("pipeline-test").unwrap();
+            let params = ExtractionParameters {
+                decompression_threads: 2,
+                decompression_copy_buffer_length: 100,
+                password: Some(ExtractionPassword::Fixed(b"password".to_vec())),
+                ..Default::default()
+            };
+            let report = pool_extract(&zip, td.path(), params).unwrap();
+            assert_eq!(
+                report
+                    .entries
+                    .iter()
+                    .map(|entry| (entry.name.as_str(), entry.method.unwrap()))
+                    .collect::<Vec<_>>(),
+                [
+                    ("a/b/", ExtractionMethod::Directory),
+                    ("a/b/c", ExtractionMethod::Pooled),
+                    ("d", ExtractionMethod::Pooled),
+                    ("e", ExtractionMethod::Pooled),
+                    ("f", ExtractionMethod::Symlink),
+                ]
+            );
+            assert_eq!(fs::read(td.path().join("a/b/c")).unwrap(), [1; 10000]);
+            assert_eq!(fs::read(td.path().join("d")).unwrap(), b"stored");
+            assert_eq!(fs::read(td.path().join("e")).unwrap(), b"encrypted");
+            #[cfg(unix)]
+            {
+                assert_eq!(fs::read(td.path().join("f")).unwrap(), [1; 10000]);
+                let mode = |path: &str| {
+                    fs::metadata(td.path().join(path))
+                        .unwrap()
+                        .permissions()
+                        .mode()
+                        & 0o777
+                };
+                assert_eq!(mode("a/b"), 0o700);
+                assert_eq!(mode("a/b/c"), 0o640);
+            }
+            #[cfg(not(unix))]
+            assert_eq!(fs::read(td.path().join("f")).unwrap(), b"a/b/c");
+        }
+
+        #[test]
+        fn pool_extraction_without_threads() {
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            zip.start_file("a", SimpleFileOptions::default()).unwrap();
+            zip.write_all(b"a").unwrap();
+            let zip = ZipArchive::new(Cursor::new(zip.finish().unwrap().into_inner())).unwrap();
+
+            let td = TempDir::new("pipeline-test").unwrap();
+            let params = ExtractionParameters {
+                decompression_threads: 0,
+                ..Default::default()
+            };
+            let report = pool_extract(&zip, td.path(), params).unwrap();
+            assert!(report.is_complete());
+            assert_eq!(fs::read(td.path().join("a")).unwrap(), b"a");
+        }
+    }
+}
+
+#[cfg(unix)]
+pub mod split_extraction {
+    use std::fs;
+    use std::io::{self, Read, Seek, SeekFrom};
+    use std::mem::{self, MaybeUninit};
+    use std::path::Path;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Instant;
+
+    use crate::compression::CompressionMethod;
+    use crate::read::extract::{entry_times, set_file_handle_times};
+    use crate::read::limits::Budget;
+    use crate::read::{EntrySource, ReadAt, ZipArchive};
+    use crate::result::ZipError;
+    use crate::spec::FixedSizeBlock;
+    use crate::types::{ZipFileData, ZipLocalEntryBlock};
+
+    #[cfg(not(target_os = "linux"))]
+    use crate::read::split::pipe::unix::{PipeReadBufferSplicer, PipeWriteBufferSplicer};
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    use crate::read::split::uring::{Job, OutputRing, Source};
+    #[cfg(target_os = "linux")]
+    use crate::read::split::{
+        file::linux::FileCopy,
+        pipe::linux::{PipeReadSplicer, PipeWriteSplicer},
+    };
+    use crate::read::split::{
+        file::{
+            unix::{FileBufferCopy, FileInput, FileOutput},
+            CopyRange, InputFile, MemoryInput, OutputFile, ReadAtInput,
+        },
+        pipe::{
+            unix::{create_pipe, WritePipe},
+            ReadSplicer, WriteSplicer,
+        },
+        util::{copy_via_buf, TakeWrite},
+    };
+
+    use super::extraction::{
+        finish_extraction, open_entry_reader, prepare_entries, read_error, wrap_spawn_err,
+        ExtractionMethod, ExtractionParameters, ExtractionReport, Reporter, SplitExtractionError,
+    };
+    use super::handle_creation::ZipDataHandle;
+
+    /* TODO: make this share code with find_data_start()! */
+    fn get_or_find_data_start<InF>(data: &ZipFileData, input_file: InF) -> Result<u64, ZipError>
+    where
+        InF: InputFile,
+    {
+        // TODO: use .get_or_try_init() once stabilized to provide a closure returning a Result!
+        if let Some(data_start) = data.data_start.get() {
+            return Ok(*data_start);
+        }
+
+        let block = {
+            let block: MaybeUninit<[u8; mem::size_of::<ZipLocalEntryBlock>()]> =
+                MaybeUninit::uninit();
+            let mut block: [MaybeUninit<u8>; mem::size_of::<ZipLocalEntryBlock>()] =
+                unsafe { mem::transmute(block) };
+
+            input_file.pread_all(data.header_start, &mut block[..])?;
+
+            let block: MaybeUninit<[u8; mem::size_of::<ZipLocalEntryBlock>()]> =
+                unsafe { mem::transmute(block) };
+            unsafe { block.assume_init() }
+        };
+        // Parse static-sized fields and check the magic value.
+        let block = ZipLocalEntryBlock::interpret(block.as_ref())?;
+
+        // Calculate the end of the local header from the fields we just parsed.
+        let variable_fields_len: u64 =
+            // Each of these fields must be converted to u64 before adding, as the result may
+            // easily overflow a u16.
+            block.file_name_length as u64 + block.extra_field_length as u64;
+        let local_entry_block_size: u64 = mem::size_of::<ZipLocalEntryBlock>().try_into().unwrap();
+        let data_start: u64 = data.header_start + local_entry_block_size + variable_fields_len;
+
+        // Set the value so we don't have to read it again.
+        match data.data_start.set(data_start) {
+            Ok(()) => (),
+            // If the value was already set in the meantime, ensure it matches.
+            Err(_) => {
+                assert_eq!(*data.data_start.get().unwrap(), data_start);
+            }
+        }
+        Ok(data_start)
+    }
+
+    mod private {
+        use super::*;
+
+        /// Copies entry data out of an archive for the pipeline threads.
+        pub trait PipelineInput<'a>: InputFile + Copy + Send + Sync {
+            /// How a stored entry will be copied into `output`.
+            fn copy_method(&self, output: &FileOutput) -> io::Result<ExtractionMethod>;
+
+            /// Copies `len` bytes from `start` to the beginning of `output`, as `method` says to.
+            /// `buf` is used if the bytes have to be read into userspace first.
+            fn copy_stored(
+                &self,
+                method: ExtractionMethod,
+                start: u64,
+                output: &mut FileOutput,
+                len: usize,
+                buf: &mut [u8],
+            ) -> io::Result<()>;
+
+            /// Writes `len` bytes from `start` into `pipe`. `buf` is used if the bytes have to be
+            /// read into userspace first.
+            fn splice_into(
+                &self,
+                start: u64,
+                pipe: &mut WritePipe,
+                len: usize,
+                buf: &mut [u8],
+            ) -> io::Result<()>;
+
+            /// Where an [`OutputRing`] should copy `len` bytes from `start` out of, or `None` if
+            /// they have to be copied with [`Self::copy_stored()`] instead.
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            fn ring_source(&self, start: u64, len: usize) -> io::Result<Option<Source<'a>>>;
+        }
+
+        pub trait Sealed {
+            type Input<'a>: PipelineInput<'a>
+            where
+                Self: 'a;
+
+            fn pipeline_input(&self) -> io::Result<Self::Input<'_>>;
+        }
+    }
+    use private::{PipelineInput, Sealed};
+
+    /// A reader that [`split_extract()`] can extract a [`ZipArchive`] from.
+    ///
+    /// This is implemented for [`fs::File`], which is read with `pread()` from several threads at
+    /// once, and for [`io::Cursor`] over any bytes in memory: a `Vec<u8>`, a `&[u8]`, or a memory
+    /// map such as `memmap2::Mmap`. Entries in memory are written straight into the output files
+    /// and decompression pipes, so an archive that has already been downloaded doesn't need to be
+    /// written to a temporary file first.
+    ///
+    /// Any other [`ReadAt`] reader can be extracted from by wrapping it in a
+    /// [`PositionalSource`]. The trait itself is sealed, as how each source is copied from is an
+    /// implementation detail of the pipeline.
+    pub trait ExtractionSource: Sealed + ReadAt {}
+
+    impl ExtractionSource for fs::File {}
+
+    impl Sealed for fs::File {
+        type Input<'a> = FileInput<'a>;
+
+        fn pipeline_input(&self) -> io::Result<FileInput<'_>> {
+            FileInput::new(self)
+        }
+    }
+
+    impl<'fd> PipelineInput<'fd> for FileInput<'fd> {
+        fn copy_method(&self, output: &FileOutput) -> io::Result<ExtractionMethod> {
+            /* Linux can map pages from one file to another directly, without copying through
+             * userspace, but only if the files are located on the same device. */
+            #[cfg(target_os = "linux")]
+            if self.on_same_device(output)? {
+                return Ok(ExtractionMethod::CopyFileRange);
+            }
+            let _ = output;
+            Ok(ExtractionMethod::BufferCopy)
+        }
+
+        fn copy_stored(
+            &self,
+            method: ExtractionMethod,
+            start: u64,
+            output: &mut FileOutput,
+            len: usize,
+            buf: &mut [u8],
+        ) -> io::Result<()> {
+            #[cfg(target_os = "linux")]
+            if method == ExtractionMethod::CopyFileRange {
+                return FileCopy::new().copy_file_range_all((self, start), (output, 0), len);
+            }
+            debug_assert_eq!(method, ExtractionMethod::BufferCopy);
+            FileBufferCopy::new(buf).copy_file_range_all((self, start), (output, 0), len)
+        }
+
+        fn splice_into(
+            &self,
+            start: u64,
+            pipe: &mut WritePipe,
+            len: usize,
+            buf: &mut [u8],
+        ) -> io::Result<()> {
+            #[cfg(target_os = "linux")]
+            {
+                let _ = buf;
+                PipeWriteSplicer::new().splice_from_file_all((self, start), pipe, len)
+            }
+            #[cfg(not(target_os = "linux"))]
+            PipeWriteBufferSplicer::new(buf).splice_from_file_all((self, start), pipe, len)
+        }
+
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        fn ring_source(&self, start: u64, _len: usize) -> io::Result<Option<Source<'fd>>> {
+            Ok(Some(Source::File(*self, start)))
+        }
+    }
+
+    impl<T: AsRef<[u8]>> ExtractionSource for io::Cursor<T> {}
+
+    impl<T: AsRef<[u8]>> Sealed for io::Cursor<T> {
+        type Input<'a>
+            = MemoryInput<'a>
+        where
+            T: 'a;
+
+        fn pipeline_input(&self) -> io::Result<MemoryInput<'_>> {
+            Ok(MemoryInput::new(self.get_ref().as_ref()))
+        }
+    }
+
+    /// Borrows exactly `len` bytes of an entry's data from memory.
+    fn entry_slice(input: MemoryInput<'_>, start: u64, len: usize) -> io::Result<&[u8]> {
+        let data = input.slice(start, len)?;
+        if data.len() < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "entry data extends past the end of the archive",
+            ));
+        }
+        Ok(data)
+    }
+
+    impl<'a> PipelineInput<'a> for MemoryInput<'a> {
+        fn copy_method(&self, _output: &FileOutput) -> io::Result<ExtractionMethod> {
+            Ok(ExtractionMethod::MemoryCopy)
+        }
+
+        fn copy_stored(
+            &self,
+            _method: ExtractionMethod,
+            start: u64,
+            output: &mut FileOutput,
+            len: usize,
+            _buf: &mut [u8],
+        ) -> io::Result<()> {
+            output.pwrite_all(0, entry_slice(*self, start, len)?)
+        }
+
+        fn splice_into(
+            &self,
+            start: u64,
+            pipe: &mut WritePipe,
+            len: usize,
+            _buf: &mut [u8],
+        ) -> io::Result<()> {
+            use io::Write;
+
+            pipe.write_all(entry_slice(*self, start, len)?)
+        }
+
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        fn ring_source(&self, start: u64, len: usize) -> io::Result<Option<Source<'a>>> {
+            entry_slice(*self, start, len).map(|data| Some(Source::Memory(data)))
+        }
+    }
+
+    /// Wraps any [`ReadAt`] reader so that [`split_extract()`] can extract from it.
+    ///
+    /// Unlike a [`fs::File`], the reader can't be handed to the kernel, so stored entries are
+    /// copied through a buffer and everything else is written into the decompression pipes from
+    /// userspace. Reads still happen from several threads at once, so the reader must be [`Sync`].
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::io::{Cursor, Write};
+    /// use zip::read::{split_extract, ExtractionParameters, PositionalSource};
+    ///
+    /// let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    /// zip.start_file("a.txt", zip::write::SimpleFileOptions::default())?;
+    /// zip.write_all(b"hello")?;
+    /// let reader = zip.finish()?;
+    ///
+    /// let archive = zip::ZipArchive::new(PositionalSource::new(reader)?)?;
+    /// let dir = std::env::temp_dir().join("positional-source-example");
+    /// let report = split_extract(&archive, &dir, ExtractionParameters::default())?;
+    /// assert!(report.is_complete());
+    /// assert_eq!(std::fs::read(dir.join("a.txt"))?, b"hello");
+    /// # std::fs::remove_dir_all(dir)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[derive(Debug)]
+    pub struct PositionalSource<R> {
+        inner: R,
+        len: u64,
+    }
+
+    impl<R: Seek> PositionalSource<R> {
+        /// Wraps `inner`, whose length is found by seeking to its end. It's rewound afterwards.
+        pub fn new(mut inner: R) -> io::Result<Self> {
+            let len = inner.seek(SeekFrom::End(0))?;
+            inner.rewind()?;
+            Ok(Self { inner, len })
+        }
+    }
+
+    impl<R> PositionalSource<R> {
+        /// Gets a reference to the underlying reader.
+        pub const fn get_ref(&self) -> &R {
+            &self.inner
+        }
+
+        /// Unwraps this `PositionalSource`, returning the underlying reader.
+        pub fn into_inner(self) -> R {
+            self.inner
+        }
+    }
+
+    impl<R: Read> Read for PositionalSource<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl<R: Seek> Seek for PositionalSource<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    impl<R: ReadAt> ReadAt for PositionalSource<R> {
+        fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            self.inner.read_at(buf, offset)
+        }
+    }
+
+    impl<R: ReadAt + Sync> ExtractionSource for PositionalSource<R> {}
+
+    impl<R: ReadAt + Sync> Sealed for PositionalSource<R> {
+        type Input<'a>
+            = ReadAtInput<'a>
+        where
+            R: 'a;
+
+        fn pipeline_input(&self) -> io::Result<ReadAtInput<'_>> {
+            Ok(ReadAtInput::new(&self.inner, self.len))
+        }
+    }
+
+    impl<'a> PipelineInput<'a> for ReadAtInput<'a> {
+        fn copy_method(&self, _output: &FileOutput) -> io::Result<ExtractionMethod> {
+            Ok(ExtractionMethod::BufferCopy)
+        }
+
+        fn copy_stored(
+            &self,
+            _method: ExtractionMethod,
+            start: u64,
+            output: &mut FileOutput,
+            len: usize,
+            buf: &mut [u8],
+        ) -> io::Result<()> {
+            let mut copied: u64 = 0;
+            for chunk_len in chunk_lengths(len, buf.len()) {
+                let chunk = &mut buf[..chunk_len];
+                self.read_exact_at(start + copied, chunk)?;
+                output.pwrite_all(copied, chunk)?;
+                copied += u64::try_from(chunk_len).unwrap();
+            }
+            Ok(())
+        }
+
+        fn splice_into(
+            &self,
+            start: u64,
+            pipe: &mut WritePipe,
+            len: usize,
+            buf: &mut [u8],
+        ) -> io::Result<()> {
+            use io::Write;
+
+            let mut copied: u64 = 0;
+            for chunk_len in chunk_lengths(len, buf.len()) {
+                let chunk = &mut buf[..chunk_len];
+                self.read_exact_at(start + copied, chunk)?;
+                pipe.write_all(chunk)?;
+                copied += u64::try_from(chunk_len).unwrap();
+            }
+            Ok(())
+        }
+
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        fn ring_source(&self, _start: u64, _len: usize) -> io::Result<Option<Source<'a>>> {
+            Ok(None)
+        }
+    }
+
+    /// Splits `len` bytes into pieces of at most `buf_len`.
+    fn chunk_lengths(len: usize, buf_len: usize) -> impl Iterator<Item = usize> {
+        assert!(buf_len > 0);
+        (0..len)
+            .step_by(buf_len)
+            .map(move |offset| buf_len.min(len - offset))
+    }
+
+    /// How many output files the zip-uring thread keeps in flight at once.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    const URING_FILES_IN_FLIGHT: usize = 32;
+
+    /// Extract all entries in parallel using a pipelined strategy.
+    ///
+    /// The archive can be read from a file or from memory, as described for [`ExtractionSource`].
+    ///
+    /// Symlinks are created and checked as [`ZipArchive::extract`] would, according to
+    /// [`ExtractionParameters::symlink_policy`]. Their targets are read before anything else is
+    /// extracted, and extraction fails if any other entry lies underneath one.
+    ///
+    /// The [`Limits`](crate::read::Limits) the archive was read with are checked against the
+    /// sizes in its headers before anything is written, then enforced again as each entry is
+    /// decompressed.
+    pub fn split_extract<R: ExtractionSource>(
+        archive: &ZipArchive<R>,
+        top_level_extraction_dir: &Path,
+        params: ExtractionParameters,
+    ) -> Result<ExtractionReport, SplitExtractionError> {
+        let ZipArchive {
+            reader: ref input,
+            ref shared,
+            ..
+        } = archive;
+        let (reporter, report_receiver) = Reporter::new(params.continue_on_error);
+        let budget = &Budget::new(shared.limits);
+
+        /* (0) Create a wrapper over the input file which uses pread() to read from multiple
+         *     sections in parallel across a thread pool, or over the archive's bytes in memory. */
+        let input_file = input.pipeline_input()?;
+
+        /* (1-3) Select and check entries, then create everything they'll be extracted into. */
+        let (selected, mut handles) = prepare_entries(
+            shared,
+            input,
+            top_level_extraction_dir,
+            &params,
+            &reporter,
+            budget,
+        )?;
+
+        let ExtractionParameters {
+            decompression_threads,
+            decompression_copy_buffer_length,
+            file_range_copy_buffer_length,
+            #[cfg(not(target_os = "linux"))]
+            splice_read_buffer_length,
+            #[cfg(not(target_os = "linux"))]
+            splice_write_buffer_length,
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            io_uring,
+            restore_times,
+            password,
+            ..
+        } = params;
+        let password = &password;
+        #[allow(clippy::mutable_key_type)]
+        let file_handle_mapping = mem::take(&mut handles.file_handle_mapping);
+        let set_times = move |file: &fs::File, entry: &ZipFileData| -> io::Result<()> {
+            match entry_times(entry).filter(|_| restore_times) {
+                Some((accessed, modified)) => {
+                    set_file_handle_times(file, Some(accessed), Some(modified))
+                }
+                None => Ok(()),
+            }
+        };
+
+        let selected = &selected[..];
+        let reporter = &reporter;
+        /* With io_uring, output files are left for the zip-uring thread to set the length of. */
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        let set_len_later = io_uring;
+        #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+        let set_len_later = false;
+
+        thread::scope(|scope| {
+            /* (4) Create n parallel consumer pipelines. Threads are spawned into the scope, so
+             *     panics get propagated automatically, and all threads are joined at the end of the
+             *     scope. wrap_spawn_err() is used to enable thread closures to return a Result and
+             *     asynchronously propagate the error back up to the main scope thread. */
+            let (err_sender, err_receiver) = mpsc::channel::<SplitExtractionError>();
+
+            /* (9) With io_uring, a single thread finishes the output files of every pipeline: it
+             *     copies in stored entries, then sets the times, length and fsync() of each file
+             *     and reports it, with many files in flight on one ring. */
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            let ring_sender = if io_uring {
+                let mut ring =
+                    OutputRing::new(URING_FILES_IN_FLIGHT, file_range_copy_buffer_length)?;
+                let (ring_sender, ring_receiver) =
+                    mpsc::channel::<Job<'_, (&ZipFileData, ExtractionMethod, Instant)>>();
+                thread::Builder::new()
+                    .name("zip-uring".to_string())
+                    .spawn_scoped(
+                        scope,
+                        wrap_spawn_err(err_sender.clone(), move || {
+                            ring.run(
+                                ring_receiver,
+                                |&(entry, _, _), file| set_times(file, entry),
+                                |(entry, method, started), result| {
+                                    let result = result.map_err(SplitExtractionError::from);
+                                    reporter.finish(entry, Some(method), started, result)
+                                },
+                            )
+                        }),
+                    )?;
+                Some(ring_sender)
+            } else {
+                None
+            };
+            /* This channel is used to notify the zip-input-reader thread when a consumer has
+             * completed decompressing/copying an entry, and is ready to receive new input. This is
+             * neither round-robin nor LRU: no thread is prioritized over any other, and new
+             * entries are sent off to workers in order of when they notify the zip-input-reader
+             * thread of their readiness. */
+            let (queue_sender, queue_receiver) = mpsc::channel::<usize>();
+            let input_writer_infos: Vec<mpsc::Sender<(&ZipFileData, u64, FileOutput)>> = (0
+                ..decompression_threads.max(1))
+                .map(|consumer_index| {
+                    /* Create pipes to write entries through. */
+                    let (compressed_read_end, compressed_write_end) = create_pipe()?;
+                    let (uncompressed_read_end, uncompressed_write_end) = create_pipe()?;
+
+                    /* Create channels to send entries through. */
+                    let (read_send, read_recv) = mpsc::channel::<(&ZipFileData, u64, FileOutput)>();
+                    let (compressed_sender, compressed_receiver) =
+                        mpsc::channel::<(&ZipFileData, FileOutput, Instant)>();
+                    let (uncompressed_sender, uncompressed_receiver) =
+                        mpsc::channel::<(&ZipFileData, FileOutput, Instant)>();
+
+                    /* Send this consumer pipeline's index to the zip-input-reader thread when it's
+                     * ready to receive new input. */
+                    let queue_sender = queue_sender.clone();
+                    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+                    let (writer_ring_sender, reader_ring_sender) =
+                        (ring_sender.clone(), ring_sender.clone());
+                    #[allow(clippy::single_match)]
+                    let notify_readiness = move || match queue_sender.send(consumer_index) {
+                        Ok(()) => (),
+                        /* Disconnected; this is expected to occur at the end of extraction. */
+                        Err(mpsc::SendError(_)) => (),
+                    };
+
+                    /* (8) Write decompressed entries to the preallocated output handles. */
+                    thread::Builder::new()
+                        .name(format!("zip-output-writer-{}", consumer_index))
+                        .spawn_scoped(
+                            scope,
+                            wrap_spawn_err(err_sender.clone(), move || {
+                                let uncompressed_receiver = uncompressed_receiver;
+                                let mut uncompressed_read_end = uncompressed_read_end;
+
+                                #[cfg(target_os = "linux")]
+                                let mut s = PipeReadSplicer;
+                                #[cfg(not(target_os = "linux"))]
+                                let mut splice_buf: Box<[u8]> =
+                                    vec![0u8; splice_read_buffer_length].into_boxed_slice();
+                                #[cfg(not(target_os = "linux"))]
+                                let mut s = PipeReadBufferSplicer::new(&mut splice_buf);
+
+                                for (entry, mut output_file, started) in
+                                    uncompressed_receiver.iter()
+                                {
+                                    s.splice_to_file_all(
+                                        &mut uncompressed_read_end,
+                                        (&mut output_file, 0),
+                                        entry.uncompressed_size.try_into().unwrap(),
+                                    )?;
+                                    let output_file = output_file.into_file();
+                                    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+                                    if let Some(ring_sender) = writer_ring_sender.as_ref() {
+                                        let job = Job {
+                                            source: Source::Written,
+                                            output: output_file,
+                                            len: entry.uncompressed_size,
+                                            tag: (entry, ExtractionMethod::Decompression, started),
+                                        };
+                                        /* The zip-uring thread only stops early after failing
+                                         * with an error of its own. */
+                                        if ring_sender.send(job).is_err() {
+                                            return Ok(());
+                                        }
+                                        continue;
+                                    }
+                                    set_times(&output_file, entry)?;
+                                    output_file.sync_data()?;
+                                    mem::drop(output_file);
+                                    reporter.finish(
+                                        entry,
+                                        Some(ExtractionMethod::Decompression),
+                                        started,
+                                        Ok(entry.uncompressed_size),
+                                    )?;
+                                }
+
+                                Ok(())
+                            }),
                         )?;
 
                     /* (7) Read compressed entries, decompress them, then write them to the output
@@ -892,39 +2088,65 @@ pub mod split_extraction {
                                 let mut buffer_allocation: Box<[u8]> =
                                     vec![0u8; decompression_copy_buffer_length].into_boxed_slice();
 
-                                for (entry, output_file) in compressed_receiver.iter() {
+                                for (entry, output_file, started) in compressed_receiver.iter() {
                                     /* Construct the decompressing reader. */
-                                    let limited_reader = ((&mut compressed_read_end)
+                                    let compressed_size = entry.compressed_size;
+                                    let mut compressed_entry = ((&mut compressed_read_end)
                                         as &mut dyn Read)
-                                        .take(entry.compressed_size);
-                                    let crypto_reader = make_crypto_reader(
-                                        entry.compression_method,
-                                        entry.crc32,
-                                        None,
-                                        false,
-                                        limited_reader,
-                                        None,
-                                        None,
-                                        #[cfg(feature = "aes-crypto")]
-                                        entry.compressed_size,
-                                    )?;
-                                    let mut decompressing_reader = make_reader(
-                                        entry.compression_method,
-                                        entry.crc32,
-                                        crypto_reader,
-                                    )?;
+                                        .take(compressed_size);
                                     let mut limited_writer = TakeWrite::take(
                                         uncompressed_write_end.by_ref(),
                                         entry.uncompressed_size,
                                     );
-                                    /* Send the entry and output file to the writer thread before
-                                     * writing this entry's decompressed contents. */
-                                    uncompressed_sender.send((entry, output_file)).unwrap();
-                                    copy_via_buf(
-                                        &mut decompressing_reader,
-                                        &mut limited_writer,
-                                        &mut buffer_allocation,
-                                    )?;
+                                    let mut sent = false;
+                                    let result = open_entry_reader(
+                                        entry,
+                                        EntrySource::Reader(
+                                            &mut compressed_entry as &mut dyn io::Read,
+                                        )
+                                        .take(compressed_size),
+                                        password.as_ref(),
+                                    )
+                                    .map_err(SplitExtractionError::from)
+                                    .and_then(
+                                        |decompressing_reader| {
+                                            let mut decompressing_reader = budget
+                                                .reader(decompressing_reader, move |_| {
+                                                    compressed_size
+                                                });
+                                            /* Send the entry and output file to the writer thread
+                                             * before writing this entry's decompressed contents. */
+                                            uncompressed_sender
+                                                .send((entry, output_file, started))
+                                                .unwrap();
+                                            sent = true;
+                                            copy_via_buf(
+                                                &mut decompressing_reader,
+                                                &mut limited_writer,
+                                                &mut buffer_allocation,
+                                            )
+                                            .map_err(read_error)
+                                        },
+                                    );
+                                    /* The writer thread reports successful entries once they're
+                                     * written out. */
+                                    let Err(e) = result else {
+                                        continue;
+                                    };
+                                    let method = Some(ExtractionMethod::Decompression);
+                                    reporter.finish(entry, method, started, Err(e))?;
+                                    /* We're carrying on, so leave both pipes as the other threads
+                                     * expect them: consume the rest of this entry's compressed
+                                     * data, and fill out the decompressed data the writer thread
+                                     * is waiting on, if any. The output file is removed later. */
+                                    io::copy(&mut compressed_entry, &mut io::sink())?;
+                                    if sent {
+                                        let remaining = limited_writer.limit();
+                                        io::copy(
+                                            &mut io::repeat(0).take(remaining),
+                                            &mut limited_writer,
+                                        )?;
+                                    }
                                 }
 
                                 Ok(())
@@ -945,15 +2167,11 @@ pub mod split_extraction {
 
                                 let mut copy_buf: Box<[u8]> =
                                     vec![0u8; file_range_copy_buffer_length].into_boxed_slice();
-                                let mut buffer_c = FileBufferCopy::new(&mut copy_buf);
-
-                                #[cfg(target_os = "linux")]
-                                let mut s = PipeWriteSplicer::new();
+                                /* splice() on Linux doesn't need a buffer, so sources which can't
+                                 * be spliced from borrow `copy_buf` instead. */
                                 #[cfg(not(target_os = "linux"))]
                                 let mut splice_buf: Box<[u8]> =
                                     vec![0u8; splice_write_buffer_length].into_boxed_slice();
-                                #[cfg(not(target_os = "linux"))]
-                                let mut s = PipeWriteBufferSplicer::new(&mut splice_buf);
 
                                 /* Notify readiness *after* setting up copy buffers, but *before*
                                  * waiting on any entries sent from the zip-input-reader thread,
@@ -962,50 +2180,103 @@ pub mod split_extraction {
                                 notify_readiness();
 
                                 for (entry, data_start, mut output_file) in read_recv.iter() {
+                                    let started = Instant::now();
+
                                     /* If uncompressed, we can use copy_file_range() directly, and
-                                     * avoid splicing through our decompression pipeline. */
-                                    if entry.compression_method == CompressionMethod::Stored {
+                                     * avoid splicing through our decompression pipeline. Encrypted
+                                     * entries still need to be decrypted there. */
+                                    if entry.compression_method == CompressionMethod::Stored
+                                        && !entry.encrypted
+                                    {
                                         assert_eq!(entry.compressed_size, entry.uncompressed_size);
                                         let copy_len: usize =
                                             entry.uncompressed_size.try_into().unwrap();
 
-                                        #[cfg(target_os = "linux")]
-                                        if input_file.on_same_device(&output_file)? {
-                                            /* Linux can map pages from one file to another
-                                             * directly, without copying through userspace, but
-                                             * only if the files are located on the same device. */
-                                            let mut file_c = FileCopy::new();
-                                            file_c.copy_file_range_all(
-                                                (&input_file, data_start),
-                                                (&mut output_file, 0),
-                                                copy_len,
-                                            )?;
-                                        } else {
-                                            buffer_c.copy_file_range_all(
-                                                (&input_file, data_start),
-                                                (&mut output_file, 0),
-                                                copy_len,
-                                            )?;
+                                        let method = input_file.copy_method(&output_file)?;
+
+                                        /* With io_uring, anything but copy_file_range() is left to
+                                         * the zip-uring thread, so we can move on to the next entry
+                                         * straight away. */
+                                        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+                                        if let Some(ring_sender) = reader_ring_sender.as_ref() {
+                                            let job = budget
+                                                .consume(entry.uncompressed_size)
+                                                .map_err(SplitExtractionError::from)
+                                                .and_then(|()| {
+                                                    let source = if method
+                                                        == ExtractionMethod::CopyFileRange
+                                                    {
+                                                        None
+                                                    } else {
+                                                        input_file
+                                                            .ring_source(data_start, copy_len)?
+                                                    };
+                                                    let source = match source {
+                                                        Some(source) => source,
+                                                        None => {
+                                                            input_file.copy_stored(
+                                                                method,
+                                                                data_start,
+                                                                &mut output_file,
+                                                                copy_len,
+                                                                &mut copy_buf,
+                                                            )?;
+                                                            Source::Written
+                                                        }
+                                                    };
+                                                    Ok(Job {
+                                                        source,
+                                                        output: output_file.into_file(),
+                                                        len: entry.uncompressed_size,
+                                                        tag: (entry, method, started),
+                                                    })
+                                                });
+                                            match job {
+                                                Ok(job) => {
+                                                    if ring_sender.send(job).is_err() {
+                                                        return Ok(());
+                                                    }
+                                                }
+                                                Err(e) => reporter.finish(
+                                                    entry,
+                                                    Some(method),
+                                                    started,
+                                                    Err(e),
+                                                )?,
+                                            }
+                                            notify_readiness();
+                                            continue;
                                         }
-                                        #[cfg(not(target_os = "linux"))]
-                                        buffer_c.copy_file_range_all(
-                                            (&input_file, data_start),
-                                            (&mut output_file, 0),
-                                            copy_len,
-                                        )?;
 
-                                        let output_file = output_file.into_file();
-                                        /* fsync(2) says setting the file length is a form of
-                                         * metadata that requires fsync() over fdatasync(); it's
-                                         * unclear whether rust already performs that in the
-                                         * File::set_len() call performed in the OutputFile::new()
-                                         * constructor, but this shouldn't really matter for
-                                         * performance. */
-                                        output_file.sync_all()?;
-                                        /* This is done automatically, but this way we can ensure
-                                         * we've correctly avoided aliasing the output file in this
-                                         * branch. */
-                                        mem::drop(output_file);
+                                        let result = budget
+                                            .consume(entry.uncompressed_size)
+                                            .map_err(SplitExtractionError::from)
+                                            .and_then(|()| {
+                                                input_file.copy_stored(
+                                                    method,
+                                                    data_start,
+                                                    &mut output_file,
+                                                    copy_len,
+                                                    &mut copy_buf,
+                                                )?;
+
+                                                let output_file = output_file.into_file();
+                                                set_times(&output_file, entry)?;
+                                                /* fsync(2) says setting the file length is a form
+                                                 * of metadata that requires fsync() over
+                                                 * fdatasync(); it's unclear whether rust already
+                                                 * performs that in the File::set_len() call
+                                                 * performed in the OutputFile::new() constructor,
+                                                 * but this shouldn't really matter for
+                                                 * performance. */
+                                                output_file.sync_all()?;
+                                                /* This is done automatically, but this way we can
+                                                 * ensure we've correctly avoided aliasing the
+                                                 * output file in this branch. */
+                                                mem::drop(output_file);
+                                                Ok(entry.uncompressed_size)
+                                            });
+                                        reporter.finish(entry, Some(method), started, result)?;
 
                                         /* We're now completely done with this entry and have
                                          * closed the output file handle, so we can receive another
@@ -1018,7 +2289,9 @@ pub mod split_extraction {
                                      * synchronous computation) in a separate thread, to avoid
                                      * jumping back and forth in the call stack between i/o from the
                                      * kernel and in-memory computation in rust. */
-                                    compressed_sender.send((entry, output_file)).unwrap();
+                                    compressed_sender
+                                        .send((entry, output_file, started))
+                                        .unwrap();
 
                                     /* Write this uncompressed entry into the waiting pipe. Because
                                      * unix pipes have a constant non-configurable buffer size of
@@ -1034,10 +2307,15 @@ pub mod split_extraction {
                                     /* TODO: consider using rust-level ring buffers here with
                                      * configurable size on all platforms, trading greater memory
                                      * allocation for further I/O readahead throughput. */
-                                    s.splice_from_file_all(
-                                        (&input_file, data_start),
+                                    #[cfg(target_os = "linux")]
+                                    let splice_buf = &mut copy_buf;
+                                    #[cfg(not(target_os = "linux"))]
+                                    let splice_buf = &mut splice_buf;
+                                    input_file.splice_into(
+                                        data_start,
                                         &mut compressed_write_end,
                                         entry.compressed_size.try_into().unwrap(),
+                                        splice_buf,
                                     )?;
 
                                     /* Notify the zip-input-reader thread that we are ready to
@@ -1052,6 +2330,9 @@ pub mod split_extraction {
                     Ok(read_send)
                 })
                 .collect::<Result<_, SplitExtractionError>>()?;
+            /* The zip-uring thread stops once every pipeline has dropped its senders. */
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            mem::drop(ring_sender);
 
             /* (5) Iterate over each entry sequentially, farming it out to a pipe to decompress if
              *     needed. */
@@ -1073,12 +2354,16 @@ pub mod split_extraction {
 
                         /* Entries are ordered by their offset, so we will be going monotonically
                          * forward in the underlying file. */
-                        for entry in shared.files.values() {
+                        for &entry in selected.iter() {
                             /* We have already created all necessary directories, and we set any
                              * dir perms after extracting file contents. */
                             if entry.is_dir() || entry.is_dir_by_mode() {
                                 continue;
                             }
+                            /* Symlinks were created along with the other handles. */
+                            if entry.is_symlink() {
+                                continue;
+                            }
 
                             /* Create a handle to the memory location of this entry. This allows
                              * us to quickly test membership without hashing any
@@ -1089,9 +2374,12 @@ pub mod split_extraction {
                              * linux-specific wrapper. */
                             let output_file = file_handle_mapping.remove(&handle).unwrap();
                             /* Set the length of the output handle according to the known output
-                             * size. */
-                            let output_file =
-                                FileOutput::new(output_file, entry.uncompressed_size)?;
+                             * size, unless the zip-uring thread will. */
+                            let output_file = if set_len_later {
+                                FileOutput::with_extent(output_file, entry.uncompressed_size)
+                            } else {
+                                FileOutput::new(output_file, entry.uncompressed_size)?
+                            };
 
                             /* Get the start of data for this entry without mutating any state
                              * using pread. */
@@ -1118,14 +2406,17 @@ pub mod split_extraction {
                 return Err(err);
             }
 
-            /* (10) Set permissions on specified entries. */
-            /* TODO: consider parallelizing this with rayon's parallel iterators. */
-            for (entry_path, perms) in perms_todo.into_iter() {
-                fs::set_permissions(entry_path, perms)?;
-            }
-
             Ok(())
-        })
+        })?;
+
+        finish_extraction(
+            shared,
+            top_level_extraction_dir,
+            selected,
+            report_receiver,
+            handles,
+            restore_times,
+        )
     }
 
     #[cfg(test)]
@@ -1133,10 +2424,15 @@ pub mod split_extraction {
         use tempdir::TempDir;
         use tempfile;
 
-        use std::io::prelude::*;
+        use std::io::{prelude::*, Cursor};
+        use std::sync::Arc;
 
+        use crate::read::extract::SymlinkPolicy;
         use crate::write::{SimpleFileOptions, ZipWriter};
 
+        use super::super::extraction::{
+            pool_extract, EntrySelector, ExtractionPassword, MAX_SYMLINK_TARGET_LEN,
+        };
         use super::*;
 
         #[test]
@@ -1205,5 +2501,403 @@ pub mod split_extraction {
                 &fs::read(td.path().join("d/e")).unwrap()[..]
             );
         }
+
+        #[test]
+        fn limits() {
+            use crate::read::{Config, Limits};
+
+            let mut zip = ZipWriter::new(tempfile::tempfile().unwrap());
+            zip.start_file("a/b/c", SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(&[0; 1000]).unwrap();
+            let file = zip.finish().unwrap();
+
+            let limits = Limits {
+                max_total_size: Some(999),
+                ..Default::default()
+            };
+            let zip = ZipArchive::with_limits(Config::default(), limits, file).unwrap();
+            let td = TempDir::new("pipeline-test").unwrap();
+            assert!(matches!(
+                split_extract(&zip, td.path(), ExtractionParameters::default()),
+                Err(SplitExtractionError::Zip(ZipError::LimitExceeded(_)))
+            ));
+        }
+
+        #[test]
+        #[cfg(feature = "filetime")]
+        fn restore_times() {
+            use crate::DateTime;
+
+            let time = DateTime::from_date_and_time(1999, 1, 2, 3, 4, 6).unwrap();
+            let options = SimpleFileOptions::default().last_modified_time(time);
+            let mut zip = ZipWriter::new(tempfile::tempfile().unwrap());
+            zip.add_directory("a/", options).unwrap();
+            zip.start_file("a/b", options).unwrap();
+            zip.write_all(&[0; 1000]).unwrap();
+            zip.start_file("a/c", options.compression_method(CompressionMethod::Stored))
+                .unwrap();
+            zip.write_all(&[1; 1000]).unwrap();
+            let file = zip.finish().unwrap();
+            let zip = ZipArchive::new(file).unwrap();
+
+            let modified = |path: &Path| fs::metadata(path).unwrap().modified().unwrap();
+            let td = TempDir::new("pipeline-test").unwrap();
+            split_extract(&zip, td.path(), ExtractionParameters::default()).unwrap();
+            for name in ["a", "a/b", "a/c"] {
+                assert_eq!(modified(&td.path().join(name)), time.to_system_time());
+            }
+
+            let td = TempDir::new("pipeline-test").unwrap();
+            let params = ExtractionParameters {
+                restore_times: false,
+                ..Default::default()
+            };
+            split_extract(&zip, td.path(), params).unwrap();
+            assert!(modified(&td.path().join("a/b")) > time.to_system_time());
+        }
+
+        #[test]
+        fn passwords() {
+            let mut zip = ZipWriter::new(tempfile::tempfile().unwrap());
+            let opts = SimpleFileOptions::default();
+            zip.start_file("zip-crypto", opts.with_deprecated_encryption(b"first"))
+                .unwrap();
+            zip.write_all(b"zip-crypto").unwrap();
+            zip.start_file(
+                "stored",
+                opts.compression_method(CompressionMethod::Stored)
+                    .with_deprecated_encryption(b"first"),
+            )
+            .unwrap();
+            zip.write_all(b"stored").unwrap();
+            #[cfg(feature = "aes-crypto")]
+            {
+                zip.start_file(
+                    "aes",
+                    opts.with_aes_encryption(crate::AesMode::Aes256, "second"),
+                )
+                .unwrap();
+                zip.write_all(b"aes").unwrap();
+            }
+            zip.start_file("plain", opts).unwrap();
+            zip.write_all(b"plain").unwrap();
+            let file = zip.finish().unwrap();
+            let zip = ZipArchive::new(file).unwrap();
+
+            let extract = |password| {
+                let td = TempDir::new("pipeline-test").unwrap();
+                let params = ExtractionParameters {
+                    password,
+                    ..Default::default()
+                };
+                split_extract(&zip, td.path(), params).map(|_| td)
+            };
+            assert!(matches!(
+                extract(None),
+                Err(SplitExtractionError::Zip(ZipError::UnsupportedArchive(
+                    ZipError::PASSWORD_REQUIRED
+                )))
+            ));
+            #[cfg(feature = "aes-crypto")]
+            assert!(matches!(
+                extract(Some(ExtractionPassword::Fixed(b"first".to_vec()))),
+                Err(SplitExtractionError::Zip(ZipError::InvalidPassword))
+            ));
+
+            let td = extract(Some(ExtractionPassword::PerEntry(Arc::new(|name| {
+                Some(if name == "aes" { "second" } else { "first" }.into())
+            }))))
+            .unwrap();
+            for name in ["zip-crypto", "stored", "plain"] {
+                assert_eq!(fs::read_to_string(td.path().join(name)).unwrap(), name);
+            }
+            #[cfg(feature = "aes-crypto")]
+            assert_eq!(fs::read_to_string(td.path().join("aes")).unwrap(), "aes");
+        }
+
+        #[test]
+        fn symlinks() {
+            use crate::read::ExtractOptions;
+
+            let archive = |links: &[(&str, &str)]| {
+                let mut zip = ZipWriter::new(tempfile::tempfile().unwrap());
+                let opts = SimpleFileOptions::default();
+                zip.start_file("a/f", opts).unwrap();
+                zip.write_all(b"f").unwrap();
+                for (name, target) in links {
+                    zip.add_symlink(*name, *target, opts).unwrap();
+                }
+                ZipArchive::new(zip.finish().unwrap()).unwrap()
+            };
+
+            /* Links come out the same as with sequential extraction. */
+            let contained = [("a/l", "f"), ("b/up", "../a/l"), ("c", "a")];
+            let mut zip = archive(&contained);
+            let td = TempDir::new("pipeline-test").unwrap();
+            split_extract(&zip, td.path(), ExtractionParameters::default()).unwrap();
+            let sequential = TempDir::new("pipeline-test").unwrap();
+            zip.extract_with(&sequential, ExtractOptions::default())
+                .unwrap();
+            for (name, target) in contained {
+                assert_eq!(
+                    fs::read_link(td.path().join(name)).unwrap(),
+                    Path::new(target)
+                );
+                assert_eq!(
+                    fs::read_link(sequential.path().join(name)).unwrap(),
+                    Path::new(target)
+                );
+            }
+            assert_eq!(fs::read_to_string(td.path().join("b/up")).unwrap(), "f");
+
+            /* Escaping links are rejected or rewritten. */
+            let zip = archive(&[("a/up", "../../x")]);
+            let td = TempDir::new("pipeline-test").unwrap();
+            assert!(matches!(
+                split_extract(&zip, td.path(), ExtractionParameters::default()),
+                Err(SplitExtractionError::Zip(ZipError::InvalidArchive(_)))
+            ));
+            let params = ExtractionParameters {
+                symlink_policy: SymlinkPolicy::Rewrite,
+                ..Default::default()
+            };
+            split_extract(&zip, td.path(), params).unwrap();
+            assert_eq!(
+                fs::read_link(td.path().join("a/up")).unwrap(),
+                Path::new("../x")
+            );
+
+            /* Overlong targets are rejected without being read in full. */
+            let long_target = "x".repeat(MAX_SYMLINK_TARGET_LEN as usize + 1);
+            let zip = archive(&[("long", &long_target)]);
+            let td = TempDir::new("pipeline-test").unwrap();
+            assert!(matches!(
+                split_extract(&zip, td.path(), ExtractionParameters::default()),
+                Err(SplitExtractionError::Zip(ZipError::InvalidArchive(_)))
+            ));
+
+            /* Nothing is extracted through a link. */
+            let mut zip = ZipWriter::new(tempfile::tempfile().unwrap());
+            zip.add_symlink("l", "a", SimpleFileOptions::default())
+                .unwrap();
+            zip.start_file("l/f", SimpleFileOptions::default()).unwrap();
+            let zip = ZipArchive::new(zip.finish().unwrap()).unwrap();
+            let td = TempDir::new("pipeline-test").unwrap();
+            assert!(matches!(
+                split_extract(&zip, td.path(), ExtractionParameters::default()),
+                Err(SplitExtractionError::PathSplit(_))
+            ));
+            assert!(!td.path().join("a").exists());
+        }
+
+        #[test]
+        fn select_and_report() {
+            let mut zip = ZipWriter::new(tempfile::tempfile().unwrap());
+            let opts = SimpleFileOptions::default();
+            zip.add_directory("a/", opts).unwrap();
+            zip.start_file("a/x", opts).unwrap();
+            zip.write_all(&[1; 1000]).unwrap();
+            zip.start_file("a/y", opts.compression_method(CompressionMethod::Stored))
+                .unwrap();
+            zip.write_all(&[2; 100]).unwrap();
+            zip.start_file("b/z", opts).unwrap();
+            zip.write_all(&[3; 10]).unwrap();
+            let zip = ZipArchive::new(zip.finish().unwrap()).unwrap();
+
+            let td = TempDir::new("pipeline-test").unwrap();
+            let params = ExtractionParameters {
+                select: Some(EntrySelector::subtree("a/")),
+                ..Default::default()
+            };
+            let report = split_extract(&zip, td.path(), params).unwrap();
+            assert!(report.is_complete());
+            assert_eq!(
+                report
+                    .entries
+                    .iter()
+                    .map(|entry| (entry.index, entry.name.as_str(), entry.bytes_written))
+                    .collect::<Vec<_>>(),
+                vec![(0, "a/", 0), (1, "a/x", 1000), (2, "a/y", 100)]
+            );
+            assert_eq!(report.entries[0].method, Some(ExtractionMethod::Directory));
+            assert!(matches!(
+                report.entries[2].method,
+                Some(ExtractionMethod::CopyFileRange | ExtractionMethod::BufferCopy)
+            ));
+            assert_eq!(fs::read(td.path().join("a/y")).unwrap(), [2; 100]);
+            assert!(!td.path().join("b").exists());
+        }
+
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        #[test]
+        fn io_uring() {
+            let mut zip = ZipWriter::new(tempfile::tempfile().unwrap());
+            let opts = SimpleFileOptions::default();
+            for i in 0..20u8 {
+                zip.start_file(format!("{}/x", i), opts).unwrap();
+                zip.write_all(&[i; 10000]).unwrap();
+                zip.start_file(
+                    format!("{}/y", i),
+                    opts.compression_method(CompressionMethod::Stored),
+                )
+                .unwrap();
+                zip.write_all(&[i; 1000]).unwrap();
+            }
+            let zip = zip.finish_into_readable().unwrap();
+
+            let td = TempDir::new("pipeline-test").unwrap();
+            let params = ExtractionParameters {
+                io_uring: true,
+                decompression_copy_buffer_length: 100,
+                ..Default::default()
+            };
+            let report = split_extract(&zip, td.path(), params).unwrap();
+            assert!(report.is_complete());
+            for i in 0..20u8 {
+                let dir = td.path().join(i.to_string());
+                assert_eq!(fs::read(dir.join("x")).unwrap(), [i; 10000]);
+                assert_eq!(fs::read(dir.join("y")).unwrap(), [i; 1000]);
+            }
+        }
+
+        #[test]
+        fn in_memory() {
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            let opts = SimpleFileOptions::default();
+            zip.start_file("a/x", opts).unwrap();
+            zip.write_all(&[1; 1000]).unwrap();
+            zip.start_file("a/y", opts.compression_method(CompressionMethod::Stored))
+                .unwrap();
+            zip.write_all(&[2; 100]).unwrap();
+            zip.add_symlink("b", "a/y", opts).unwrap();
+            let bytes = zip.finish().unwrap().into_inner();
+
+            let td = TempDir::new("pipeline-test").unwrap();
+            let zip = ZipArchive::new(Cursor::new(&bytes[..])).unwrap();
+            let report = split_extract(&zip, td.path(), ExtractionParameters::default()).unwrap();
+            assert_eq!(
+                report
+                    .entries
+                    .iter()
+                    .map(|entry| entry.method.unwrap())
+                    .collect::<Vec<_>>(),
+                vec![
+                    ExtractionMethod::Decompression,
+                    ExtractionMethod::MemoryCopy,
+                    ExtractionMethod::Symlink
+                ]
+            );
+            assert_eq!(fs::read(td.path().join("a/x")).unwrap(), [1; 1000]);
+            assert_eq!(fs::read(td.path().join("b")).unwrap(), [2; 100]);
+
+            /* An owned buffer works the same way. */
+            let td = TempDir::new("pipeline-test").unwrap();
+            let zip = ZipArchive::new(Cursor::new(bytes)).unwrap();
+            split_extract(&zip, td.path(), ExtractionParameters::default()).unwrap();
+            assert_eq!(fs::read(td.path().join("a/y")).unwrap(), [2; 100]);
+        }
+
+        #[test]
+        fn positional_source() {
+            /* A reader of the caller's own, which split_extract() knows nothing about. */
+            struct Reader(Cursor<Vec<u8>>);
+
+            impl Read for Reader {
+                fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                    self.0.read(buf)
+                }
+            }
+
+            impl Seek for Reader {
+                fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+                    self.0.seek(pos)
+                }
+            }
+
+            impl ReadAt for Reader {
+                fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+                    /* Return short reads, so they have to be retried. */
+                    let len = buf.len().min(7);
+                    self.0.read_at(&mut buf[..len], offset)
+                }
+            }
+
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            let opts = SimpleFileOptions::default();
+            zip.start_file("a/x", opts).unwrap();
+            zip.write_all(&[1; 1000]).unwrap();
+            zip.start_file("a/y", opts.compression_method(CompressionMethod::Stored))
+                .unwrap();
+            zip.write_all(&[2; 100]).unwrap();
+            let reader = Reader(zip.finish().unwrap());
+
+            let td = TempDir::new("pipeline-test").unwrap();
+            let zip = ZipArchive::new(PositionalSource::new(reader).unwrap()).unwrap();
+            let params = ExtractionParameters {
+                file_range_copy_buffer_length: 30,
+                ..Default::default()
+            };
+            let report = split_extract(&zip, td.path(), params).unwrap();
+            assert_eq!(
+                report
+                    .entries
+                    .iter()
+                    .map(|entry| entry.method.unwrap())
+                    .collect::<Vec<_>>(),
+                vec![
+                    ExtractionMethod::Decompression,
+                    ExtractionMethod::BufferCopy
+                ]
+            );
+            assert_eq!(fs::read(td.path().join("a/x")).unwrap(), [1; 1000]);
+            assert_eq!(fs::read(td.path().join("a/y")).unwrap(), [2; 100]);
+        }
+
+        #[test]
+        fn continue_on_error() {
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            let opts = SimpleFileOptions::default();
+            zip.start_file("encrypted", opts.with_deprecated_encryption(b"password"))
+                .unwrap();
+            zip.write_all(b"encrypted").unwrap();
+            zip.start_file("corrupt", opts).unwrap();
+            zip.write_all(&b"corrupt".repeat(1000)).unwrap();
+            zip.start_file("fine", opts).unwrap();
+            zip.write_all(b"fine").unwrap();
+            let mut zip = zip.finish_into_readable().unwrap();
+
+            /* Flip a byte in the middle of the corrupt entry's compressed data. */
+            let corrupt = zip.by_name("corrupt").unwrap();
+            let middle = corrupt.data_start() + corrupt.compressed_size() / 2;
+            drop(corrupt);
+            let mut bytes = zip.into_inner().into_inner();
+            bytes[middle as usize] ^= 0xff;
+            let mut file = tempfile::tempfile().unwrap();
+            file.write_all(&bytes).unwrap();
+            let zip = ZipArchive::new(file).unwrap();
+
+            for extract in [split_extract::<fs::File>, pool_extract::<fs::File>] {
+                let td = TempDir::new("pipeline-test").unwrap();
+                assert!(extract(&zip, td.path(), ExtractionParameters::default()).is_err());
+
+                let td = TempDir::new("pipeline-test").unwrap();
+                let params = ExtractionParameters {
+                    continue_on_error: true,
+                    ..Default::default()
+                };
+                let report = extract(&zip, td.path(), params).unwrap();
+                assert_eq!(
+                    report
+                        .failures()
+                        .map(|entry| entry.name.as_str())
+                        .collect::<Vec<_>>(),
+                    ["encrypted", "corrupt"]
+                );
+                assert!(!td.path().join("encrypted").exists());
+                assert!(!td.path().join("corrupt").exists());
+                assert_eq!(fs::read_to_string(td.path().join("fine")).unwrap(), "fine");
+            }
+        }
     }
 }
@@ -0,0 +1,273 @@
+//! Reading entries of an archive from several threads at once.
+
+use super::{
+    data_start_from_local_block, open_entry, password_for, EntrySource, ZipArchive, ZipFile,
+};
+use crate::result::{ZipError, ZipResult};
+use crate::spec::FixedSizeBlock;
+use crate::types::{ZipFileData, ZipLocalEntryBlock};
+use std::fs::File;
+use std::io::{self, Cursor, Read};
+
+/// Readers that can read from any offset without moving a cursor, such as files and byte slices,
+/// so that reads through a shared reference don't interfere with one another.
+///
+/// A [`ZipArchive`] over one of these can read entries through a shared reference, with
+/// [`ZipArchive::by_index_shared`] and [`ZipArchive::by_name_shared`].
+pub trait ReadAt {
+    /// Reads bytes starting at `offset` into `buf`, and returns how many were read, with 0
+    /// meaning the end has been reached.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+}
+
+impl ReadAt for [u8] {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let start = usize::try_from(offset).map_or(self.len(), |offset| offset.min(self.len()));
+        let remaining = &self[start..];
+        let len = buf.len().min(remaining.len());
+        buf[..len].copy_from_slice(&remaining[..len]);
+        Ok(len)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+}
+
+/// Reads from the underlying bytes, regardless of the cursor's position.
+impl<T: AsRef<[u8]>> ReadAt for Cursor<T> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.get_ref().as_ref().read_at(buf, offset)
+    }
+}
+
+#[cfg(unix)]
+impl ReadAt for File {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+}
+
+/// Moves the file's cursor, which [`ZipArchive`] doesn't rely on, since it always seeks before
+/// reading.
+#[cfg(windows)]
+impl ReadAt for File {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+}
+
+/// Reads sequentially from a [`ReadAt`], keeping its own position.
+struct PositionalReader<'a> {
+    source: &'a dyn ReadAt,
+    position: u64,
+}
+
+impl<'a> Read for PositionalReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.source.read_at(buf, self.position)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+/// Like [`find_content`](super::find_content), but reads the local header by offset.
+pub(crate) fn find_content_at<'a>(
+    data: &ZipFileData,
+    source: &'a dyn ReadAt,
+) -> ZipResult<io::Take<EntrySource<'a>>> {
+    let data_start = match data.data_start.get() {
+        Some(data_start) => *data_start,
+        None => {
+            let mut header = PositionalReader {
+                source,
+                position: data.header_start,
+            };
+            let block = ZipLocalEntryBlock::parse(&mut header)?;
+            data_start_from_local_block(data, &block)
+        }
+    };
+    let reader: Box<dyn Read> = Box::new(PositionalReader {
+        source,
+        position: data_start,
+    });
+    Ok(EntrySource::Owned(reader).take(data.compressed_size))
+}
+
+impl<R: ReadAt> ZipArchive<R> {
+    /// Get a contained file by index, through a shared reference. Each thread can read a
+    /// different entry at the same time, since the data is read with [`ReadAt`].
+    ///
+    /// ```
+    /// # fn main() -> zip::result::ZipResult<()> {
+    /// use std::io::{Cursor, Read, Write};
+    /// use std::thread;
+    ///
+    /// let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    /// for i in 0..4 {
+    ///     zip.start_file(format!("{i}.txt"), zip::write::SimpleFileOptions::default())?;
+    ///     write!(zip, "entry {i}")?;
+    /// }
+    /// let archive = zip.finish_into_readable()?;
+    ///
+    /// let contents: Vec<String> = thread::scope(|scope| {
+    ///     let handles: Vec<_> = (0..archive.len())
+    ///         .map(|i| {
+    ///             let archive = &archive;
+    ///             scope.spawn(move || -> zip::result::ZipResult<String> {
+    ///                 let mut contents = String::new();
+    ///                 archive.by_index_shared(i)?.read_to_string(&mut contents)?;
+    ///                 Ok(contents)
+    ///             })
+    ///         })
+    ///         .collect();
+    ///     handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Result<_, _>>()
+    /// })?;
+    /// assert_eq!(contents, ["entry 0", "entry 1", "entry 2", "entry 3"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn by_index_shared(&self, file_number: usize) -> ZipResult<ZipFile<'_>> {
+        self.by_index_shared_with_optional_password(file_number, None)
+    }
+
+    /// Get a contained file by index through a shared reference, decrypt with given password.
+    /// See [`ZipArchive::by_index_decrypt`] for the weaknesses of the encryption.
+    pub fn by_index_shared_decrypt(
+        &self,
+        file_number: usize,
+        password: &[u8],
+    ) -> ZipResult<ZipFile<'_>> {
+        self.by_index_shared_with_optional_password(file_number, Some(password))
+    }
+
+    /// Search for a file entry by name, through a shared reference. See
+    /// [`ZipArchive::by_index_shared`].
+    pub fn by_name_shared(&self, name: &str) -> ZipResult<ZipFile<'_>> {
+        self.by_index_shared_with_optional_password(self.shared_index_for_name(name)?, None)
+    }
+
+    /// Search for a file entry by name through a shared reference, decrypt with given password.
+    /// See [`ZipArchive::by_index_decrypt`] for the weaknesses of the encryption.
+    pub fn by_name_shared_decrypt(&self, name: &str, password: &[u8]) -> ZipResult<ZipFile<'_>> {
+        self.by_index_shared_with_optional_password(
+            self.shared_index_for_name(name)?,
+            Some(password),
+        )
+    }
+
+    fn shared_index_for_name(&self, name: &str) -> ZipResult<usize> {
+        self.shared
+            .files
+            .get_index_of(name)
+            .ok_or(ZipError::FileNotFound)
+    }
+
+    fn by_index_shared_with_optional_password(
+        &self,
+        file_number: usize,
+        password: Option<&[u8]>,
+    ) -> ZipResult<ZipFile<'_>> {
+        let (_, data) = self
+            .shared
+            .files
+            .get_index(file_number)
+            .ok_or(ZipError::FileNotFound)?;
+        let password = password_for(data, password)?;
+        let limit_reader = find_content_at(data, &self.reader)?;
+        open_entry(data, limit_reader, password, &self.shared.limits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ReadAt;
+    use crate::result::{ZipError, ZipResult};
+    use crate::write::SimpleFileOptions;
+    use crate::{ZipArchive, ZipWriter};
+    use std::io::{Cursor, Read, Seek, Write};
+    use std::thread;
+
+    fn contents(i: usize) -> Vec<u8> {
+        format!("entry {i} ").repeat(1000 + i).into_bytes()
+    }
+
+    fn write_archive<W: Write + Seek>(writer: W) -> ZipResult<W> {
+        let mut writer = ZipWriter::new(writer);
+        for i in 0..16 {
+            writer.start_file(format!("{i}.txt"), SimpleFileOptions::default())?;
+            writer.write_all(&contents(i))?;
+        }
+        writer.start_file(
+            "encrypted.txt",
+            SimpleFileOptions::default().with_deprecated_encryption(b"password"),
+        )?;
+        writer.write_all(b"encrypted contents")?;
+        writer.finish()
+    }
+
+    /// Reads every entry on its own thread, while the main thread reads the encrypted one.
+    fn read_concurrently<R: ReadAt + Sync>(archive: &ZipArchive<R>) -> ZipResult<()> {
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..16)
+                .map(|i| {
+                    scope.spawn(move || -> ZipResult<()> {
+                        let mut read = Vec::new();
+                        archive
+                            .by_name_shared(&format!("{i}.txt"))?
+                            .read_to_end(&mut read)?;
+                        assert!(read == contents(i));
+                        Ok(())
+                    })
+                })
+                .collect();
+            assert!(archive.by_name_shared("encrypted.txt").is_err());
+            let mut read = String::new();
+            archive
+                .by_name_shared_decrypt("encrypted.txt", b"password")?
+                .read_to_string(&mut read)?;
+            assert_eq!(read, "encrypted contents");
+            for handle in handles {
+                handle.join().unwrap()?;
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn shared_reads_from_memory() -> ZipResult<()> {
+        let bytes = write_archive(Cursor::new(Vec::new()))?.into_inner();
+        let archive = ZipArchive::new(Cursor::new(&bytes[..]))?;
+        read_concurrently(&archive)?;
+        assert!(matches!(
+            archive.by_index_shared(17),
+            Err(ZipError::FileNotFound)
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn shared_reads_check_limits() -> ZipResult<()> {
+        use crate::read::{Config, Limits};
+
+        let bytes = include_bytes!("../../tests/data/xz.zip");
+        let limits = Limits::default().with_max_total_size(11);
+        let archive = ZipArchive::with_limits(Config::default(), limits, Cursor::new(&bytes[..]))?;
+        assert!(matches!(
+            archive.by_index_shared(0),
+            Err(ZipError::LimitExceeded(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn shared_reads_from_file() -> ZipResult<()> {
+        let mut file = write_archive(tempfile::tempfile()?)?;
+        file.rewind()?;
+        read_concurrently(&ZipArchive::new(file)?)
+    }
+}
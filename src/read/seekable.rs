@@ -0,0 +1,673 @@
+//! Seeking inside an entry.
+//!
+//! Stored data maps straight onto the archive. Compressed data is decoded forward from the
+//! nearest checkpoint before the position sought: a copy of the decoder's state, which is recorded
+//! while decoding at most once per [`ZipFileSeek::set_checkpoint_interval`] bytes. A Deflate
+//! decoder can be copied anywhere, whereas Zstandard streams can only be resumed where a frame
+//! starts, and the other methods only from the start of the data.
+
+use crate::compression::CompressionMethod;
+use crate::crc32::invalid_checksum;
+use crate::result::{ZipError, ZipResult};
+use crate::types::ZipFileData;
+use crc32fast::Hasher;
+use std::cell::RefCell;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+/// How much decompressed data there is between checkpoints by default.
+const DEFAULT_CHECKPOINT_INTERVAL: u64 = 4 << 20;
+
+/// The reader of an archive, which a [`ZipFileSeek`] doesn't need to know the type of.
+trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// The archive's reader, shared by the decoders of an entry and its checkpoints.
+struct Source<'a> {
+    reader: &'a mut (dyn ReadSeek + 'a),
+    /// Where the reader is, if that's known, to avoid seeking when reading on from there.
+    position: Option<u64>,
+}
+
+/// Reads the archive from `offset` up to `end`, seeking only when something else read elsewhere
+/// in between.
+#[derive(Clone)]
+struct SourceReader<'a> {
+    source: Rc<RefCell<Source<'a>>>,
+    offset: u64,
+    end: u64,
+}
+
+impl<'a> Read for SourceReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min((self.end - self.offset) as usize);
+        if len == 0 {
+            return Ok(0);
+        }
+        let mut source = self.source.borrow_mut();
+        if source.position != Some(self.offset) {
+            source.position = None;
+            source.reader.seek(SeekFrom::Start(self.offset))?;
+        }
+        let read = source.reader.read(&mut buf[..len])?;
+        self.offset += read as u64;
+        source.position = Some(self.offset);
+        Ok(read)
+    }
+}
+
+/// Decompresses an entry, and can be copied at some points to resume from there.
+trait EntryDecoder<'a>: Read {
+    /// A decoder that resumes from where this one is, if one can be made at this point.
+    fn snapshot(&self) -> Option<Box<dyn EntryDecoder<'a> + 'a>>;
+
+    /// Moves to `position` in the decompressed data, if the data can be addressed directly.
+    fn seek_directly(&mut self, _position: u64) -> bool {
+        false
+    }
+}
+
+/// Stored data, which is read where it is.
+struct StoredDecoder<'a> {
+    reader: SourceReader<'a>,
+    data_start: u64,
+}
+
+impl<'a> Read for StoredDecoder<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl<'a> EntryDecoder<'a> for StoredDecoder<'a> {
+    fn snapshot(&self) -> Option<Box<dyn EntryDecoder<'a> + 'a>> {
+        None
+    }
+
+    fn seek_directly(&mut self, position: u64) -> bool {
+        self.reader.offset = self
+            .data_start
+            .saturating_add(position)
+            .min(self.reader.end);
+        true
+    }
+}
+
+#[cfg(any(
+    feature = "deflate64",
+    feature = "bzip2",
+    feature = "lzma",
+    feature = "xz"
+))]
+/// A decoder that can only start from the beginning of the data.
+struct RestartingDecoder<'a>(Box<dyn Read + 'a>);
+
+#[cfg(any(
+    feature = "deflate64",
+    feature = "bzip2",
+    feature = "lzma",
+    feature = "xz"
+))]
+impl<'a> Read for RestartingDecoder<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+#[cfg(any(
+    feature = "deflate64",
+    feature = "bzip2",
+    feature = "lzma",
+    feature = "xz"
+))]
+impl<'a> EntryDecoder<'a> for RestartingDecoder<'a> {
+    fn snapshot(&self) -> Option<Box<dyn EntryDecoder<'a> + 'a>> {
+        None
+    }
+}
+
+#[cfg(feature = "_deflate-any")]
+mod inflate {
+    use super::{EntryDecoder, SourceReader};
+    use miniz_oxide::inflate::core::inflate_flags::TINFL_FLAG_HAS_MORE_INPUT;
+    use miniz_oxide::inflate::core::{decompress, DecompressorOxide, TINFL_LZ_DICT_SIZE};
+    use miniz_oxide::inflate::TINFLStatus;
+    use std::io::{self, Read};
+    use std::ops::Range;
+
+    const INPUT_LEN: usize = 16 * 1024;
+
+    /// Deflate decoder whose whole state, including the window that back-references point into,
+    /// can be copied.
+    #[derive(Clone)]
+    pub(super) struct Inflater<'a> {
+        reader: SourceReader<'a>,
+        state: Box<DecompressorOxide>,
+        window: Box<[u8]>,
+        window_pos: usize,
+        /// Decompressed data in the window that hasn't been read yet.
+        available: Range<usize>,
+        input: Box<[u8]>,
+        unread_input: Range<usize>,
+        done: bool,
+    }
+
+    impl<'a> Inflater<'a> {
+        pub(super) fn new(reader: SourceReader<'a>) -> Self {
+            Inflater {
+                reader,
+                state: Box::default(),
+                window: vec![0; TINFL_LZ_DICT_SIZE].into_boxed_slice(),
+                window_pos: 0,
+                available: 0..0,
+                input: vec![0; INPUT_LEN].into_boxed_slice(),
+                unread_input: 0..0,
+                done: false,
+            }
+        }
+
+        /// Decompresses until there's more output, or the stream ends.
+        fn fill(&mut self) -> io::Result<()> {
+            while self.available.is_empty() && !self.done {
+                if self.unread_input.is_empty() {
+                    let read = self.reader.read(&mut self.input)?;
+                    self.unread_input = 0..read;
+                }
+                let more_input = self.reader.offset < self.reader.end;
+                let flags = if more_input {
+                    TINFL_FLAG_HAS_MORE_INPUT
+                } else {
+                    0
+                };
+                let (status, consumed, written) = decompress(
+                    &mut self.state,
+                    &self.input[self.unread_input.clone()],
+                    &mut self.window,
+                    self.window_pos,
+                    flags,
+                );
+                self.unread_input.start += consumed;
+                self.available = self.window_pos..self.window_pos + written;
+                self.window_pos = (self.window_pos + written) & (TINFL_LZ_DICT_SIZE - 1);
+                match status {
+                    TINFLStatus::Done => self.done = true,
+                    TINFLStatus::HasMoreOutput => {}
+                    TINFLStatus::NeedsMoreInput => {
+                        if !more_input && self.unread_input.is_empty() && written == 0 {
+                            return Err(io::Error::new(
+                                io::ErrorKind::UnexpectedEof,
+                                "Deflate data ended unexpectedly",
+                            ));
+                        }
+                    }
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "Invalid Deflate data",
+                        ))
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<'a> Read for Inflater<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            self.fill()?;
+            let len = buf.len().min(self.available.len());
+            buf[..len].copy_from_slice(&self.window[self.available.start..][..len]);
+            self.available.start += len;
+            Ok(len)
+        }
+    }
+
+    impl<'a> EntryDecoder<'a> for Inflater<'a> {
+        fn snapshot(&self) -> Option<Box<dyn EntryDecoder<'a> + 'a>> {
+            Some(Box::new(self.clone()))
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+mod zstd_frames {
+    use super::{EntryDecoder, SourceReader};
+    use std::io::{self, Read};
+    use std::ops::Range;
+    use zstd::stream::raw::{Decoder, InBuffer, Operation, OutBuffer};
+
+    const INPUT_LEN: usize = 16 * 1024;
+
+    /// Zstandard decoder that can be resumed where a frame starts, since frames are independent.
+    pub(super) struct FrameDecoder<'a> {
+        reader: SourceReader<'a>,
+        decoder: Decoder<'static>,
+        input: Box<[u8]>,
+        unread_input: Range<usize>,
+        at_frame_start: bool,
+    }
+
+    impl<'a> FrameDecoder<'a> {
+        pub(super) fn new(reader: SourceReader<'a>) -> io::Result<Self> {
+            Ok(FrameDecoder {
+                reader,
+                decoder: Decoder::new()?,
+                input: vec![0; INPUT_LEN].into_boxed_slice(),
+                unread_input: 0..0,
+                at_frame_start: true,
+            })
+        }
+    }
+
+    impl<'a> Read for FrameDecoder<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            loop {
+                if self.unread_input.is_empty() {
+                    let read = self.reader.read(&mut self.input)?;
+                    self.unread_input = 0..read;
+                }
+                let input_ended = self.unread_input.is_empty();
+                if input_ended && self.at_frame_start {
+                    return Ok(0);
+                }
+                let mut input = InBuffer::around(&self.input[self.unread_input.clone()]);
+                let mut output = OutBuffer::around(&mut *buf);
+                // This is 0 once a frame has been decoded and all of it written out
+                let hint = self.decoder.run(&mut input, &mut output)?;
+                self.unread_input.start += input.pos();
+                self.at_frame_start = hint == 0;
+                if output.pos() > 0 {
+                    return Ok(output.pos());
+                }
+                if input_ended {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "Zstandard data ended unexpectedly",
+                    ));
+                }
+            }
+        }
+    }
+
+    impl<'a> EntryDecoder<'a> for FrameDecoder<'a> {
+        fn snapshot(&self) -> Option<Box<dyn EntryDecoder<'a> + 'a>> {
+            if !self.at_frame_start {
+                return None;
+            }
+            let mut reader = self.reader.clone();
+            reader.offset -= self.unread_input.len() as u64;
+            Some(Box::new(FrameDecoder::new(reader).ok()?))
+        }
+    }
+}
+
+/// An entry's contents that can be read from any position, as returned by
+/// [`ZipArchive::by_index_seek`](crate::ZipArchive::by_index_seek) and
+/// [`ZipArchive::by_name_seek`](crate::ZipArchive::by_name_seek).
+///
+/// Seeking backwards in compressed data decodes it again from the nearest checkpoint before the
+/// new position, which it records while decoding. Seeking past the end is allowed, and reads
+/// nothing. The checksum is verified when the contents are read through from the start.
+///
+/// Zstandard data only has checkpoints where a frame starts. [`ZipWriter`](crate::ZipWriter)
+/// compresses each entry as a single frame, so seeking backwards in Zstandard entries it wrote
+/// always decodes them again from the start. Entries written by tools that split their output
+/// into several frames can be sought in like Deflate entries.
+pub struct ZipFileSeek<'a> {
+    data: &'a ZipFileData,
+    source: Rc<RefCell<Source<'a>>>,
+    data_start: u64,
+    decoder: Box<dyn EntryDecoder<'a> + 'a>,
+    /// How much the decoder has decompressed.
+    position: u64,
+    /// Where the next read starts.
+    target: u64,
+    /// Checksum of the contents decoded so far, as long as they were decoded from the start.
+    hasher: Option<Hasher>,
+    checkpoints: Vec<(u64, Box<dyn EntryDecoder<'a> + 'a>)>,
+    checkpoint_interval: Option<u64>,
+}
+
+impl<'a> ZipFileSeek<'a> {
+    pub(crate) fn new<R: Read + Seek + 'a>(
+        data: &'a ZipFileData,
+        reader: &'a mut R,
+        data_start: u64,
+    ) -> ZipResult<Self> {
+        if data.encrypted {
+            return Err(ZipError::UnsupportedArchive(
+                "Seeking inside encrypted entries is not supported",
+            ));
+        }
+        let source = Rc::new(RefCell::new(Source {
+            reader,
+            position: None,
+        }));
+        let decoder = open_decoder(data, &source, data_start)?;
+        Ok(ZipFileSeek {
+            data,
+            source,
+            data_start,
+            decoder,
+            position: 0,
+            target: 0,
+            hasher: Some(Hasher::new()),
+            checkpoints: Vec::new(),
+            checkpoint_interval: Some(DEFAULT_CHECKPOINT_INTERVAL),
+        })
+    }
+
+    /// Sets how much decompressed data there must at least be between checkpoints, or stops
+    /// recording them if `interval` is `None`. The default is 4 MiB. A Deflate checkpoint takes
+    /// about 60 KiB, and a Zstandard one a new decoder; stored data needs none.
+    pub fn set_checkpoint_interval(&mut self, interval: Option<u64>) {
+        self.checkpoint_interval = interval;
+    }
+
+    /// Decodes the rest of the contents to record their checkpoints, so that later seeks within
+    /// them don't have to decode anything before the nearest one.
+    pub fn build_checkpoints(&mut self) -> io::Result<()> {
+        let target = self.target;
+        self.move_to(u64::MAX)?;
+        self.target = target;
+        Ok(())
+    }
+
+    /// Decodes into `buf`, and records a checkpoint if one is due.
+    fn decode(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.data.uncompressed_size.saturating_sub(self.position);
+        let len = buf.len().min(remaining.try_into().unwrap_or(usize::MAX));
+        let read = self.decoder.read(&mut buf[..len])?;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..read]);
+        }
+        self.position += read as u64;
+        if self.position == self.data.uncompressed_size {
+            if let Some(hasher) = self.hasher.take() {
+                if hasher.finalize() != self.data.crc32 {
+                    return Err(invalid_checksum());
+                }
+            }
+        }
+        if let Some(interval) = self.checkpoint_interval {
+            let last = self.checkpoints.last().map_or(0, |(position, _)| *position);
+            if self.position >= last.saturating_add(interval) {
+                if let Some(snapshot) = self.decoder.snapshot() {
+                    self.checkpoints.push((self.position, snapshot));
+                }
+            }
+        }
+        Ok(read)
+    }
+
+    /// Gets the decoder to `target`, or to the end if that's before it.
+    fn move_to(&mut self, target: u64) -> io::Result<()> {
+        if self.decoder.seek_directly(target) {
+            self.position = target.min(self.data.uncompressed_size);
+            self.hasher = None;
+            return Ok(());
+        }
+        let checkpoint = self
+            .checkpoints
+            .partition_point(|(position, _)| *position <= target);
+        let resume_from = checkpoint
+            .checked_sub(1)
+            .map(|index| &self.checkpoints[index]);
+        match resume_from {
+            Some((position, snapshot)) if *position > self.position || target < self.position => {
+                self.decoder = snapshot.snapshot().unwrap();
+                self.position = *position;
+                self.hasher = None;
+            }
+            None if target < self.position => {
+                self.decoder = open_decoder(self.data, &self.source, self.data_start)?;
+                self.position = 0;
+                self.hasher = Some(Hasher::new());
+            }
+            _ => {}
+        }
+        let mut skipped = [0u8; 8192];
+        while self.position < target {
+            let len = skipped.len().min((target - self.position) as usize);
+            if self.decode(&mut skipped[..len])? == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Opens a decoder at the start of the data.
+fn open_decoder<'a>(
+    data: &ZipFileData,
+    source: &Rc<RefCell<Source<'a>>>,
+    data_start: u64,
+) -> ZipResult<Box<dyn EntryDecoder<'a> + 'a>> {
+    let reader = SourceReader {
+        source: source.clone(),
+        offset: data_start,
+        end: data_start + data.compressed_size,
+    };
+    #[allow(deprecated)]
+    let decoder: Box<dyn EntryDecoder<'a> + 'a> = match data.compression_method {
+        CompressionMethod::Stored => Box::new(StoredDecoder { reader, data_start }),
+        #[cfg(feature = "_deflate-any")]
+        CompressionMethod::Deflated => Box::new(inflate::Inflater::new(reader)),
+        #[cfg(feature = "zstd")]
+        CompressionMethod::Zstd => Box::new(zstd_frames::FrameDecoder::new(reader)?),
+        #[cfg(feature = "deflate64")]
+        CompressionMethod::Deflate64 => Box::new(RestartingDecoder(Box::new(
+            deflate64::Deflate64Decoder::new(reader),
+        ))),
+        #[cfg(feature = "bzip2")]
+        CompressionMethod::Bzip2 => Box::new(RestartingDecoder(Box::new(
+            bzip2::read::BzDecoder::new(reader),
+        ))),
+        #[cfg(feature = "lzma")]
+        CompressionMethod::Lzma => Box::new(RestartingDecoder(Box::new(
+            super::lzma::LzmaDecoder::new(reader),
+        ))),
+        #[cfg(feature = "xz")]
+        CompressionMethod::Xz => Box::new(RestartingDecoder(Box::new(super::xz::XzDecoder::new(
+            io::BufReader::new(reader),
+            data.uncompressed_size,
+        )))),
+        _ => {
+            return Err(ZipError::UnsupportedArchive(
+                "Compression method not supported",
+            ))
+        }
+    };
+    Ok(decoder)
+}
+
+impl<'a> Read for ZipFileSeek<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.target != self.position {
+            self.move_to(self.target)?;
+            if self.position != self.target {
+                // The data ended before the target
+                return Ok(0);
+            }
+        }
+        let read = self.decode(buf)?;
+        self.target = self.position;
+        Ok(read)
+    }
+}
+
+impl<'a> Seek for ZipFileSeek<'a> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.data.uncompressed_size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.target.checked_add_signed(offset),
+        };
+        self.target = target.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.target)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::result::ZipResult;
+    use crate::write::SimpleFileOptions;
+    use crate::{CompressionMethod, ZipArchive, ZipWriter};
+    use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+    fn contents() -> Vec<u8> {
+        (0..300_000u32)
+            .flat_map(|i| (i.wrapping_mul(2_654_435_761) >> 28).to_le_bytes())
+            .collect()
+    }
+
+    fn check_seeks(method: CompressionMethod) -> ZipResult<()> {
+        check_seeks_with(method, |_| ())
+    }
+
+    fn check_seeks_with(
+        method: CompressionMethod,
+        check_checkpoints: impl FnOnce(usize),
+    ) -> ZipResult<()> {
+        let data = contents();
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(method);
+        writer.start_file("data", options)?;
+        writer.write_all(&data)?;
+        let mut archive = ZipArchive::new(writer.finish()?)?;
+        let mut file = archive.by_name_seek("data")?;
+        file.set_checkpoint_interval(Some(100_000));
+        let mut read = Vec::new();
+        file.read_to_end(&mut read)?;
+        assert!(read == data);
+        check_checkpoints(file.checkpoints.len());
+        for (start, len) in [(1_000_000, 10), (5, 70_000), (600_123, 345_678), (0, 1)] {
+            assert_eq!(file.seek(SeekFrom::Start(start))?, start);
+            let mut buf = vec![0; len];
+            file.read_exact(&mut buf)?;
+            assert!(buf[..] == data[start as usize..][..len]);
+        }
+        assert_eq!(file.seek(SeekFrom::End(-4))?, data.len() as u64 - 4);
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        assert_eq!(buf, data[data.len() - 4..]);
+        file.seek(SeekFrom::Current(10))?;
+        assert_eq!(file.read(&mut buf)?, 0);
+        assert!(file.seek(SeekFrom::Current(-100_000_000)).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "_deflate-any")]
+    #[test]
+    fn build_checkpoints() -> ZipResult<()> {
+        let data = contents();
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("data", SimpleFileOptions::default())?;
+        writer.write_all(&data)?;
+        let mut archive = ZipArchive::new(writer.finish()?)?;
+        let mut file = archive.by_name_seek("data")?;
+        file.set_checkpoint_interval(Some(400_000));
+        file.build_checkpoints()?;
+        assert_eq!(file.checkpoints.len(), 2);
+        let mut start = [0; 4];
+        file.read_exact(&mut start)?;
+        assert_eq!(start, data[..4]);
+        Ok(())
+    }
+
+    #[test]
+    fn stored() -> ZipResult<()> {
+        check_seeks(CompressionMethod::Stored)
+    }
+
+    #[cfg(feature = "_deflate-any")]
+    #[test]
+    fn deflated() -> ZipResult<()> {
+        check_seeks_with(CompressionMethod::Deflated, |checkpoints| {
+            assert!((8..=11).contains(&checkpoints))
+        })
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd() -> ZipResult<()> {
+        // A single frame, which can only be resumed from where it ends
+        check_seeks_with(CompressionMethod::Zstd, |checkpoints| {
+            assert_eq!(checkpoints, 1)
+        })
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_frames() -> ZipResult<()> {
+        let data = contents();
+        let mut compressed = Vec::new();
+        for chunk in data.chunks(200_000) {
+            compressed.extend(zstd::stream::encode_all(chunk, 0)?);
+        }
+        // Store the frames, then relabel the entry as Zstandard with the real size and checksum.
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.start_file("data", options)?;
+        writer.write_all(&compressed)?;
+        let mut bytes = writer.finish()?.into_inner();
+        let central = bytes.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+        for (method, crc32, uncompressed_size) in
+            [(8, 14, 22), (central + 10, central + 16, central + 24)]
+        {
+            bytes[method..method + 2].copy_from_slice(&93u16.to_le_bytes());
+            bytes[crc32..crc32 + 4].copy_from_slice(&crc32fast::hash(&data).to_le_bytes());
+            bytes[uncompressed_size..uncompressed_size + 4]
+                .copy_from_slice(&(data.len() as u32).to_le_bytes());
+        }
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        let mut file = archive.by_name_seek("data")?;
+        file.set_checkpoint_interval(Some(100_000));
+        file.build_checkpoints()?;
+        // One after each frame
+        assert_eq!(file.checkpoints.len(), 6);
+        file.seek(SeekFrom::Start(700_000))?;
+        let mut buf = vec![0; 300_000];
+        file.read_exact(&mut buf)?;
+        assert!(buf[..] == data[700_000..1_000_000]);
+        Ok(())
+    }
+
+    #[cfg(feature = "bzip2")]
+    #[test]
+    fn bzip2() -> ZipResult<()> {
+        check_seeks(CompressionMethod::Bzip2)
+    }
+
+    #[test]
+    fn corrupt_checksum() -> ZipResult<()> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.start_file("data", options)?;
+        writer.write_all(b"some data")?;
+        let mut bytes = writer.finish()?.into_inner();
+        let position = bytes.windows(9).position(|w| w == b"some data").unwrap();
+        bytes[position] = b'S';
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        let mut file = archive.by_name_seek("data")?;
+        assert!(file.read_to_end(&mut Vec::new()).is_err());
+        file.seek(SeekFrom::Start(1))?;
+        let mut rest = Vec::new();
+        file.read_to_end(&mut rest)?;
+        assert_eq!(rest, b"ome data");
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+//! Archives that are already in memory.
+
+use super::{data_start_from_local_block, Config, Limits, ZipArchive};
+use crate::compression::CompressionMethod;
+use crate::crc32::{invalid_checksum, Crc32Reader};
+use crate::result::{ZipError, ZipResult};
+use crate::spec::FixedSizeBlock;
+use crate::types::{ZipFileData, ZipLocalEntryBlock};
+use std::io::{self, Cursor, Read};
+
+/// ZIP archive held in memory, as a byte slice that may come from `include_bytes!` or a memory
+/// map, whose stored entries are borrowed from it rather than copied.
+///
+/// Everything else about the archive can be looked up through [`ZipSliceArchive::archive`], and
+/// encrypted entries read through [`ZipSliceArchive::archive_mut`].
+///
+/// ```
+/// # fn main() -> zip::result::ZipResult<()> {
+/// use std::io::{Cursor, Read, Write};
+/// use zip::read::ZipSliceArchive;
+/// use zip::write::SimpleFileOptions;
+///
+/// let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+/// let options = SimpleFileOptions::default();
+/// zip.start_file("stored.txt", options.compression_method(zip::CompressionMethod::Stored))?;
+/// zip.write_all(b"Hello")?;
+/// zip.start_file("deflated.txt", options)?;
+/// zip.write_all(b"World")?;
+/// let bytes = zip.finish()?.into_inner();
+///
+/// let archive = ZipSliceArchive::new(&bytes)?;
+/// assert_eq!(archive.by_name("stored.txt")?, b"Hello");
+/// let mut contents = String::new();
+/// archive.reader_by_name("deflated.txt")?.read_to_string(&mut contents)?;
+/// assert_eq!(contents, "World");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ZipSliceArchive<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+    verify_checksums: bool,
+}
+
+/// Contents of an entry in a [`ZipSliceArchive`], decompressed from the slice as they're read.
+/// The checksum is verified once they have all been read.
+pub struct ZipSliceReader<'a> {
+    inner: Crc32Reader<Box<dyn Read + 'a>>,
+}
+
+impl<'a> Read for ZipSliceReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<'a> ZipSliceArchive<'a> {
+    /// Reads the central directory of the archive in `bytes`.
+    pub fn new(bytes: &'a [u8]) -> ZipResult<Self> {
+        Self::with_config(Default::default(), bytes)
+    }
+
+    /// Reads the central directory of the archive in `bytes`, with the given configuration.
+    pub fn with_config(config: Config, bytes: &'a [u8]) -> ZipResult<Self> {
+        Self::with_limits(config, Limits::default(), bytes)
+    }
+
+    /// Reads the central directory of the archive in `bytes`, with the given configuration and
+    /// [`Limits`], as [`ZipArchive::with_limits`] does.
+    pub fn with_limits(config: Config, limits: Limits, bytes: &'a [u8]) -> ZipResult<Self> {
+        Ok(ZipSliceArchive {
+            archive: ZipArchive::with_limits(config, limits, Cursor::new(bytes))?,
+            verify_checksums: false,
+        })
+    }
+
+    /// Sets whether [`ZipSliceArchive::by_name`] and [`ZipSliceArchive::by_index`] check the
+    /// contents they return against their checksum, which means reading all of them. This is off
+    /// by default.
+    pub fn set_verify_checksums(&mut self, verify_checksums: bool) {
+        self.verify_checksums = verify_checksums;
+    }
+
+    /// The archive, for its metadata and anything else it offers.
+    pub const fn archive(&self) -> &ZipArchive<Cursor<&'a [u8]>> {
+        &self.archive
+    }
+
+    /// The archive, to read entries through it, such as encrypted ones.
+    pub fn archive_mut(&mut self) -> &mut ZipArchive<Cursor<&'a [u8]>> {
+        &mut self.archive
+    }
+
+    /// Unwraps the archive.
+    pub fn into_archive(self) -> ZipArchive<Cursor<&'a [u8]>> {
+        self.archive
+    }
+
+    /// Number of files contained in this zip.
+    pub fn len(&self) -> usize {
+        self.archive.len()
+    }
+
+    /// Whether this zip archive contains no files
+    pub fn is_empty(&self) -> bool {
+        self.archive.is_empty()
+    }
+
+    /// Borrows the contents of a stored, unencrypted entry by name.
+    pub fn by_name(&self, name: &str) -> ZipResult<&'a [u8]> {
+        let index = self
+            .archive
+            .index_for_name(name)
+            .ok_or(ZipError::FileNotFound)?;
+        self.by_index(index)
+    }
+
+    /// Borrows the contents of a stored, unencrypted entry by index.
+    pub fn by_index(&self, index: usize) -> ZipResult<&'a [u8]> {
+        let data = self.file_data(index)?;
+        if data.compression_method != CompressionMethod::Stored || data.encrypted {
+            return Err(ZipError::UnsupportedArchive(
+                "Only stored, unencrypted entries can be borrowed",
+            ));
+        }
+        let contents = self.compressed_contents(data)?;
+        if self.verify_checksums && crc32fast::hash(contents) != data.crc32 {
+            return Err(invalid_checksum().into());
+        }
+        Ok(contents)
+    }
+
+    /// Reads an unencrypted entry by name, decompressing it straight from the slice.
+    pub fn reader_by_name(&self, name: &str) -> ZipResult<ZipSliceReader<'a>> {
+        let index = self
+            .archive
+            .index_for_name(name)
+            .ok_or(ZipError::FileNotFound)?;
+        self.reader_by_index(index)
+    }
+
+    /// Reads an unencrypted entry by index, decompressing it straight from the slice.
+    pub fn reader_by_index(&self, index: usize) -> ZipResult<ZipSliceReader<'a>> {
+        let data = self.file_data(index)?;
+        if data.encrypted {
+            return Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED));
+        }
+        self.archive
+            .shared
+            .limits
+            .check_in_memory_entry(data, false)?;
+        let contents = self.compressed_contents(data)?;
+        #[allow(deprecated)]
+        let decoder: Box<dyn Read + 'a> = match data.compression_method {
+            CompressionMethod::Stored => Box::new(contents),
+            #[cfg(feature = "deflate-flate2")]
+            CompressionMethod::Deflated => Box::new(flate2::bufread::DeflateDecoder::new(contents)),
+            #[cfg(feature = "deflate64")]
+            CompressionMethod::Deflate64 => {
+                Box::new(deflate64::Deflate64Decoder::with_buffer(contents))
+            }
+            #[cfg(feature = "bzip2")]
+            CompressionMethod::Bzip2 => Box::new(bzip2::bufread::BzDecoder::new(contents)),
+            #[cfg(feature = "zstd")]
+            CompressionMethod::Zstd => {
+                Box::new(zstd::stream::read::Decoder::with_buffer(contents)?)
+            }
+            #[cfg(feature = "lzma")]
+            CompressionMethod::Lzma => Box::new(super::lzma::LzmaDecoder::new(contents)),
+            #[cfg(feature = "xz")]
+            CompressionMethod::Xz => {
+                Box::new(super::xz::XzDecoder::new(contents, data.uncompressed_size))
+            }
+            _ => {
+                return Err(ZipError::UnsupportedArchive(
+                    "Compression method not supported",
+                ))
+            }
+        };
+        Ok(ZipSliceReader {
+            inner: Crc32Reader::new(decoder, data.crc32, false),
+        })
+    }
+
+    fn file_data(&self, index: usize) -> ZipResult<&ZipFileData> {
+        self.archive
+            .shared
+            .files
+            .get_index(index)
+            .map(|(_, data)| data)
+            .ok_or(ZipError::FileNotFound)
+    }
+
+    /// The compressed contents of an entry, after its local header.
+    fn compressed_contents(&self, data: &ZipFileData) -> ZipResult<&'a [u8]> {
+        let bytes: &'a [u8] = self.archive.reader.get_ref();
+        let data_start = match data.data_start.get() {
+            Some(data_start) => *data_start,
+            None => {
+                let mut header = usize::try_from(data.header_start)
+                    .ok()
+                    .and_then(|start| bytes.get(start..))
+                    .ok_or(ZipError::InvalidArchive("Local header is out of bounds"))?;
+                let block = ZipLocalEntryBlock::parse(&mut header)?;
+                data_start_from_local_block(data, &block)
+            }
+        };
+        data_start
+            .checked_add(data.compressed_size)
+            .and_then(|end| {
+                bytes.get(usize::try_from(data_start).ok()?..usize::try_from(end).ok()?)
+            })
+            .ok_or(ZipError::InvalidArchive("File data is out of bounds"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ZipSliceArchive;
+    use crate::result::{ZipError, ZipResult};
+    use crate::write::SimpleFileOptions;
+    use crate::{CompressionMethod, ZipWriter};
+    use std::io::{Cursor, Read, Write};
+
+    fn archive() -> ZipResult<Vec<u8>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.start_file("stored.txt", stored)?;
+        writer.write_all(b"stored contents")?;
+        writer.start_file(
+            "encrypted.txt",
+            stored.with_deprecated_encryption(b"password"),
+        )?;
+        writer.write_all(b"encrypted contents")?;
+        writer.start_file("deflated.txt", SimpleFileOptions::default())?;
+        writer.write_all(&b"deflated contents ".repeat(100))?;
+        Ok(writer.finish()?.into_inner())
+    }
+
+    #[test]
+    fn borrow_stored() -> ZipResult<()> {
+        let bytes = archive()?;
+        let archive = ZipSliceArchive::new(&bytes)?;
+        assert_eq!(archive.len(), 3);
+        let contents = archive.by_name("stored.txt")?;
+        assert_eq!(contents, b"stored contents");
+        assert!(bytes.as_ptr_range().contains(&contents.as_ptr()));
+        #[cfg(feature = "_deflate-any")]
+        assert!(matches!(
+            archive.by_name("deflated.txt"),
+            Err(ZipError::UnsupportedArchive(_))
+        ));
+        assert!(matches!(
+            archive.by_name("encrypted.txt"),
+            Err(ZipError::UnsupportedArchive(_))
+        ));
+        assert!(matches!(
+            archive.by_name("missing.txt"),
+            Err(ZipError::FileNotFound)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn verify_checksums() -> ZipResult<()> {
+        let mut bytes = archive()?;
+        let position = bytes
+            .windows(15)
+            .position(|window| window == b"stored contents")
+            .unwrap();
+        bytes[position] = b'S';
+        let mut archive = ZipSliceArchive::new(&bytes)?;
+        assert_eq!(archive.by_name("stored.txt")?, b"Stored contents");
+        archive.set_verify_checksums(true);
+        assert!(archive.by_name("stored.txt").is_err());
+        assert!(archive
+            .reader_by_name("stored.txt")?
+            .read_to_end(&mut Vec::new())
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_compressed() -> ZipResult<()> {
+        let bytes = archive()?;
+        let mut archive = ZipSliceArchive::new(&bytes)?;
+        let mut contents = Vec::new();
+        archive
+            .reader_by_name("deflated.txt")?
+            .read_to_end(&mut contents)?;
+        assert_eq!(contents, b"deflated contents ".repeat(100));
+        assert!(archive.reader_by_name("encrypted.txt").is_err());
+        let mut contents = String::new();
+        archive
+            .archive_mut()
+            .by_name_decrypt("encrypted.txt", b"password")?
+            .read_to_string(&mut contents)?;
+        assert_eq!(contents, "encrypted contents");
+        Ok(())
+    }
+}
@@ -96,6 +96,89 @@ pub mod file {
         }
     }
 
+    /// An [`InputFile`] over bytes which are already in memory, such as a downloaded archive or a
+    /// memory map.
+    #[derive(Debug, Copy, Clone)]
+    pub struct MemoryInput<'a> {
+        bytes: &'a [u8],
+    }
+
+    impl<'a> MemoryInput<'a> {
+        pub const fn new(bytes: &'a [u8]) -> Self {
+            Self { bytes }
+        }
+
+        /// Borrow `len` bytes at `start` directly, without copying them anywhere.
+        pub fn slice(&self, start: u64, len: usize) -> io::Result<&'a [u8]> {
+            let count = self.range_len(start, len)?;
+            let start: usize = start.try_into().unwrap();
+            Ok(&self.bytes[start..(start + count)])
+        }
+    }
+
+    impl<'a> FixedFile for MemoryInput<'a> {
+        fn extent(&self) -> u64 {
+            self.bytes.len().try_into().unwrap()
+        }
+    }
+
+    impl<'a> InputFile for MemoryInput<'a> {
+        fn pread(&self, start: u64, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+            let src = self.slice(start, buf.len())?;
+            let dst: *mut u8 = buf.as_mut_ptr().cast();
+            unsafe { dst.copy_from_nonoverlapping(src.as_ptr(), src.len()) };
+            Ok(src.len())
+        }
+    }
+
+    /// An [`InputFile`] over any [`ReadAt`](crate::read::ReadAt) reader of a known length, which
+    /// can only be read by copying into a buffer.
+    #[derive(Copy, Clone)]
+    pub struct ReadAtInput<'a> {
+        source: &'a (dyn crate::read::ReadAt + Sync),
+        len: u64,
+    }
+
+    impl<'a> ReadAtInput<'a> {
+        pub fn new(source: &'a (dyn crate::read::ReadAt + Sync), len: u64) -> Self {
+            Self { source, len }
+        }
+
+        /// Fill `buf` with the bytes at `start`, failing if the reader ends first.
+        pub fn read_exact_at(&self, mut start: u64, mut buf: &mut [u8]) -> io::Result<()> {
+            while !buf.is_empty() {
+                let num_read: usize =
+                    interruptible_buffered_io_op![self.source.read_at(buf, start)];
+                if num_read == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "pread less than expected range",
+                    ));
+                }
+                buf = &mut buf[num_read..];
+                start += u64::try_from(num_read).unwrap();
+            }
+            Ok(())
+        }
+    }
+
+    impl<'a> FixedFile for ReadAtInput<'a> {
+        fn extent(&self) -> u64 {
+            self.len
+        }
+    }
+
+    impl<'a> InputFile for ReadAtInput<'a> {
+        fn pread(&self, start: u64, buf: &mut [MaybeUninit<u8>]) -> io::Result<usize> {
+            let count = self.range_len(start, buf.len())?;
+            let buf = &mut buf[..count];
+            buf.fill(MaybeUninit::new(0));
+            /* The bytes were all initialized just above. */
+            let buf: &mut [u8] = unsafe { &mut *(buf as *mut [MaybeUninit<u8>] as *mut [u8]) };
+            self.source.read_at(buf, start)
+        }
+    }
+
     pub trait OutputFile: FixedFile {
         fn pwrite(&mut self, start: u64, buf: &[u8]) -> io::Result<usize>;
 
@@ -172,6 +255,33 @@ pub mod file {
         }
     }
 
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        use std::mem;
+
+        #[allow(clippy::missing_transmute_annotations)]
+        #[test]
+        fn memory_pread() {
+            let ii = MemoryInput::new(b"asdf");
+            assert_eq!(4, ii.extent());
+
+            let buf: MaybeUninit<[u8; 10]> = MaybeUninit::zeroed();
+            let mut buf: [MaybeUninit<u8>; 10] = unsafe { mem::transmute(buf) };
+            assert_eq!(2, ii.pread(0, &mut buf[..2]).unwrap());
+            assert_eq!(3, ii.pread(1, &mut buf[4..]).unwrap());
+            assert_eq!(
+                unsafe { mem::transmute::<_, &[u8]>(&buf[..]) },
+                &[b'a', b's', 0, 0, b's', b'd', b'f', 0, 0, 0]
+            );
+            assert_eq!(0, ii.pread(4, &mut buf[..]).unwrap());
+            assert!(ii.pread(5, &mut buf[..]).is_err());
+
+            assert_eq!(b"df", ii.slice(2, 10).unwrap());
+        }
+    }
+
     #[cfg(unix)]
     pub mod unix {
         use super::{CopyRange, FixedFile, InputFile, OutputFile};
@@ -261,6 +371,14 @@ pub mod file {
                 })
             }
 
+            /// Wraps `file` without setting its length, for callers which set it later.
+            pub fn with_extent(file: fs::File, extent: u64) -> Self {
+                Self {
+                    handle: file.into(),
+                    extent,
+                }
+            }
+
             pub(crate) fn fd(&self) -> RawFd {
                 self.handle.as_raw_fd()
             }
@@ -1069,6 +1187,624 @@ pub mod pipe {
     }
 }
 
+/// Finishing output files through [io_uring](https://man7.org/linux/man-pages/man7/io_uring.7.html).
+///
+/// An [`OutputRing`] keeps many output files in flight on a single ring, driven by a single
+/// thread: it copies in the data of any file that hasn't been written yet, then sets the length of
+/// each file and syncs it. Reads and writes that depend on each other are linked, so a buffered
+/// copy costs one submission instead of a `pread()` followed by a `pwrite()`, and none of these
+/// steps holds up a thread of its own.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub mod uring {
+    use super::file::unix::FileInput;
+
+    use std::collections::HashSet;
+    use std::fs;
+    use std::io;
+    use std::mem;
+    use std::os::fd::AsRawFd;
+    use std::sync::mpsc;
+
+    use io_uring::{opcode, squeue, types, IoUring, Probe};
+    use libc;
+
+    /// Marks the cancellations submitted by [`Ring::drain()`], whose completions are ignored.
+    const CANCEL: u64 = u64::MAX;
+
+    /* The low byte of each operation's user_data says which step of a job it belongs to, and the
+     * rest is the index of the job's slot. */
+    const OP_READ: u64 = 0;
+    const OP_WRITE: u64 = 1;
+    const OP_TRUNCATE: u64 = 2;
+    const OP_SYNC: u64 = 3;
+
+    fn completed(res: i32) -> io::Result<usize> {
+        if res < 0 {
+            return Err(io::Error::from_raw_os_error(-res));
+        }
+        Ok(res.try_into().unwrap())
+    }
+
+    /// An io_uring instance which keeps track of the operations it has in flight, so that they can
+    /// all be waited out before anything they refer to is freed.
+    struct Ring {
+        ring: IoUring,
+        in_flight: HashSet<u64>,
+        /// Whether the kernel can set file lengths through the ring, which needs Linux 6.9.
+        ftruncate: bool,
+    }
+
+    impl Ring {
+        fn new(entries: u32) -> io::Result<Self> {
+            let ring = IoUring::new(entries)?;
+            let mut probe = Probe::new();
+            let ftruncate = ring.submitter().register_probe(&mut probe).is_ok()
+                && probe.is_supported(opcode::Ftruncate::CODE);
+            Ok(Self {
+                ring,
+                in_flight: HashSet::new(),
+                ftruncate,
+            })
+        }
+
+        /// Queue `entries` to be submitted together, so that links between them are kept.
+        ///
+        /// # Safety
+        /// Any buffer or file descriptor the entries refer to must stay valid until they complete.
+        unsafe fn push(&mut self, entries: &[squeue::Entry]) -> io::Result<()> {
+            let free = {
+                let sq = self.ring.submission();
+                sq.capacity() - sq.len()
+            };
+            if free < entries.len() {
+                loop {
+                    interruptible_buffered_io_op![self.ring.submit()];
+                    break;
+                }
+            }
+            self.ring.submission().push_multiple(entries).map_err(|_| {
+                io::Error::new(io::ErrorKind::Other, "io_uring submission queue full")
+            })?;
+            for entry in entries {
+                let user_data = entry.get_user_data();
+                if user_data != CANCEL {
+                    self.in_flight.insert(user_data);
+                }
+            }
+            Ok(())
+        }
+
+        /// Submit everything queued, wait for at least one operation to complete, and collect the
+        /// user_data and result of each that has.
+        fn wait(&mut self, completed: &mut Vec<(u64, i32)>) -> io::Result<()> {
+            loop {
+                interruptible_buffered_io_op![self.ring.submit_and_wait(1)];
+                break;
+            }
+            for entry in self.ring.completion() {
+                let user_data = entry.user_data();
+                if user_data == CANCEL {
+                    continue;
+                }
+                self.in_flight.remove(&user_data);
+                completed.push((user_data, entry.result()));
+            }
+            Ok(())
+        }
+
+        /// Cancel every operation in flight, and wait until the kernel is done with all of them.
+        fn drain(&mut self) -> io::Result<()> {
+            let in_flight: Vec<u64> = self.in_flight.iter().copied().collect();
+            for user_data in in_flight {
+                let cancel = opcode::AsyncCancel::new(user_data)
+                    .build()
+                    .user_data(CANCEL);
+                /* Cancellations refer to nothing but the user_data of another operation. */
+                unsafe { self.push(&[cancel])? };
+            }
+            let mut completed = Vec::new();
+            while !self.in_flight.is_empty() {
+                self.wait(&mut completed)?;
+                completed.clear();
+            }
+            Ok(())
+        }
+    }
+
+    /// Where the data of an output file comes from.
+    #[derive(Debug, Copy, Clone)]
+    pub enum Source<'a> {
+        /// The data has already been written to the file.
+        Written,
+        /// The data is read from a file, starting at the given offset.
+        File(FileInput<'a>, u64),
+        /// The data is written straight from memory.
+        Memory(&'a [u8]),
+    }
+
+    /// An output file for an [`OutputRing`] to finish.
+    pub struct Job<'a, T> {
+        pub source: Source<'a>,
+        pub output: fs::File,
+        /// The length the file should have once it's finished.
+        pub len: u64,
+        /// Whatever the caller needs to know about the file once it's finished.
+        pub tag: T,
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    enum Step {
+        Start,
+        /// A read into the buffer, linked to a write of the same range.
+        Copy,
+        /// A write of the rest of the buffer, or of the next range of memory.
+        Write,
+        Truncate,
+        Sync,
+    }
+
+    struct Slot<'a, T> {
+        job: Job<'a, T>,
+        buf: Box<[u8]>,
+        /// How much of the data is in the file so far.
+        written: u64,
+        /// The range of the buffer which has been read but not written yet.
+        buffered: (usize, usize),
+        step: Step,
+        /// How many operations of the current step are still in flight.
+        pending: u8,
+        /// The results of the current step's operations: a read and whichever operation follows it.
+        results: [i32; 2],
+    }
+
+    /// What happened after a job's slot was advanced.
+    enum Progress {
+        Submitted,
+        Done(io::Result<u64>),
+    }
+
+    /// Finishes output files on a single ring, keeping up to a fixed number of them in flight.
+    ///
+    /// Each file's data is copied in through a buffer of its own, if it hasn't been written
+    /// already. Its length is then set, through the ring on Linux 6.9 and later, and it's synced
+    /// with `fsync()`. If anything goes wrong with the ring itself, or the caller gives up, every
+    /// operation still in flight is cancelled and waited out before the ring is dropped.
+    pub struct OutputRing<'a, T> {
+        ring: Ring,
+        slots: Vec<Option<Slot<'a, T>>>,
+        buf_len: usize,
+    }
+
+    impl<'a, T> OutputRing<'a, T> {
+        /// Set up a ring for up to `files` output files at once, each copied through a buffer of
+        /// at most `buf_len` bytes. This fails on kernels older than 5.6, or where io_uring has
+        /// been disabled.
+        pub fn new(files: usize, buf_len: usize) -> io::Result<Self> {
+            assert!(files > 0);
+            assert!(buf_len > 0);
+            /* Each file has at most a read and a write in flight at once. */
+            let ring = Ring::new((files * 2).try_into().unwrap())?;
+            Ok(Self {
+                ring,
+                slots: (0..files).map(|_| None).collect(),
+                buf_len,
+            })
+        }
+
+        /// Finish every job received from `jobs` until it's disconnected.
+        ///
+        /// `before_sync` is called on each file once its data and length are in place, just
+        /// before it's synced, and `finish` is called with the outcome of each job once its file
+        /// has been closed. Errors from either are passed on to `finish` for the job they were
+        /// for, so only an error from `finish` itself, or from the ring, stops the rest.
+        pub fn run<E: From<io::Error>>(
+            &mut self,
+            jobs: mpsc::Receiver<Job<'a, T>>,
+            mut before_sync: impl FnMut(&T, &fs::File) -> io::Result<()>,
+            mut finish: impl FnMut(T, io::Result<u64>) -> Result<(), E>,
+        ) -> Result<(), E> {
+            let result = self.run_jobs(jobs, &mut before_sync, &mut finish);
+            if result.is_err() {
+                self.ring.drain()?;
+            }
+            result
+        }
+
+        fn run_jobs<E: From<io::Error>>(
+            &mut self,
+            jobs: mpsc::Receiver<Job<'a, T>>,
+            before_sync: &mut impl FnMut(&T, &fs::File) -> io::Result<()>,
+            finish: &mut impl FnMut(T, io::Result<u64>) -> Result<(), E>,
+        ) -> Result<(), E> {
+            let mut open = true;
+            let mut completed: Vec<(u64, i32)> = Vec::new();
+            loop {
+                /* Take on new jobs while there are free slots, but only block waiting for one if
+                 * nothing else is in flight. */
+                while open {
+                    let Some(index) = self.slots.iter().position(Option::is_none) else {
+                        break;
+                    };
+                    let job = if self.ring.in_flight.is_empty() {
+                        match jobs.recv() {
+                            Ok(job) => job,
+                            Err(mpsc::RecvError) => {
+                                open = false;
+                                break;
+                            }
+                        }
+                    } else {
+                        match jobs.try_recv() {
+                            Ok(job) => job,
+                            Err(mpsc::TryRecvError::Empty) => break,
+                            Err(mpsc::TryRecvError::Disconnected) => {
+                                open = false;
+                                break;
+                            }
+                        }
+                    };
+                    let progress = self.start(index, job, before_sync)?;
+                    self.settle(index, progress, finish)?;
+                }
+                if self.ring.in_flight.is_empty() {
+                    if open {
+                        continue;
+                    }
+                    return Ok(());
+                }
+
+                self.ring.wait(&mut completed)?;
+                for (user_data, res) in completed.drain(..) {
+                    let index: usize = (user_data >> 8).try_into().unwrap();
+                    let progress = self.complete(index, user_data & 0xff, res, before_sync)?;
+                    self.settle(index, progress, finish)?;
+                }
+            }
+        }
+
+        fn start(
+            &mut self,
+            index: usize,
+            job: Job<'a, T>,
+            before_sync: &mut impl FnMut(&T, &fs::File) -> io::Result<()>,
+        ) -> io::Result<Progress> {
+            let (written, buf) = match job.source {
+                Source::Written => (job.len, Box::default()),
+                Source::File(..) => {
+                    let buf_len: usize = job
+                        .len
+                        .min(self.buf_len.try_into().unwrap())
+                        .try_into()
+                        .unwrap();
+                    (0, vec![0u8; buf_len].into_boxed_slice())
+                }
+                Source::Memory(_) => (0, Box::default()),
+            };
+            self.slots[index] = Some(Slot {
+                job,
+                buf,
+                written,
+                buffered: (0, 0),
+                step: Step::Start,
+                pending: 0,
+                results: [0; 2],
+            });
+            self.advance(index, before_sync)
+        }
+
+        /// Record the result of one of a slot's operations, and advance the slot once its step is
+        /// complete.
+        fn complete(
+            &mut self,
+            index: usize,
+            op: u64,
+            res: i32,
+            before_sync: &mut impl FnMut(&T, &fs::File) -> io::Result<()>,
+        ) -> io::Result<Progress> {
+            let slot = self.slots[index].as_mut().unwrap();
+            slot.results[usize::from(op != OP_READ)] = res;
+            slot.pending -= 1;
+            if slot.pending > 0 {
+                return Ok(Progress::Submitted);
+            }
+
+            let result = match slot.step {
+                Step::Copy => completed(slot.results[0]).and_then(|num_read| {
+                    if num_read == 0 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "read less than expected file range",
+                        ));
+                    }
+                    /* A short read cancels the linked write, and a write may itself be short, so
+                     * whatever is left over gets written on its own. */
+                    let num_written = match slot.results[1] {
+                        res if res == -libc::ECANCELED => 0,
+                        res => completed(res)?,
+                    };
+                    slot.written += u64::try_from(num_written).unwrap();
+                    slot.buffered = (num_written, num_read);
+                    Ok(())
+                }),
+                Step::Write => completed(slot.results[1]).and_then(|num_written| {
+                    if num_written == 0 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WriteZero,
+                            "wrote less than expected file range",
+                        ));
+                    }
+                    slot.written += u64::try_from(num_written).unwrap();
+                    slot.buffered.0 += num_written;
+                    Ok(())
+                }),
+                Step::Truncate | Step::Sync => completed(slot.results[1]).map(|_| ()),
+                Step::Start => unreachable!("nothing is submitted before a job starts"),
+            };
+            match result {
+                Ok(()) => self.advance(index, before_sync),
+                Err(e) => Ok(Progress::Done(Err(e))),
+            }
+        }
+
+        /// Submit the next step of a slot's job, if it has any left.
+        fn advance(
+            &mut self,
+            index: usize,
+            before_sync: &mut impl FnMut(&T, &fs::File) -> io::Result<()>,
+        ) -> io::Result<Progress> {
+            let user_data = |op: u64| (u64::try_from(index).unwrap() << 8) | op;
+            let slot = self.slots[index].as_mut().unwrap();
+            let Job {
+                source,
+                ref output,
+                len,
+                ref tag,
+            } = slot.job;
+            let output_fd = types::Fd(output.as_raw_fd());
+
+            if slot.written < len {
+                let remaining = len - slot.written;
+                let write = |data: &[u8]| {
+                    let count: u32 = data.len().min(u32::MAX as usize).try_into().unwrap();
+                    opcode::Write::new(output_fd, data.as_ptr(), count)
+                        .offset(slot.written)
+                        .build()
+                        .user_data(user_data(OP_WRITE))
+                };
+                let entries = match source {
+                    Source::File(input, start) if slot.buffered.0 == slot.buffered.1 => {
+                        let count = remaining.min(slot.buf.len() as u64).min(u32::MAX as u64);
+                        let count: u32 = count.try_into().unwrap();
+                        let read =
+                            opcode::Read::new(types::Fd(input.fd()), slot.buf.as_mut_ptr(), count)
+                                .offset(start + slot.written)
+                                .build()
+                                .flags(squeue::Flags::IO_LINK)
+                                .user_data(user_data(OP_READ));
+                        let write = opcode::Write::new(output_fd, slot.buf.as_ptr(), count)
+                            .offset(slot.written)
+                            .build()
+                            .user_data(user_data(OP_WRITE));
+                        slot.step = Step::Copy;
+                        vec![read, write]
+                    }
+                    Source::File(..) => {
+                        let (from, to) = slot.buffered;
+                        slot.step = Step::Write;
+                        vec![write(&slot.buf[from..to])]
+                    }
+                    Source::Memory(data) => {
+                        let remaining: usize = remaining.try_into().unwrap();
+                        let from: usize = slot.written.try_into().unwrap();
+                        slot.step = Step::Write;
+                        vec![write(&data[from..(from + remaining)])]
+                    }
+                    Source::Written => unreachable!("written jobs start with all their data"),
+                };
+                slot.pending = entries.len().try_into().unwrap();
+                /* The buffer and both files stay in the slot until these complete, even if the
+                 * job fails or is abandoned in the meantime. */
+                unsafe { self.ring.push(&entries)? };
+                return Ok(Progress::Submitted);
+            }
+
+            let mut step = slot.step;
+            if matches!(step, Step::Start | Step::Copy | Step::Write) {
+                if self.ring.ftruncate {
+                    let truncate = opcode::Ftruncate::new(output_fd, len)
+                        .build()
+                        .user_data(user_data(OP_TRUNCATE));
+                    slot.step = Step::Truncate;
+                    slot.pending = 1;
+                    unsafe { self.ring.push(&[truncate])? };
+                    return Ok(Progress::Submitted);
+                }
+                if let Err(e) = output.set_len(len) {
+                    return Ok(Progress::Done(Err(e)));
+                }
+                step = Step::Truncate;
+            }
+            if step == Step::Sync {
+                return Ok(Progress::Done(Ok(len)));
+            }
+
+            if let Err(e) = before_sync(tag, output) {
+                return Ok(Progress::Done(Err(e)));
+            }
+            let sync = opcode::Fsync::new(output_fd)
+                .build()
+                .user_data(user_data(OP_SYNC));
+            slot.step = Step::Sync;
+            slot.pending = 1;
+            unsafe { self.ring.push(&[sync])? };
+            Ok(Progress::Submitted)
+        }
+
+        /// Free the slot of a job that's done, and pass on its outcome.
+        fn settle<E>(
+            &mut self,
+            index: usize,
+            progress: Progress,
+            finish: &mut impl FnMut(T, io::Result<u64>) -> Result<(), E>,
+        ) -> Result<(), E> {
+            let Progress::Done(result) = progress else {
+                return Ok(());
+            };
+            let Slot {
+                job: Job { output, tag, .. },
+                ..
+            } = self.slots[index].take().unwrap();
+            mem::drop(output);
+            finish(tag, result)
+        }
+    }
+
+    impl<'a, T> Drop for OutputRing<'a, T> {
+        fn drop(&mut self) {
+            /* If the kernel might still be using the buffers and files, leak them instead. */
+            if self.ring.drain().is_err() {
+                mem::forget(mem::take(&mut self.slots));
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        use std::io::prelude::*;
+
+        use tempfile;
+
+        fn readable_file(input: &[u8]) -> io::Result<fs::File> {
+            let mut i = tempfile::tempfile()?;
+            i.write_all(input)?;
+            Ok(i)
+        }
+
+        fn read_back(mut file: fs::File) -> Vec<u8> {
+            file.rewind().unwrap();
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf).unwrap();
+            buf
+        }
+
+        #[test]
+        fn finish_outputs() {
+            let i = readable_file(b"asdfasdf").unwrap();
+            let ii = FileInput::new(&i).unwrap();
+
+            let outputs: Vec<fs::File> = (0..4).map(|_| tempfile::tempfile().unwrap()).collect();
+            let mut written = outputs[2].try_clone().unwrap();
+            written.write_all(b"xy").unwrap();
+            let jobs = [
+                (Source::File(ii, 1), 5),
+                (Source::Memory(b"qwer"), 4),
+                (Source::Written, 2),
+                (Source::File(ii, 8), 0),
+            ];
+
+            let (send, recv) = mpsc::channel();
+            for (tag, ((source, len), output)) in jobs.into_iter().zip(&outputs).enumerate() {
+                let output = output.try_clone().unwrap();
+                send.send(Job {
+                    source,
+                    output,
+                    len,
+                    tag,
+                })
+                .unwrap();
+            }
+            mem::drop(send);
+
+            /* Two files at once, through a buffer of 2 bytes, so copies take several steps. */
+            let mut ring = OutputRing::new(2, 2).unwrap();
+            let mut synced = Vec::new();
+            let mut finished = Vec::new();
+            ring.run::<io::Error>(
+                recv,
+                |&tag, file| {
+                    synced.push((tag, file.metadata()?.len()));
+                    Ok(())
+                },
+                |tag, result| {
+                    finished.push((tag, result.unwrap()));
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+            synced.sort();
+            assert_eq!(synced, [(0, 5), (1, 4), (2, 2), (3, 0)]);
+            finished.sort();
+            assert_eq!(finished, [(0, 5), (1, 4), (2, 2), (3, 0)]);
+            let contents: Vec<Vec<u8>> = outputs.into_iter().map(read_back).collect();
+            assert_eq!(
+                contents,
+                [b"sdfas".to_vec(), b"qwer".to_vec(), b"xy".to_vec(), vec![]]
+            );
+        }
+
+        #[test]
+        fn read_past_end() {
+            let i = readable_file(b"asdf").unwrap();
+            let ii = FileInput::new(&i).unwrap();
+
+            let (send, recv) = mpsc::channel();
+            send.send(Job {
+                source: Source::File(ii, 2),
+                output: tempfile::tempfile().unwrap(),
+                len: 5,
+                tag: (),
+            })
+            .unwrap();
+            mem::drop(send);
+
+            let mut ring = OutputRing::new(1, 4).unwrap();
+            let mut errors = Vec::new();
+            ring.run::<io::Error>(
+                recv,
+                |_, _| panic!("a failed file isn't synced"),
+                |(), result| {
+                    errors.push(result.unwrap_err().kind());
+                    Ok(())
+                },
+            )
+            .unwrap();
+            assert_eq!(errors, [io::ErrorKind::UnexpectedEof]);
+        }
+
+        #[test]
+        fn give_up() {
+            let i = readable_file(&[1; 1000]).unwrap();
+            let ii = FileInput::new(&i).unwrap();
+
+            let (send, recv) = mpsc::channel();
+            for tag in 0..8 {
+                send.send(Job {
+                    source: Source::File(ii, 0),
+                    output: tempfile::tempfile().unwrap(),
+                    len: 1000,
+                    tag,
+                })
+                .unwrap();
+            }
+            mem::drop(send);
+
+            /* Failing the first job to finish leaves the others in flight, which must be waited
+             * out before their buffers are freed. */
+            let mut ring = OutputRing::new(4, 10).unwrap();
+            let result = ring.run(
+                recv,
+                |_, _| Ok(()),
+                |_, _| Err(io::Error::new(io::ErrorKind::Other, "giving up")),
+            );
+            assert_eq!(result.unwrap_err().to_string(), "giving up");
+            assert!(ring.ring.in_flight.is_empty());
+        }
+    }
+}
+
 pub mod util {
     use std::io::{self, Read, Write};
 
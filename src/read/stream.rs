@@ -2,49 +2,81 @@ use std::fs;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 
+use super::extract::{entry_times, set_file_handle_times, set_file_times, FileTime};
+use super::limits::{Budget, UNLIMITED};
 use super::{
-    central_header_to_zip_file_inner, read_zipfile_from_stream, ZipCentralEntryBlock, ZipError,
-    ZipFile, ZipFileData, ZipResult,
+    central_header_to_zip_file_inner, read_zipfile_from_buffered_stream, Limits,
+    ZipCentralEntryBlock, ZipError, ZipFile, ZipFileData, ZipResult,
 };
 use crate::spec::FixedSizeBlock;
 
 /// Stream decoder for zip.
 #[derive(Debug)]
-pub struct ZipStreamReader<R>(R);
+pub struct ZipStreamReader<R> {
+    reader: R,
+    limits: Limits,
+    restore_times: bool,
+}
 
 impl<R> ZipStreamReader<R> {
     /// Create a new ZipStreamReader
     pub const fn new(reader: R) -> Self {
-        Self(reader)
+        Self::with_limits(reader, UNLIMITED)
+    }
+
+    /// Create a new ZipStreamReader whose [`extract`](Self::extract) enforces the given limits.
+    ///
+    /// [`Limits::max_total_size`] also bounds any entry that [`visit`](Self::visit) has to
+    /// decompress whole into memory, as described for
+    /// [`read_zipfile_from_stream`](crate::read::read_zipfile_from_stream).
+    pub const fn with_limits(reader: R, limits: Limits) -> Self {
+        ZipStreamReader {
+            reader,
+            limits,
+            restore_times: true,
+        }
+    }
+
+    /// Set whether [`extract`](Self::extract) gives extracted files and directories the times
+    /// stored in the archive, as described for
+    /// [`ExtractOptions::restore_times`](super::ExtractOptions::restore_times).
+    ///
+    /// The default is true.
+    #[must_use]
+    pub const fn restore_times(mut self, restore: bool) -> Self {
+        self.restore_times = restore;
+        self
     }
 }
 
 impl<R: Read> ZipStreamReader<R> {
-    fn parse_central_directory(&mut self) -> ZipResult<ZipStreamFileMetadata> {
+    fn parse_central_directory(reader: &mut impl Read) -> ZipResult<ZipStreamFileMetadata> {
         // Give archive_offset and central_header_start dummy value 0, since
         // they are not used in the output.
         let archive_offset = 0;
         let central_header_start = 0;
 
         // Parse central header
-        let block = ZipCentralEntryBlock::parse(&mut self.0)?;
-        let file = central_header_to_zip_file_inner(
-            &mut self.0,
-            archive_offset,
-            central_header_start,
-            block,
-        )?;
+        let block = ZipCentralEntryBlock::parse(reader)?;
+        let file =
+            central_header_to_zip_file_inner(reader, archive_offset, central_header_start, block)?;
         Ok(ZipStreamFileMetadata(file))
     }
 
     /// Iterate over the stream and extract all file and their
     /// metadata.
-    pub fn visit<V: ZipStreamVisitor>(mut self, visitor: &mut V) -> ZipResult<()> {
-        while let Some(mut file) = read_zipfile_from_stream(&mut self.0)? {
+    ///
+    /// The stream is buffered internally, so that entries followed by a data descriptor can be
+    /// decoded without reading it one byte at a time.
+    pub fn visit<V: ZipStreamVisitor>(self, visitor: &mut V) -> ZipResult<()> {
+        let mut reader = io::BufReader::new(self.reader);
+        while let Some(mut file) =
+            read_zipfile_from_buffered_stream(&mut reader, self.limits.max_total_size)?
+        {
             visitor.visit_file(&mut file)?;
         }
 
-        while let Ok(metadata) = self.parse_central_directory() {
+        while let Ok(metadata) = Self::parse_central_directory(&mut reader) {
             visitor.visit_additional_metadata(&metadata)?;
         }
 
@@ -56,24 +88,49 @@ impl<R: Read> ZipStreamReader<R> {
     ///
     /// Extraction is not atomic; If an error is encountered, some of the files
     /// may be left on disk.
+    ///
+    /// Files and directories are given the times stored in the archive, unless turned off with
+    /// [`restore_times`](Self::restore_times). Limits given to [`with_limits`](Self::with_limits)
+    /// are enforced as each entry is decompressed.
     pub fn extract<P: AsRef<Path>>(self, directory: P) -> ZipResult<()> {
-        struct Extractor<'a>(&'a Path);
+        struct Extractor<'a> {
+            directory: &'a Path,
+            budget: Budget,
+            entries: usize,
+            restore_times: bool,
+            dirs_with_times: Vec<(PathBuf, (FileTime, FileTime))>,
+        }
         impl ZipStreamVisitor for Extractor<'_> {
             fn visit_file(&mut self, file: &mut ZipFile<'_>) -> ZipResult<()> {
+                self.entries += 1;
+                self.budget.limits().check_entry_count(self.entries)?;
+                self.budget.limits().check_name(file.name())?;
                 let filepath = file
                     .enclosed_name()
                     .ok_or(ZipError::InvalidArchive("Invalid file path"))?;
 
-                let outpath = self.0.join(filepath);
+                let outpath = self.directory.join(filepath);
+                let times = entry_times(&file.data).filter(|_| self.restore_times);
 
                 if file.is_dir() {
                     fs::create_dir_all(&outpath)?;
+                    if let Some(times) = times {
+                        self.dirs_with_times.push((outpath, times));
+                    }
                 } else {
                     if let Some(p) = outpath.parent() {
                         fs::create_dir_all(p)?;
                     }
                     let mut outfile = fs::File::create(&outpath)?;
-                    io::copy(file, &mut outfile)?;
+                    io::copy(
+                        &mut self
+                            .budget
+                            .reader(file, |file| file.compressed_bytes_read()),
+                        &mut outfile,
+                    )?;
+                    if let Some((accessed, modified)) = times {
+                        set_file_handle_times(&outfile, Some(accessed), Some(modified))?;
+                    }
                 }
 
                 Ok(())
@@ -90,7 +147,7 @@ impl<R: Read> ZipStreamReader<R> {
                         .enclosed_name()
                         .ok_or(ZipError::InvalidArchive("Invalid file path"))?;
 
-                    let outpath = self.0.join(filepath);
+                    let outpath = self.directory.join(filepath);
 
                     use std::os::unix::fs::PermissionsExt;
                     if let Some(mode) = metadata.unix_mode() {
@@ -102,7 +159,21 @@ impl<R: Read> ZipStreamReader<R> {
             }
         }
 
-        self.visit(&mut Extractor(directory.as_ref()))
+        let mut extractor = Extractor {
+            directory: directory.as_ref(),
+            budget: Budget::new(self.limits),
+            entries: 0,
+            restore_times: self.restore_times,
+            dirs_with_times: Vec::new(),
+        };
+        self.visit(&mut extractor)?;
+        // Creating anything in a directory changes its times, so they're set last, deepest first.
+        let mut dirs_with_times = extractor.dirs_with_times;
+        dirs_with_times.sort_by(|(a, _), (b, _)| b.cmp(a));
+        for (path, (accessed, modified)) in dirs_with_times {
+            set_file_times(path, accessed, modified)?;
+        }
+        Ok(())
     }
 }
 
@@ -182,7 +253,7 @@ impl ZipStreamFileMetadata {
         self.name()
             .chars()
             .next_back()
-            .map_or(false, |c| c == '/' || c == '\\')
+            .is_some_and(|c| c == '/' || c == '\\')
     }
 
     /// Returns whether the file is a regular file
@@ -360,4 +431,76 @@ mod test {
         .visit(&mut DummyVisitor)
         .unwrap_err();
     }
+
+    #[test]
+    fn extract_with_limits() -> ZipResult<()> {
+        use crate::write::{SimpleFileOptions, ZipWriter};
+        use std::io::Write;
+
+        let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
+        for name in ["a/b/c", "d"] {
+            writer.start_file(name, SimpleFileOptions::default())?;
+            writer.write_all(&[0; 1000])?;
+        }
+        let bytes = writer.finish()?.into_inner();
+
+        let extract = |limits: Limits| {
+            let tempdir = tempdir::TempDir::new("stream_extract_with_limits")?;
+            ZipStreamReader::with_limits(&bytes[..], limits).extract(&tempdir)
+        };
+        for limits in [
+            Limits {
+                max_total_size: Some(1999),
+                ..Default::default()
+            },
+            Limits {
+                max_entries: Some(1),
+                ..Default::default()
+            },
+            Limits {
+                max_depth: Some(2),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(extract(limits), Err(ZipError::LimitExceeded(_))));
+        }
+        extract(Limits {
+            max_total_size: Some(2000),
+            max_entries: Some(2),
+            max_depth: Some(3),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    #[cfg(feature = "filetime")]
+    fn extract_restores_times() -> ZipResult<()> {
+        use crate::write::{SimpleFileOptions, ZipWriter};
+        use crate::DateTime;
+        use std::io::Write;
+
+        let time = DateTime::from_date_and_time(1999, 1, 2, 3, 4, 6)?;
+        let options = SimpleFileOptions::default().last_modified_time(time);
+        let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
+        writer.add_directory("dir/", options)?;
+        writer.start_file("dir/file", options)?;
+        writer.write_all(b"contents")?;
+        let bytes = writer.finish()?.into_inner();
+
+        let modified = |path: &Path| std::fs::metadata(path).unwrap().modified().unwrap();
+        let tempdir = tempdir::TempDir::new("stream_extract_restores_times")?;
+        ZipStreamReader::new(&bytes[..]).extract(&tempdir)?;
+        assert_eq!(
+            modified(&tempdir.path().join("dir/file")),
+            time.to_system_time()
+        );
+        assert_eq!(modified(&tempdir.path().join("dir")), time.to_system_time());
+
+        let tempdir = tempdir::TempDir::new("stream_extract_restores_times")?;
+        ZipStreamReader::new(&bytes[..])
+            .restore_times(false)
+            .extract(&tempdir)?;
+        assert!(modified(&tempdir.path().join("dir/file")) > time.to_system_time());
+        Ok(())
+    }
 }
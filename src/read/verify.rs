@@ -0,0 +1,763 @@
+//! Checking that an archive is sound, beyond what's needed to read it.
+
+use super::data_descriptor::has_zip64_extra_field;
+use super::zip_archive::Shared;
+use super::{find_data_start, ZipArchive};
+use crate::crc32::is_invalid_checksum;
+use crate::result::{ZipError, ZipResult};
+use crate::spec::{self, FixedSizeBlock, Magic};
+use crate::types::{AesVendorVersion, ZipCentralEntryBlock, ZipFileData, ZipLocalEntryBlock};
+use crate::unstable::LittleEndianReadExt;
+use crc32fast::Hasher;
+use displaydoc::Display;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::mem::size_of;
+
+/// What was tolerated while reading the central directory, kept for [`ZipArchive::verify`].
+#[derive(Debug, Default)]
+pub(crate) struct Tolerated {
+    /// The size of the central directory according to the end record, if it was read from one.
+    pub(crate) directory_size: Option<u64>,
+    /// An archive offset implied by the end record, which was ignored because there's no central
+    /// directory there.
+    pub(crate) ignored_archive_offset: Option<u64>,
+    /// End records that were tried before the one that was used, with where they start and why
+    /// they were passed over.
+    pub(crate) rejected_ends: Vec<(u64, String)>,
+    /// Entries that were replaced by a later entry with the same name.
+    pub(crate) duplicate_names: Vec<Box<str>>,
+}
+
+/// The result of [`ZipArchive::verify`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct VerificationReport {
+    /// Problems with the archive as a whole.
+    pub problems: Vec<ArchiveProblem>,
+    /// One report for each entry, in the order of [`ZipArchive::by_index`].
+    pub entries: Vec<EntryReport>,
+}
+
+impl VerificationReport {
+    /// Whether no problems were found with the archive or any of its entries.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty() && self.entries.iter().all(EntryReport::is_ok)
+    }
+}
+
+/// The result of verifying a single entry.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct EntryReport {
+    /// Name of the entry, as given by the central directory.
+    pub name: Box<str>,
+    /// Whether the contents were read and checked. They aren't when the entry is encrypted and no
+    /// password was given.
+    pub contents_checked: bool,
+    /// What's wrong with the entry, if anything.
+    pub problems: Vec<EntryProblem>,
+}
+
+impl EntryReport {
+    /// Whether no problems were found with this entry.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// A problem with an archive as a whole, found by [`ZipArchive::verify`].
+#[derive(Debug, Display)]
+#[non_exhaustive]
+pub enum ArchiveProblem {
+    /// {offset} bytes precede the archive, and its offsets don't account for them
+    ArchiveOffset {
+        /// How far every offset recorded in the archive is off by.
+        offset: u64,
+    },
+    /// the end record implies that {offset} bytes precede the archive, but the central directory isn't there
+    IgnoredArchiveOffset {
+        /// The offset that was implied and ignored.
+        offset: u64,
+    },
+    /// the end record at {position} was passed over: {reason}
+    RejectedEndRecord {
+        /// Where the end of central directory record starts.
+        position: u64,
+        /// Why it couldn't be used.
+        reason: String,
+    },
+    /// the central directory is {actual} bytes long, but the end record says {recorded}
+    CentralDirectorySize {
+        /// The size according to the end record.
+        recorded: u64,
+        /// The size of the headers that were read.
+        actual: u64,
+    },
+    /// the central directory holds more headers than the end record counts
+    UncountedEntries,
+    /// an entry named {name} is hidden by a later one with the same name
+    DuplicateName {
+        /// Name of the hidden entry.
+        name: Box<str>,
+    },
+}
+
+/// A problem with a single entry, found by [`ZipArchive::verify`].
+#[derive(Debug, Display)]
+#[non_exhaustive]
+pub enum EntryProblem {
+    /// the local header can't be read: {0}
+    UnreadableLocalHeader(ZipError),
+    /// the local header's {0} doesn't match the central directory
+    LocalHeaderMismatch(HeaderField),
+    /// the data descriptor can't be read: {0}
+    UnreadableDataDescriptor(ZipError),
+    /// the data descriptor's {0} doesn't match the central directory
+    DataDescriptorMismatch(HeaderField),
+    /// the data overlaps that of entry {0}
+    Overlap(usize),
+    /// the data runs past the start of the central directory
+    PastCentralDirectory,
+    /// the contents can't be read: {0}
+    UnreadableContents(ZipError),
+    /// the contents have CRC-32 {actual:#010x}, but {expected:#010x} was recorded
+    Crc32 {
+        /// The CRC-32 according to the central directory.
+        expected: u32,
+        /// The CRC-32 of the contents.
+        actual: u32,
+    },
+    /// the contents are {actual} bytes long, but {expected} was recorded
+    UncompressedSize {
+        /// The size according to the central directory.
+        expected: u64,
+        /// The size of the contents.
+        actual: u64,
+    },
+}
+
+/// A field that's recorded both in the central directory and in a local header or data
+/// descriptor.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HeaderField {
+    /// file name
+    Name,
+    /// compression method
+    CompressionMethod,
+    /// general purpose flags
+    Flags,
+    /// CRC-32
+    Crc32,
+    /// compressed size
+    CompressedSize,
+    /// uncompressed size
+    UncompressedSize,
+}
+
+impl<R: Read + Seek> ZipArchive<R> {
+    /// Checks that the archive is sound, reading every entry to the end.
+    ///
+    /// Besides the CRC-32 and size of each entry's contents, this checks that its local header
+    /// and data descriptor agree with the central directory, and that no two entries' data
+    /// overlap or run into the central directory. It also reports what [`ZipArchive::new`]
+    /// tolerated to find the central directory, such as an end record that was passed over
+    /// because it counted the wrong number of files, or data prepended to the archive.
+    ///
+    /// Problems are collected in the report rather than returned as errors; an error is only
+    /// returned if the central directory can no longer be read. The contents of encrypted entries
+    /// aren't checked; use [`ZipArchive::verify_decrypt`] for those.
+    ///
+    /// ```
+    /// # fn main() -> zip::result::ZipResult<()> {
+    /// let file = std::fs::File::open("tests/data/files_and_dirs.zip")?;
+    /// let mut archive = zip::ZipArchive::new(file)?;
+    /// let report = archive.verify()?;
+    /// for entry in &report.entries {
+    ///     for problem in &entry.problems {
+    ///         println!("{}: {problem}", entry.name);
+    ///     }
+    /// }
+    /// assert!(report.is_ok());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify(&mut self) -> ZipResult<VerificationReport> {
+        self.verify_with_optional_password(None)
+    }
+
+    /// Like [`ZipArchive::verify`], but also checks the contents of encrypted entries, which are
+    /// decrypted with `password`.
+    pub fn verify_decrypt(&mut self, password: &[u8]) -> ZipResult<VerificationReport> {
+        self.verify_with_optional_password(Some(password))
+    }
+
+    fn verify_with_optional_password(
+        &mut self,
+        password: Option<&[u8]>,
+    ) -> ZipResult<VerificationReport> {
+        let shared = self.shared.clone();
+        let mut problems = Vec::new();
+        if shared.offset != 0 {
+            problems.push(ArchiveProblem::ArchiveOffset {
+                offset: shared.offset,
+            });
+        }
+        let tolerated = &shared.tolerated;
+        if let Some(offset) = tolerated.ignored_archive_offset {
+            problems.push(ArchiveProblem::IgnoredArchiveOffset { offset });
+        }
+        for (position, reason) in &tolerated.rejected_ends {
+            problems.push(ArchiveProblem::RejectedEndRecord {
+                position: *position,
+                reason: reason.clone(),
+            });
+        }
+        for name in &tolerated.duplicate_names {
+            problems.push(ArchiveProblem::DuplicateName { name: name.clone() });
+        }
+
+        let mut entries = Vec::with_capacity(shared.files.len());
+        // Where each entry's local header, data and data descriptor are, by index.
+        let mut extents = Vec::with_capacity(shared.files.len());
+        let mut directory_end = shared.dir_start;
+        for (index, data) in shared.files.values().enumerate() {
+            let mut problems = Vec::new();
+            self.reader
+                .seek(SeekFrom::Start(data.central_header_start))?;
+            let central = ZipCentralEntryBlock::parse(&mut self.reader)?;
+            let mut central_name = vec![0; central.file_name_length as usize];
+            self.reader.read_exact(&mut central_name)?;
+            directory_end = directory_end.max(
+                data.central_header_start
+                    + size_of::<ZipCentralEntryBlock>() as u64
+                    + central.file_name_length as u64
+                    + central.extra_field_length as u64
+                    + central.file_comment_length as u64,
+            );
+
+            let local = check_local_header(
+                &mut self.reader,
+                data,
+                &central,
+                &central_name,
+                &mut problems,
+            );
+            match local {
+                Ok(end) => {
+                    if end > shared.dir_start {
+                        problems.push(EntryProblem::PastCentralDirectory);
+                    }
+                    extents.push((data.header_start, end, index));
+                }
+                Err(e) => problems.push(EntryProblem::UnreadableLocalHeader(e)),
+            }
+
+            let contents_checked = password.is_some() || !data.encrypted;
+            if contents_checked {
+                let mut digest = Digest::default();
+                let result = self
+                    .by_index_with_optional_password(index, password)
+                    .and_then(|mut file| Ok(io::copy(&mut file, &mut digest)?));
+                match result {
+                    Err(ZipError::Io(e)) if is_invalid_checksum(&e) => {}
+                    Err(e) => problems.push(EntryProblem::UnreadableContents(e)),
+                    Ok(_) => {}
+                }
+                if !matches!(problems.last(), Some(EntryProblem::UnreadableContents(_))) {
+                    let actual = digest.hasher.finalize();
+                    let ae2 = matches!(data.aes_mode, Some((_, AesVendorVersion::Ae2, _)));
+                    if !ae2 && actual != data.crc32 {
+                        problems.push(EntryProblem::Crc32 {
+                            expected: data.crc32,
+                            actual,
+                        });
+                    }
+                    if digest.len != data.uncompressed_size {
+                        problems.push(EntryProblem::UncompressedSize {
+                            expected: data.uncompressed_size,
+                            actual: digest.len,
+                        });
+                    }
+                }
+            }
+
+            entries.push(EntryReport {
+                name: data.file_name.clone(),
+                contents_checked,
+                problems,
+            });
+        }
+
+        let actual = directory_end - shared.dir_start;
+        if let Some(recorded) = tolerated.directory_size.filter(|&size| size != actual) {
+            problems.push(ArchiveProblem::CentralDirectorySize { recorded, actual });
+        }
+        self.reader.seek(SeekFrom::Start(directory_end))?;
+        if let Ok(magic) = self.reader.read_u32_le() {
+            if Magic::from_le_bytes(magic.to_le_bytes())
+                == Magic::CENTRAL_DIRECTORY_HEADER_SIGNATURE
+            {
+                problems.push(ArchiveProblem::UncountedEntries);
+            }
+        }
+
+        for (first, second) in overlaps(extents) {
+            entries[first].problems.push(EntryProblem::Overlap(second));
+            entries[second].problems.push(EntryProblem::Overlap(first));
+        }
+
+        Ok(VerificationReport { problems, entries })
+    }
+}
+
+impl<R: Read + Seek> ZipArchive<R> {
+    /// Lists the pairs of entries whose local headers and data overlap, by index.
+    ///
+    /// Each pair has the entry that starts first on the left. An entry that overlaps several
+    /// others is paired with at least one of them. Unless the archive was read with
+    /// [`Limits::reject_overlapping_entries`](crate::read::Limits::reject_overlapping_entries) set,
+    /// this reads every local header.
+    pub fn overlapping_entries(&mut self) -> ZipResult<Vec<(usize, usize)>> {
+        let extents = entry_extents(&self.shared, &mut self.reader)?;
+        Ok(overlaps(extents))
+    }
+}
+
+/// Where each entry's local header and data are, as `(start, end, index)`.
+fn entry_extents<R: Read + Seek>(
+    shared: &Shared,
+    reader: &mut R,
+) -> ZipResult<Vec<(u64, u64, usize)>> {
+    let mut extents = Vec::with_capacity(shared.files.len());
+    for (index, data) in shared.files.values().enumerate() {
+        let data_start = match data.data_start.get() {
+            Some(data_start) => *data_start,
+            None => find_data_start(data, reader)?,
+        };
+        extents.push((
+            data.header_start,
+            data_start.saturating_add(data.compressed_size),
+            index,
+        ));
+    }
+    Ok(extents)
+}
+
+/// Pairs up overlapping extents, comparing each with the one reaching furthest among those that
+/// start before it.
+fn overlaps(mut extents: Vec<(u64, u64, usize)>) -> Vec<(usize, usize)> {
+    extents.sort_unstable();
+    let mut pairs = Vec::new();
+    let mut furthest: Option<(u64, usize)> = None;
+    for (start, end, index) in extents {
+        if let Some((furthest_end, other)) = furthest {
+            if start < furthest_end {
+                pairs.push((other, index));
+            }
+        }
+        if furthest.map_or(true, |(furthest_end, _)| end > furthest_end) {
+            furthest = Some((end, index));
+        }
+    }
+    pairs
+}
+
+/// Implements [`Limits::reject_overlapping_entries`](crate::read::Limits::reject_overlapping_entries).
+pub(crate) fn reject_overlapping_entries<R: Read + Seek>(
+    shared: &Shared,
+    reader: &mut R,
+) -> ZipResult<()> {
+    let extents = entry_extents(shared, reader)?;
+    if extents.iter().any(|&(_, end, _)| end > shared.dir_start) {
+        return Err(ZipError::InvalidArchive(
+            "Entry data runs into the central directory",
+        ));
+    }
+    if !overlaps(extents).is_empty() {
+        return Err(ZipError::InvalidArchive("Overlapping entries"));
+    }
+    Ok(())
+}
+
+/// Compares the local header and data descriptor of an entry with its central directory header,
+/// returning where the entry ends.
+fn check_local_header<R: Read + Seek>(
+    reader: &mut R,
+    data: &ZipFileData,
+    central: &ZipCentralEntryBlock,
+    central_name: &[u8],
+    problems: &mut Vec<EntryProblem>,
+) -> ZipResult<u64> {
+    reader.seek(SeekFrom::Start(data.header_start))?;
+    let local = ZipLocalEntryBlock::parse(reader)?;
+    let mut name = vec![0; local.file_name_length as usize];
+    reader.read_exact(&mut name)?;
+    let mut extra_field = vec![0; local.extra_field_length as usize];
+    reader.read_exact(&mut extra_field)?;
+    let data_start = reader.stream_position()?;
+
+    let mut mismatch = |field| problems.push(EntryProblem::LocalHeaderMismatch(field));
+    if name != central_name {
+        mismatch(HeaderField::Name);
+    }
+    if local.compression_method != central.compression_method {
+        mismatch(HeaderField::CompressionMethod);
+    }
+    if local.flags != central.flags {
+        mismatch(HeaderField::Flags);
+    }
+    // With a data descriptor, these may be left as zeros.
+    let descriptor = local.flags & (1 << 3) != 0;
+    if local.crc32 != data.crc32 && !(descriptor && local.crc32 == 0) {
+        mismatch(HeaderField::Crc32);
+    }
+    let (uncompressed_size, compressed_size) = local_sizes(&local, &extra_field);
+    if uncompressed_size != data.uncompressed_size && !(descriptor && uncompressed_size == 0) {
+        mismatch(HeaderField::UncompressedSize);
+    }
+    if compressed_size != data.compressed_size && !(descriptor && compressed_size == 0) {
+        mismatch(HeaderField::CompressedSize);
+    }
+
+    let data_end = data_start + data.compressed_size;
+    if !data.using_data_descriptor {
+        return Ok(data_end);
+    }
+    let zip64 = has_zip64_extra_field(&extra_field);
+    match read_data_descriptor(reader, data_end, zip64) {
+        Ok((crc32, compressed_size, uncompressed_size, end)) => {
+            let mut mismatch = |field| problems.push(EntryProblem::DataDescriptorMismatch(field));
+            if crc32 != data.crc32 {
+                mismatch(HeaderField::Crc32);
+            }
+            if compressed_size != data.compressed_size {
+                mismatch(HeaderField::CompressedSize);
+            }
+            if uncompressed_size != data.uncompressed_size {
+                mismatch(HeaderField::UncompressedSize);
+            }
+            Ok(end)
+        }
+        Err(e) => {
+            problems.push(EntryProblem::UnreadableDataDescriptor(e));
+            Ok(data_end)
+        }
+    }
+}
+
+/// Returns the uncompressed and compressed sizes given by a local header, taking those that
+/// don't fit in it from its ZIP64 extra field.
+fn local_sizes(local: &ZipLocalEntryBlock, mut extra_field: &[u8]) -> (u64, u64) {
+    let mut uncompressed_size = local.uncompressed_size as u64;
+    let mut compressed_size = local.compressed_size as u64;
+    while let (Ok(kind), Ok(len)) = (extra_field.read_u16_le(), extra_field.read_u16_le()) {
+        let Some(mut field) = extra_field.get(..len as usize) else {
+            break;
+        };
+        if kind == 0x0001 {
+            if uncompressed_size == spec::ZIP64_BYTES_THR {
+                uncompressed_size = field.read_u64_le().unwrap_or(uncompressed_size);
+            }
+            if compressed_size == spec::ZIP64_BYTES_THR {
+                compressed_size = field.read_u64_le().unwrap_or(compressed_size);
+            }
+            break;
+        }
+        extra_field = &extra_field[len as usize..];
+    }
+    (uncompressed_size, compressed_size)
+}
+
+/// Reads the data descriptor at `position`, returning the CRC-32, compressed size and
+/// uncompressed size it gives, and where it ends.
+fn read_data_descriptor<R: Read + Seek>(
+    reader: &mut R,
+    position: u64,
+    zip64: bool,
+) -> ZipResult<(u32, u64, u64, u64)> {
+    reader.seek(SeekFrom::Start(position))?;
+    // The signature is optional.
+    let mut crc32 = reader.read_u32_le()?;
+    if Magic::from_le_bytes(crc32.to_le_bytes()) == Magic::DATA_DESCRIPTOR_SIGNATURE {
+        crc32 = reader.read_u32_le()?;
+    }
+    let (compressed_size, uncompressed_size) = if zip64 {
+        (reader.read_u64_le()?, reader.read_u64_le()?)
+    } else {
+        (reader.read_u32_le()? as u64, reader.read_u32_le()? as u64)
+    };
+    Ok((
+        crc32,
+        compressed_size,
+        uncompressed_size,
+        reader.stream_position()?,
+    ))
+}
+
+/// Computes the CRC-32 and length of what's written to it.
+#[derive(Default)]
+struct Digest {
+    hasher: Hasher,
+    len: u64,
+}
+
+impl Write for Digest {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+        self.len += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ArchiveProblem, EntryProblem, HeaderField};
+    use crate::result::{ZipError, ZipResult};
+    use crate::write::SimpleFileOptions;
+    use crate::{CompressionMethod, ZipArchive, ZipWriter};
+    use std::io::{Cursor, Seek, Write};
+
+    fn archive(bytes: Vec<u8>) -> ZipResult<ZipArchive<Cursor<Vec<u8>>>> {
+        ZipArchive::new(Cursor::new(bytes))
+    }
+
+    /// An archive with a stored file `a`, written with a data descriptor if `stream`.
+    fn stored_archive(stream: bool) -> ZipResult<Vec<u8>> {
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        if stream {
+            let mut zip = ZipWriter::new_stream(Vec::new());
+            zip.start_file("a", options)?;
+            zip.write_all(b"Hello, World!")?;
+            Ok(zip.finish()?.into_inner())
+        } else {
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            zip.start_file("a", options)?;
+            zip.write_all(b"Hello, World!")?;
+            Ok(zip.finish()?.into_inner())
+        }
+    }
+
+    /// Writes files with and without ZIP64 extra fields and encryption.
+    fn write_files<W: Write + Seek>(mut zip: ZipWriter<W>) -> ZipResult<W> {
+        let contents = vec![7; 100_000];
+        for large_file in [false, true] {
+            let options = SimpleFileOptions::default().large_file(large_file);
+            zip.start_file(format!("{large_file}"), options)?;
+            zip.write_all(&contents)?;
+            zip.start_file(
+                format!("{large_file} encrypted"),
+                options.with_deprecated_encryption(b"password"),
+            )?;
+            zip.write_all(&contents)?;
+        }
+        zip.add_directory("dir", SimpleFileOptions::default())?;
+        zip.finish()
+    }
+
+    #[test]
+    fn sound_archives() -> ZipResult<()> {
+        let seekable = write_files(ZipWriter::new(Cursor::new(Vec::new())))?.into_inner();
+        let stream = write_files(ZipWriter::new_stream(Vec::new()))?.into_inner();
+        for bytes in [seekable, stream] {
+            let report = archive(bytes.clone())?.verify()?;
+            assert!(report.is_ok(), "{report:?}");
+            assert!(!report.entries[1].contents_checked);
+            let report = archive(bytes)?.verify_decrypt(b"password")?;
+            assert!(report.is_ok(), "{report:?}");
+            assert!(report.entries.iter().all(|entry| entry.contents_checked));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_contents() -> ZipResult<()> {
+        let mut bytes = stored_archive(false)?;
+        // After the 30-byte local header and 1-byte name.
+        bytes[31] ^= 1;
+        let report = archive(bytes)?.verify()?;
+        assert!(report.problems.is_empty());
+        assert!(matches!(
+            report.entries[0].problems[..],
+            [EntryProblem::Crc32 { expected, actual }] if expected != actual
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn local_header_mismatch() -> ZipResult<()> {
+        let mut bytes = stored_archive(false)?;
+        bytes[30] = b'b';
+        // The last byte of the compressed size.
+        bytes[21] = 1;
+        let report = archive(bytes)?.verify()?;
+        assert!(matches!(
+            report.entries[0].problems[..],
+            [
+                EntryProblem::LocalHeaderMismatch(HeaderField::Name),
+                EntryProblem::LocalHeaderMismatch(HeaderField::CompressedSize),
+            ]
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn data_descriptor_mismatch() -> ZipResult<()> {
+        let mut bytes = stored_archive(true)?;
+        // The uncompressed size in the data descriptor, after its signature, CRC and compressed
+        // size.
+        bytes[31 + 13 + 12] = 14;
+        let report = archive(bytes)?.verify()?;
+        assert!(matches!(
+            report.entries[0].problems[..],
+            [EntryProblem::DataDescriptorMismatch(
+                HeaderField::UncompressedSize
+            )]
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn overlapping_entries() -> ZipResult<()> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("a", SimpleFileOptions::default())?;
+        zip.write_all(b"Hello, World!")?;
+        zip.start_file("b", SimpleFileOptions::default())?;
+        zip.shallow_copy_file("a", "c")?;
+        let report = archive(zip.finish()?.into_inner())?.verify()?;
+        assert!(matches!(
+            report.entries[0].problems[..],
+            [EntryProblem::Overlap(2)]
+        ));
+        assert!(report.entries[1].is_ok());
+        assert!(matches!(
+            report.entries[2].problems[..],
+            [
+                EntryProblem::LocalHeaderMismatch(HeaderField::Name),
+                EntryProblem::Overlap(0),
+            ]
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn reject_overlapping_entries() -> ZipResult<()> {
+        use crate::read::{Config, Limits};
+
+        let open = |bytes: Vec<u8>| {
+            let limits = Limits {
+                reject_overlapping_entries: true,
+                ..Default::default()
+            };
+            ZipArchive::with_limits(Config::default(), limits, Cursor::new(bytes))
+        };
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("a", SimpleFileOptions::default())?;
+        zip.write_all(b"Hello, World!")?;
+        zip.start_file("b", SimpleFileOptions::default())?;
+        zip.shallow_copy_file("a", "c")?;
+        let bytes = zip.finish()?.into_inner();
+        assert_eq!(archive(bytes.clone())?.overlapping_entries()?, [(0, 2)]);
+        assert!(matches!(
+            open(bytes),
+            Err(ZipError::InvalidArchive("Overlapping entries"))
+        ));
+
+        let mut bytes = stored_archive(false)?;
+        assert!(archive(bytes.clone())?.overlapping_entries()?.is_empty());
+        open(bytes.clone())?;
+        // Claim the data runs on into the central directory.
+        let central = bytes
+            .windows(4)
+            .position(|window| window == b"PK\x01\x02")
+            .unwrap();
+        bytes[central + 20..central + 24].copy_from_slice(&100u32.to_le_bytes());
+        assert!(archive(bytes.clone())?.overlapping_entries()?.is_empty());
+        assert!(matches!(
+            open(bytes),
+            Err(ZipError::InvalidArchive(
+                "Entry data runs into the central directory"
+            ))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn tolerated_central_directory() -> ZipResult<()> {
+        let bytes = stored_archive(false)?;
+
+        let mut prepended = b"junk".to_vec();
+        prepended.extend_from_slice(&bytes);
+        let report = archive(prepended)?.verify()?;
+        assert!(matches!(
+            report.problems[..],
+            [ArchiveProblem::ArchiveOffset { offset: 4 }]
+        ));
+        assert!(report.entries[0].is_ok());
+
+        // A copy of the end record counting one more file than there is, appended to the archive.
+        let mut appended = bytes.clone();
+        let end_record = bytes.len() - 22;
+        appended.extend_from_slice(&bytes[end_record..]);
+        appended[bytes.len() + 10] += 1;
+        let report = archive(appended)?.verify()?;
+        assert!(matches!(
+            report.problems[..],
+            [ArchiveProblem::RejectedEndRecord { position, .. }] if position == bytes.len() as u64
+        ));
+
+        // A central directory with a second header for the same file, which the end record
+        // includes in its size but doesn't count.
+        let central_start = end_record - 47;
+        let mut uncounted = bytes[..end_record].to_vec();
+        uncounted.extend_from_slice(&bytes[central_start..]);
+        uncounted[end_record + 47 + 12] = 94;
+        let report = archive(uncounted)?.verify()?;
+        assert!(matches!(
+            report.problems[..],
+            [
+                ArchiveProblem::CentralDirectorySize {
+                    recorded: 94,
+                    actual: 47
+                },
+                ArchiveProblem::UncountedEntries,
+            ]
+        ));
+
+        // A second file that's renamed to the first in the central directory.
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("a", SimpleFileOptions::default())?;
+        zip.start_file("b", SimpleFileOptions::default())?;
+        let mut duplicate = zip.finish()?.into_inner();
+        let end_record = duplicate.len() - 22;
+        duplicate[end_record - 1] = b'a';
+        let report = archive(duplicate)?.verify()?;
+        assert!(matches!(
+            &report.problems[..],
+            [ArchiveProblem::DuplicateName { name }] if &**name == "a"
+        ));
+        assert!(matches!(
+            report.entries[..],
+            [ref entry] if matches!(
+                entry.problems[..],
+                [EntryProblem::LocalHeaderMismatch(HeaderField::Name)]
+            )
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn split_archive() -> ZipResult<()> {
+        let volumes = vec![
+            Cursor::new(include_bytes!("../../tests/data/split.z01").to_vec()),
+            Cursor::new(include_bytes!("../../tests/data/split.zip").to_vec()),
+        ];
+        let report = ZipArchive::open_split(volumes)?.verify()?;
+        assert!(report.is_ok(), "{report:?}");
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+//! Reading archives that are split across several files (volumes).
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// A reader over the volumes of a split archive (such as `archive.z01`, `archive.z02`, ...,
+/// `archive.zip`), which behaves as if they were concatenated in order.
+///
+/// This is what [`crate::ZipArchive::open_split`] reads from; it can also be used on its own,
+/// for instance to join the volumes.
+#[derive(Clone, Debug)]
+pub struct MultiVolumeReader<R> {
+    volumes: Vec<R>,
+    /// Where each volume starts in the concatenation, followed by its total length.
+    starts: Vec<u64>,
+    position: u64,
+    /// The volume whose cursor is at `position`, if any.
+    current: Option<usize>,
+}
+
+impl<R: Seek> MultiVolumeReader<R> {
+    /// Concatenate `volumes`, which must be given in order, ending with the one that contains the
+    /// end of the central directory (usually the `.zip` file).
+    pub fn new(mut volumes: Vec<R>) -> io::Result<Self> {
+        if volumes.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "A split archive needs at least one volume",
+            ));
+        }
+        let mut starts = Vec::with_capacity(volumes.len() + 1);
+        let mut total: u64 = 0;
+        for volume in volumes.iter_mut() {
+            starts.push(total);
+            let len = volume.seek(SeekFrom::End(0))?;
+            total = total.checked_add(len).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "Volumes are too large")
+            })?;
+        }
+        starts.push(total);
+        Ok(MultiVolumeReader {
+            volumes,
+            starts,
+            position: 0,
+            current: None,
+        })
+    }
+}
+
+impl<R> MultiVolumeReader<R> {
+    /// The offset at which each volume starts, relative to the start of the first one.
+    pub fn volume_starts(&self) -> &[u64] {
+        &self.starts[..self.volumes.len()]
+    }
+
+    /// Number of volumes.
+    pub fn len(&self) -> usize {
+        self.volumes.len()
+    }
+
+    /// Whether there are no volumes, which [`MultiVolumeReader::new`] doesn't allow.
+    pub fn is_empty(&self) -> bool {
+        self.volumes.is_empty()
+    }
+
+    /// Unwrap the volumes. Their cursors are left at arbitrary positions.
+    pub fn into_inner(self) -> Vec<R> {
+        self.volumes
+    }
+
+    fn total_len(&self) -> u64 {
+        self.starts[self.volumes.len()]
+    }
+}
+
+impl<R: Read + Seek> Read for MultiVolumeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.total_len() {
+            return Ok(0);
+        }
+        // The last volume starting at or before the position; empty volumes are skipped, since a
+        // later one starts at the same offset.
+        let index = self.starts[..self.volumes.len()].partition_point(|&s| s <= self.position) - 1;
+        let volume_start = self.starts[index];
+        let volume_end = self.starts[index + 1];
+        if self.current != Some(index) {
+            self.volumes[index].seek(SeekFrom::Start(self.position - volume_start))?;
+            self.current = Some(index);
+        }
+        let max_len = (volume_end - self.position).min(buf.len() as u64) as usize;
+        let count = self.volumes[index].read(&mut buf[..max_len])?;
+        if count == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Volume is shorter than when the archive was opened",
+            ));
+        }
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl<R: Read + Seek> Seek for MultiVolumeReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.total_len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )
+        })?;
+        if position != self.position {
+            self.position = position;
+            self.current = None;
+        }
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MultiVolumeReader;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    #[test]
+    fn reads_across_volumes() {
+        let volumes = vec![
+            Cursor::new(b"abc".to_vec()),
+            Cursor::new(Vec::new()),
+            Cursor::new(b"defg".to_vec()),
+            Cursor::new(b"h".to_vec()),
+        ];
+        let mut reader = MultiVolumeReader::new(volumes).unwrap();
+        assert_eq!(reader.volume_starts(), &[0, 3, 3, 7]);
+
+        let mut all = String::new();
+        reader.read_to_string(&mut all).unwrap();
+        assert_eq!(all, "abcdefgh");
+
+        let mut buf = [0u8; 4];
+        assert_eq!(reader.seek(SeekFrom::Start(2)).unwrap(), 2);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"cdef");
+        assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 6);
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], b'g');
+        assert_eq!(reader.seek(SeekFrom::Current(-4)).unwrap(), 3);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"defg");
+        reader.seek(SeekFrom::Current(-10)).unwrap_err();
+    }
+}
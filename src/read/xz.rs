@@ -1,29 +1,35 @@
+use crate::result::ZipError;
 use crc32fast::Hasher;
 use lzma_rs::decompress::raw::Lzma2Decoder;
 use std::{
     collections::VecDeque,
-    io::{BufRead, BufReader, Error, Read, Result, Write},
+    io::{BufRead, Error, Read, Result, Write},
 };
 
 #[derive(Debug)]
 pub struct XzDecoder<R> {
-    compressed_reader: BufReader<R>,
+    compressed_reader: R,
     stream_size: usize,
     buf: VecDeque<u8>,
     check_size: usize,
     records: Vec<(usize, usize)>,
     flags: [u8; 2],
+    /// How much more may be decompressed.
+    remaining: u64,
 }
 
-impl<R: Read> XzDecoder<R> {
-    pub fn new(inner: R) -> Self {
+impl<R: BufRead> XzDecoder<R> {
+    /// Each block is decompressed whole into memory, and may hold everything, so this fails
+    /// before decompressing more than `max_size` bytes in all.
+    pub fn new(inner: R, max_size: u64) -> Self {
         XzDecoder {
-            compressed_reader: BufReader::new(inner),
+            compressed_reader: inner,
             stream_size: 0,
             buf: VecDeque::new(),
             check_size: 0,
             records: vec![],
             flags: [0, 0],
+            remaining: max_size,
         }
     }
 }
@@ -52,6 +58,120 @@ impl<R: BufRead> BufRead for CountReader<'_, R> {
     }
 }
 
+/// Adds up the unpacked sizes in the headers of the LZMA2 chunks that pass through it to the
+/// decoder, which keeps everything it decompresses from a block in memory until the block ends.
+/// Once they come to more than it allows, it stops passing anything on, so that nothing more is
+/// decompressed.
+struct ChunkSizeLimit<'a, R> {
+    inner: &'a mut R,
+    chunks: ChunkHeaders,
+}
+
+impl<R> ChunkSizeLimit<'_, R> {
+    fn check(&self) -> Result<()> {
+        if self.chunks.exceeded {
+            return Err(Error::new(
+                std::io::ErrorKind::Other,
+                "Entry too large to decompress in memory",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for ChunkSizeLimit<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.check()?;
+        let count = self.inner.read(buf)?;
+        self.chunks.scan(&buf[..count]);
+        Ok(count)
+    }
+}
+
+impl<R: BufRead> BufRead for ChunkSizeLimit<'_, R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        self.check()?;
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // What's consumed was returned by the last call to fill_buf, which returns it again.
+        if let Ok(available) = self.inner.fill_buf() {
+            self.chunks.scan(&available[..amt.min(available.len())]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+/// Follows the LZMA2 chunk headers through the bytes of a block.
+struct ChunkHeaders {
+    /// The header read so far, starting with its control byte.
+    header: [u8; 6],
+    header_len: usize,
+    /// How much of the current chunk's data is still to come.
+    data_left: u64,
+    ended: bool,
+    /// How much more the chunks may unpack to.
+    remaining: u64,
+    exceeded: bool,
+}
+
+impl ChunkHeaders {
+    fn new(remaining: u64) -> Self {
+        ChunkHeaders {
+            header: [0; 6],
+            header_len: 0,
+            data_left: 0,
+            ended: false,
+            remaining,
+            exceeded: false,
+        }
+    }
+
+    fn scan(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() && !self.ended {
+            if self.data_left > 0 {
+                let skipped = self.data_left.min(bytes.len() as u64);
+                self.data_left -= skipped;
+                bytes = &bytes[skipped as usize..];
+                continue;
+            }
+            self.header[self.header_len] = bytes[0];
+            self.header_len += 1;
+            bytes = &bytes[1..];
+            let control = self.header[0];
+            let header_len = match control {
+                0x01 | 0x02 => 3,
+                0x80..=0xBF => 5,
+                0xC0..=0xFF => 6,
+                // The end of the block, or an invalid chunk that the decoder reports.
+                _ => 1,
+            };
+            if self.header_len < header_len {
+                continue;
+            }
+            self.header_len = 0;
+            let size = u16::from_be_bytes([self.header[1], self.header[2]]) as u64 + 1;
+            let (unpacked_size, data_len) = match control {
+                0x01 | 0x02 => (size, size),
+                0x80..=0xFF => (
+                    (((control & 0x1F) as u64) << 16) + size,
+                    u16::from_be_bytes([self.header[3], self.header[4]]) as u64 + 1,
+                ),
+                _ => {
+                    self.ended = true;
+                    break;
+                }
+            };
+            self.data_left = data_len;
+            match self.remaining.checked_sub(unpacked_size) {
+                Some(remaining) => self.remaining = remaining,
+                None => self.exceeded = true,
+            }
+        }
+    }
+}
+
 struct BufWriter<'a> {
     inner: &'a mut [u8],
     written: &'a mut usize,
@@ -98,7 +218,7 @@ fn get_multibyte<R: Read>(input: &mut R, hasher: &mut Hasher) -> Result<u64> {
     error("Invalid multi-byte encoding")
 }
 
-impl<R: Read> Read for XzDecoder<R> {
+impl<R: BufRead> Read for XzDecoder<R> {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
         if !self.buf.is_empty() {
             let len = std::cmp::min(buf.len(), self.buf.len());
@@ -111,12 +231,14 @@ impl<R: Read> Read for XzDecoder<R> {
             count: &mut self.stream_size,
         };
         if *reader.count == 0 {
-            let mut b = [0u8; 12];
-            match reader.read(&mut b) {
-                Ok(0) => return Ok(0),
-                Err(e) => return Err(e),
+            match reader.fill_buf()?.first() {
+                None => return Ok(0),
+                // Streams may be concatenated, but anything else following one isn't ours to read.
+                Some(&b) if b != 0xFD && !self.records.is_empty() => return Ok(0),
                 _ => (),
             }
+            let mut b = [0u8; 12];
+            reader.read_exact(&mut b)?;
             if b[..6] != b"\xFD7zXZ\0"[..] {
                 return error("Invalid XZ header");
             }
@@ -241,15 +363,24 @@ impl<R: Read> Read for XzDecoder<R> {
         }
         let mut written = 0;
         let mut total = 0;
-        Lzma2Decoder::new().decompress(
-            &mut reader,
+        let mut limited = ChunkSizeLimit {
+            inner: &mut reader,
+            chunks: ChunkHeaders::new(self.remaining),
+        };
+        let result = Lzma2Decoder::new().decompress(
+            &mut limited,
             &mut BufWriter {
                 inner: buf,
                 written: &mut written,
                 rest: &mut self.buf,
                 total: &mut total,
             },
-        )?;
+        );
+        if limited.chunks.exceeded {
+            return Err(ZipError::LimitExceeded("Entry too large to decompress in memory").into());
+        }
+        result?;
+        self.remaining = limited.chunks.remaining;
 
         let unpadded_size = *reader.count - block_begin;
         self.records.push((unpadded_size, total));
@@ -263,8 +394,12 @@ impl<R: Read> Read for XzDecoder<R> {
     }
 }
 
-impl<R: Read> XzDecoder<R> {
+impl<R: BufRead> XzDecoder<R> {
     pub fn into_inner(self) -> R {
-        self.compressed_reader.into_inner()
+        self.compressed_reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.compressed_reader
     }
 }
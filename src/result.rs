@@ -19,7 +19,7 @@ pub type ZipResult<T> = Result<T, ZipError>;
 #[non_exhaustive]
 pub enum ZipError {
     /// i/o error: {0}
-    Io(#[from] io::Error),
+    Io(#[source] io::Error),
 
     /// invalid Zip archive: {0}
     InvalidArchive(&'static str),
@@ -32,6 +32,9 @@ pub enum ZipError {
 
     /// The password provided is incorrect
     InvalidPassword,
+
+    /// extraction limit exceeded: {0}
+    LimitExceeded(&'static str),
 }
 
 impl ZipError {
@@ -57,12 +60,26 @@ impl From<ZipError> for io::Error {
             ZipError::UnsupportedArchive(_) => io::ErrorKind::Unsupported,
             ZipError::FileNotFound => io::ErrorKind::NotFound,
             ZipError::InvalidPassword => io::ErrorKind::InvalidInput,
+            ZipError::LimitExceeded(_) => io::ErrorKind::Other,
         };
 
         io::Error::new(kind, err)
     }
 }
 
+impl From<io::Error> for ZipError {
+    fn from(err: io::Error) -> ZipError {
+        // Limits are enforced by readers, so breaches are unwrapped to tell them apart.
+        match err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<ZipError>())
+        {
+            Some(&ZipError::LimitExceeded(limit)) => ZipError::LimitExceeded(limit),
+            _ => ZipError::Io(err),
+        }
+    }
+}
+
 impl From<DateTimeRangeError> for ZipError {
     fn from(_: DateTimeRangeError) -> Self {
         ZipError::InvalidArchive("Invalid date or time")
@@ -54,6 +54,11 @@ impl Magic {
     pub const CENTRAL_DIRECTORY_END_SIGNATURE: Self = Self::literal(0x06054b50);
     pub const ZIP64_CENTRAL_DIRECTORY_END_SIGNATURE: Self = Self::literal(0x06064b50);
     pub const ZIP64_CENTRAL_DIRECTORY_END_LOCATOR_SIGNATURE: Self = Self::literal(0x07064b50);
+    pub const DATA_DESCRIPTOR_SIGNATURE: Self = Self::literal(0x08074b50);
+    /// Starts the first volume of an archive split across several files (APPNOTE 8.5.3).
+    pub const SPANNING_SIGNATURE: Self = Self::literal(0x08074b50);
+    /// Replaces [`Magic::SPANNING_SIGNATURE`] when a split archive fits in one volume (APPNOTE 8.5.4).
+    pub const SINGLE_SEGMENT_MARKER: Self = Self::literal(0x30304b50);
 }
 
 /// Similar to [`Magic`], but used for extra field tags as per section 4.5.3 of APPNOTE.TXT.
@@ -241,7 +246,7 @@ impl FixedSizeBlock for Zip32CDEBlock {
     ];
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Zip32CentralDirectoryEnd {
     pub disk_number: u16,
     pub disk_with_central_directory: u16,
@@ -659,7 +664,7 @@ pub(crate) fn is_dir(filename: &str) -> bool {
     filename
         .chars()
         .next_back()
-        .map_or(false, |c| c == '/' || c == '\\')
+        .is_some_and(|c| c == '/' || c == '\\')
 }
 
 #[cfg(test)]
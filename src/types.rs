@@ -7,6 +7,7 @@ use std::fmt::{Debug, Formatter};
 use std::mem;
 use std::path;
 use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime};
 
 #[cfg(feature = "chrono")]
 use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
@@ -225,6 +226,25 @@ impl DateTime {
         }
     }
 
+    /// The moment this represents, reading it as UTC since it has no time zone.
+    pub(crate) fn to_system_time(self) -> SystemTime {
+        // Days since 1970-01-01 in the proleptic Gregorian calendar, counting years from March so
+        // that leap days come last.
+        let (year, month) = match self.month {
+            1 | 2 => (self.year as i64 - 1, self.month as i64 + 9),
+            _ => (self.year as i64, self.month as i64 - 3),
+        };
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+        let seconds =
+            days * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64;
+        // MS-DOS dates start in 1980, so this is never before the epoch.
+        SystemTime::UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64)
+    }
+
     /// Converts an msdos (u16, u16) pair to a DateTime object if it represents a valid date and
     /// time.
     pub fn try_from_msdos(datepart: u16, timepart: u16) -> Result<DateTime, DateTimeRangeError> {
@@ -457,6 +477,8 @@ pub struct ZipFileData {
     pub file_comment: Box<str>,
     /// Specifies where the local header of the file starts
     pub header_start: u64,
+    /// Number of the disk (volume) that the local header is on, for archives split across several
+    pub disk_number: u32,
     /// Specifies where the extra data of the file starts
     pub extra_data_start: Option<u64>,
     /// Specifies where the central header of the file starts
@@ -533,6 +555,29 @@ impl ZipFileData {
         Some(path)
     }
 
+    /// When the file was last modified, from its extended timestamp if it has one, or else from
+    /// its MS-DOS time.
+    pub(crate) fn modified_time(&self) -> Option<SystemTime> {
+        let extended = self
+            .extra_fields
+            .iter()
+            .find_map(|ExtraField::ExtendedTimestamp(timestamp)| timestamp.mod_time());
+        match extended {
+            Some(seconds) => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds.into())),
+            None => self.last_modified_time.map(DateTime::to_system_time),
+        }
+    }
+
+    /// When the file was last accessed, which only an extended timestamp records, and usually
+    /// only in the local header.
+    #[cfg(feature = "filetime")]
+    pub(crate) fn accessed_time(&self) -> Option<SystemTime> {
+        self.extra_fields
+            .iter()
+            .find_map(|ExtraField::ExtendedTimestamp(timestamp)| timestamp.ac_time())
+            .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds.into()))
+    }
+
     /// Get unix mode for the file
     pub(crate) const fn unix_mode(&self) -> Option<u32> {
         if self.external_attributes == 0 {
@@ -662,6 +707,7 @@ impl ZipFileData {
             central_extra_field: options.extended_options.central_extra_data().cloned(),
             file_comment: String::with_capacity(0).into_boxed_str(),
             header_start,
+            disk_number: 0,
             data_start: OnceLock::new(),
             central_header_start: 0,
             external_attributes: permissions << 16,
@@ -695,20 +741,10 @@ impl ZipFileData {
         } = block;
 
         let encrypted: bool = flags & 1 == 1;
-        if encrypted {
-            return Err(ZipError::UnsupportedArchive(
-                "Encrypted files are not supported",
-            ));
-        }
 
         /* FIXME: these were previously incorrect: add testing! */
         /* flags & (1 << 3) != 0 */
         let using_data_descriptor: bool = flags & (1 << 3) == 1 << 3;
-        if using_data_descriptor {
-            return Err(ZipError::UnsupportedArchive(
-                "The file length is not available in the local header",
-            ));
-        }
 
         /* flags & (1 << 1) != 0 */
         let is_utf8: bool = flags & (1 << 11) != 0;
@@ -748,6 +784,7 @@ impl ZipFileData {
             // header_start and data start are not available, but also don't matter, since seeking is
             // not available.
             header_start: 0,
+            disk_number: 0,
             data_start: OnceLock::new(),
             central_header_start: 0,
             // The external_attributes field is only available in the central directory.
@@ -777,8 +814,13 @@ impl ZipFileData {
             0
         };
         let encrypted_bit: u16 = if self.encrypted { 1u16 << 0 } else { 0 };
+        let data_descriptor_bit: u16 = if self.using_data_descriptor {
+            1u16 << 3
+        } else {
+            0
+        };
 
-        utf8_bit | encrypted_bit
+        utf8_bit | encrypted_bit | data_descriptor_bit
     }
 
     fn clamp_size_field(&self, field: u64) -> u32 {
@@ -851,8 +893,11 @@ impl ZipFileData {
                 .ok_or(ZipError::InvalidArchive(
                     "Extra field length in central directory exceeds 64KiB",
                 ))?,
-            file_comment_length: self.file_comment.as_bytes().len().try_into().unwrap(),
-            disk_number: 0,
+            file_comment_length: self.file_comment.len().try_into().unwrap(),
+            disk_number: self
+                .disk_number
+                .try_into()
+                .map_err(|_| ZipError::InvalidArchive("Too many volumes"))?,
             internal_file_attributes: 0,
             external_file_attributes: self.external_attributes,
             offset: self
@@ -863,21 +908,51 @@ impl ZipFileData {
         })
     }
 
+    /// Write the data descriptor that follows the contents of an entry written with bit 3 of the
+    /// general purpose flags set. ZIP64 entries record 8-byte sizes, per APPNOTE 4.3.9.2.
+    pub(crate) fn write_data_descriptor<W: std::io::Write>(&self, writer: &mut W) -> ZipResult<()> {
+        debug_assert!(self.using_data_descriptor);
+        if self.large_file {
+            return Zip64DataDescriptorBlock {
+                magic: Zip64DataDescriptorBlock::MAGIC,
+                crc32: self.crc32,
+                compressed_size: self.compressed_size,
+                uncompressed_size: self.uncompressed_size,
+            }
+            .write(writer);
+        }
+        if self.compressed_size > spec::ZIP64_BYTES_THR
+            || self.uncompressed_size > spec::ZIP64_BYTES_THR
+        {
+            return Err(ZipError::Io(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "Large file option has not been set",
+            )));
+        }
+        ZipDataDescriptorBlock {
+            magic: ZipDataDescriptorBlock::MAGIC,
+            crc32: self.crc32,
+            compressed_size: self.compressed_size as u32,
+            uncompressed_size: self.uncompressed_size as u32,
+        }
+        .write(writer)
+    }
+
     pub(crate) fn zip64_extra_field_block(&self) -> Option<Zip64ExtraFieldBlock> {
         let uncompressed_size: Option<u64> =
             if self.uncompressed_size >= spec::ZIP64_BYTES_THR || self.large_file {
-                Some(spec::ZIP64_BYTES_THR)
+                Some(self.uncompressed_size)
             } else {
                 None
             };
         let compressed_size: Option<u64> =
             if self.compressed_size >= spec::ZIP64_BYTES_THR || self.large_file {
-                Some(spec::ZIP64_BYTES_THR)
+                Some(self.compressed_size)
             } else {
                 None
             };
         let header_start: Option<u64> = if self.header_start >= spec::ZIP64_BYTES_THR {
-            Some(spec::ZIP64_BYTES_THR)
+            Some(self.header_start)
         } else {
             None
         };
@@ -1001,6 +1076,60 @@ impl FixedSizeBlock for ZipLocalEntryBlock {
     ];
 }
 
+#[derive(Copy, Clone, Debug)]
+#[repr(packed)]
+pub(crate) struct ZipDataDescriptorBlock {
+    magic: spec::Magic,
+    pub crc32: u32,
+    pub compressed_size: u32,
+    pub uncompressed_size: u32,
+}
+
+impl FixedSizeBlock for ZipDataDescriptorBlock {
+    const MAGIC: spec::Magic = spec::Magic::DATA_DESCRIPTOR_SIGNATURE;
+
+    #[inline(always)]
+    fn magic(self) -> spec::Magic {
+        self.magic
+    }
+
+    const WRONG_MAGIC_ERROR: ZipError = ZipError::InvalidArchive("Invalid data descriptor");
+
+    to_and_from_le![
+        (magic, spec::Magic),
+        (crc32, u32),
+        (compressed_size, u32),
+        (uncompressed_size, u32),
+    ];
+}
+
+#[derive(Copy, Clone, Debug)]
+#[repr(packed)]
+pub(crate) struct Zip64DataDescriptorBlock {
+    magic: spec::Magic,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+}
+
+impl FixedSizeBlock for Zip64DataDescriptorBlock {
+    const MAGIC: spec::Magic = spec::Magic::DATA_DESCRIPTOR_SIGNATURE;
+
+    #[inline(always)]
+    fn magic(self) -> spec::Magic {
+        self.magic
+    }
+
+    const WRONG_MAGIC_ERROR: ZipError = ZipError::InvalidArchive("Invalid data descriptor");
+
+    to_and_from_le![
+        (magic, spec::Magic),
+        (crc32, u32),
+        (compressed_size, u64),
+        (uncompressed_size, u64),
+    ];
+}
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct Zip64ExtraFieldBlock {
     magic: spec::ExtraFieldMagic,
@@ -1126,6 +1255,7 @@ mod test {
             central_extra_field: None,
             file_comment: String::with_capacity(0).into_boxed_str(),
             header_start: 0,
+            disk_number: 0,
             extra_data_start: None,
             data_start: OnceLock::new(),
             central_header_start: 0,
@@ -1147,6 +1277,23 @@ mod test {
         assert_eq!(dt.datepart(), 0b0000000_0001_00001);
     }
 
+    #[test]
+    fn datetime_to_system_time() {
+        use super::DateTime;
+        use std::time::{Duration, SystemTime};
+        for ((year, month, day, hour, minute, second), seconds) in [
+            ((1980, 1, 1, 0, 0, 0), 315532800),
+            ((2024, 2, 29, 12, 34, 56), 1709210096),
+            ((2107, 12, 31, 23, 59, 58), 4354819198),
+        ] {
+            let dt = DateTime::from_date_and_time(year, month, day, hour, minute, second).unwrap();
+            assert_eq!(
+                dt.to_system_time(),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+            );
+        }
+    }
+
     #[test]
     #[allow(clippy::unusual_byte_groupings)]
     fn datetime_max() {
@@ -4,7 +4,7 @@
 use crate::aes::AesWriter;
 use crate::compression::CompressionMethod;
 use crate::read::{
-    find_content, parse_single_extra_field, Config, ZipArchive, ZipFile, ZipFileReader,
+    find_content, parse_single_extra_field, Config, Limits, ZipArchive, ZipFile, ZipFileReader,
 };
 use crate::result::{ZipError, ZipResult};
 use crate::spec::{self, FixedSizeBlock, Zip32CDEBlock};
@@ -47,6 +47,20 @@ use std::path::Path;
 #[cfg(feature = "zstd")]
 use zstd::stream::write::Encoder as ZstdEncoder;
 
+#[cfg(feature = "tokio")]
+mod async_writer;
+#[cfg(feature = "parallelism")]
+mod parallel;
+#[cfg(all(feature = "deflate-flate2", feature = "parallelism"))]
+mod parallel_deflate;
+mod volumes;
+#[cfg(feature = "tokio")]
+pub use async_writer::{AsyncStreamWriter, AsyncZipWriter};
+#[cfg(all(feature = "deflate-flate2", feature = "parallelism"))]
+use parallel_deflate::ParallelDeflateEncoder;
+use volumes::{locate, Volumes};
+pub use volumes::{SplitWriter, MIN_VOLUME_SIZE};
+
 enum MaybeEncrypted<W> {
     Unencrypted(W),
     #[cfg(feature = "aes-crypto")]
@@ -90,6 +104,8 @@ enum GenericZipWriter<W: Write + Seek> {
     Storer(MaybeEncrypted<W>),
     #[cfg(feature = "deflate-flate2")]
     Deflater(DeflateEncoder<MaybeEncrypted<W>>),
+    #[cfg(all(feature = "deflate-flate2", feature = "parallelism"))]
+    ParallelDeflater(ParallelDeflateEncoder<MaybeEncrypted<W>>),
     #[cfg(feature = "deflate-zopfli")]
     ZopfliDeflater(zopfli::DeflateEncoder<MaybeEncrypted<W>>),
     #[cfg(feature = "deflate-zopfli")]
@@ -109,6 +125,10 @@ impl<W: Write + Seek> Debug for GenericZipWriter<W> {
             GenericZipWriter::Deflater(w) => {
                 f.write_fmt(format_args!("Deflater({:?})", w.get_ref()))
             }
+            #[cfg(all(feature = "deflate-flate2", feature = "parallelism"))]
+            GenericZipWriter::ParallelDeflater(w) => {
+                f.write_fmt(format_args!("ParallelDeflater({:?})", w.get_ref()))
+            }
             #[cfg(feature = "deflate-zopfli")]
             GenericZipWriter::ZopfliDeflater(_) => f.write_str("ZopfliDeflater"),
             #[cfg(feature = "deflate-zopfli")]
@@ -160,14 +180,17 @@ pub(crate) mod zip_writer {
         pub(super) writing_raw: bool,
         pub(super) comment: Box<[u8]>,
         pub(super) flush_on_finish_file: bool,
+        pub(super) seek_possible: bool,
+        /// Set when writing a split archive, to place headers on its volumes.
+        pub(super) volumes: Option<fn(&mut W) -> &mut dyn Volumes>,
     }
 
     impl<W: Write + Seek> Debug for ZipWriter<W> {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             f.write_fmt(format_args!(
-                "ZipWriter {{files: {:?}, stats: {:?}, writing_to_file: {}, writing_raw: {}, comment: {:?}, flush_on_finish_file: {}}}",
+                "ZipWriter {{files: {:?}, stats: {:?}, writing_to_file: {}, writing_raw: {}, comment: {:?}, flush_on_finish_file: {}, seek_possible: {}, split: {}}}",
                 self.files, self.stats, self.writing_to_file, self.writing_raw,
-                self.comment, self.flush_on_finish_file))
+                self.comment, self.flush_on_finish_file, self.seek_possible, self.volumes.is_some()))
         }
     }
 }
@@ -266,6 +289,8 @@ pub struct FileOptions<'k, T: FileOptionExtension> {
     pub(crate) alignment: u16,
     #[cfg(feature = "deflate-zopfli")]
     pub(super) zopfli_buffer_size: Option<usize>,
+    #[cfg(feature = "parallelism")]
+    pub(super) compression_threads: usize,
 }
 /// Simple File Options. Can be copied and good for simple writing zip files
 pub type SimpleFileOptions = FileOptions<'static, ()>;
@@ -347,10 +372,7 @@ impl ExtendedFileOptions {
             {
                 use crate::unstable::LittleEndianReadExt;
                 let header_id = data.read_u16_le()?;
-                if EXTRA_FIELD_MAPPING
-                    .iter()
-                    .any(|&mapped| mapped == header_id)
-                {
+                if EXTRA_FIELD_MAPPING.contains(&header_id) {
                     return Err(ZipError::Io(io::Error::new(
                         io::ErrorKind::Other,
                         format!(
@@ -507,6 +529,19 @@ impl<'k, T: FileOptionExtension> FileOptions<'k, T> {
         self
     }
 
+    /// Sets how many threads compress this file's data, for Deflate and Zstandard. The result is
+    /// still one stream that any reader can decompress, and it doesn't depend on the number of
+    /// threads as long as it's more than 1. Deflate data is cut into 128 KiB blocks that are
+    /// compressed separately, which makes it slightly larger; Zstandard hands out jobs to its own
+    /// worker threads. Other compression methods, and Deflate levels above 9 that use Zopfli,
+    /// ignore this. The default of 1 compresses on the calling thread.
+    #[must_use]
+    #[cfg(feature = "parallelism")]
+    pub const fn compression_threads(mut self, threads: usize) -> Self {
+        self.compression_threads = threads;
+        self
+    }
+
     /// Returns the compression level currently set.
     pub const fn get_compression_level(&self) -> Option<i64> {
         self.compression_level
@@ -533,10 +568,10 @@ impl<'k> FileOptions<'k, ExtendedFileOptions> {
     /// Removes the extra data fields.
     #[must_use]
     pub fn clear_extra_data(mut self) -> Self {
-        if self.extended_options.extra_data.len() > 0 {
+        if !self.extended_options.extra_data.is_empty() {
             self.extended_options.extra_data = Arc::new(vec![]);
         }
-        if self.extended_options.central_extra_data.len() > 0 {
+        if !self.extended_options.central_extra_data.is_empty() {
             self.extended_options.central_extra_data = Arc::new(vec![]);
         }
         self
@@ -556,6 +591,8 @@ impl<'k, T: FileOptionExtension> Default for FileOptions<'k, T> {
             alignment: 1,
             #[cfg(feature = "deflate-zopfli")]
             zopfli_buffer_size: Some(1 << 15),
+            #[cfg(feature = "parallelism")]
+            compression_threads: 1,
         }
     }
 }
@@ -613,6 +650,136 @@ impl ZipWriterStats {
     }
 }
 
+/// Adapter for writing a ZIP archive to an output that can't seek, such as a pipe or a socket.
+///
+/// This implements [`Seek`] only to report how many bytes have been written so far; any other
+/// seek fails. See [`ZipWriter::new_stream`].
+#[derive(Debug)]
+pub struct StreamWriter<W: Write> {
+    inner: W,
+    bytes_written: u64,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Wraps `inner`, counting bytes from the current position as offset 0.
+    pub const fn new(inner: W) -> StreamWriter<W> {
+        StreamWriter {
+            inner,
+            bytes_written: 0,
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub const fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Consumes this wrapper, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for StreamWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.inner.write(buf)?;
+        self.bytes_written += count as u64;
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Seek for StreamWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match pos {
+            SeekFrom::Current(0) => Ok(self.bytes_written),
+            SeekFrom::Start(offset) if offset == self.bytes_written => Ok(offset),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "StreamWriter can't seek; it only reports its current position",
+            )),
+        }
+    }
+}
+
+impl<W: Write> ZipWriter<StreamWriter<W>> {
+    /// Initializes an archive that is written to an output that can't seek.
+    ///
+    /// Each file's local header is written with bit 3 of the general purpose flags set and with
+    /// zeros in place of the CRC and sizes, which are recorded in a data descriptor after the file's
+    /// contents instead. A file whose options set [`FileOptions::large_file`] gets a ZIP64 data
+    /// descriptor; writing more than 4 GiB to any other file fails, as with [`ZipWriter::new`].
+    ///
+    /// AES-encrypted files always use AE-2, since the AE-1 vendor version can't be chosen
+    /// retroactively, and ZipCrypto-encrypted files verify the password against the modification
+    /// time rather than the CRC, as Info-ZIP does. [`ZipWriter::abort_file`] only removes the file
+    /// from the central directory, because the bytes already written can't be taken back.
+    ///
+    /// ```
+    /// # fn main() -> zip::result::ZipResult<()> {
+    /// use std::io::Write;
+    /// use zip::write::SimpleFileOptions;
+    ///
+    /// // A `Vec<u8>` doesn't implement `Seek`.
+    /// let mut zip = zip::ZipWriter::new_stream(Vec::new());
+    /// zip.start_file("hello.txt", SimpleFileOptions::default())?;
+    /// zip.write_all(b"Hello, World!")?;
+    /// let bytes = zip.finish()?.into_inner();
+    ///
+    /// let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+    /// assert_eq!(archive.by_name("hello.txt")?.size(), 13);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_stream(inner: W) -> ZipWriter<StreamWriter<W>> {
+        let mut zip = ZipWriter::new(StreamWriter::new(inner));
+        zip.seek_possible = false;
+        zip
+    }
+}
+
+impl<V: Write + Seek, F: FnMut(u32) -> io::Result<V>> ZipWriter<SplitWriter<V, F>> {
+    /// Initializes an archive that is split across volumes of at most `max_volume_size` bytes,
+    /// which must be at least [`MIN_VOLUME_SIZE`].
+    ///
+    /// `make_volume` is called with the number of each volume when it's needed, starting from 0.
+    /// By convention, volume `n` is named `archive.z{n+1:02}`, except that the last one, which holds
+    /// the central directory, is named `archive.zip`; since the number of volumes is only known
+    /// once [`ZipWriter::finish`] returns, the last one is usually renamed then. Local headers and
+    /// central directory records are never split across volumes, but file contents are.
+    ///
+    /// The first volume starts with the spanning signature (APPNOTE 8.5.3), which is replaced with
+    /// the single-segment marker if the whole archive fits in it. [`ZipWriter::abort_file`] leaves
+    /// the aborted file's bytes in place, since volumes are never truncated.
+    ///
+    /// ```
+    /// # fn main() -> zip::result::ZipResult<()> {
+    /// use std::io::{Cursor, Write};
+    /// use zip::write::{SimpleFileOptions, MIN_VOLUME_SIZE};
+    ///
+    /// // Each volume would normally be a `File`.
+    /// let mut zip = zip::ZipWriter::new_split(MIN_VOLUME_SIZE, |_| Ok(Cursor::new(Vec::new())))?;
+    /// let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    /// zip.start_file("big.bin", options)?;
+    /// zip.write_all(&[0; 100_000])?;
+    /// let volumes = zip.finish()?.into_volumes();
+    /// assert_eq!(volumes.len(), 2);
+    ///
+    /// let mut archive = zip::ZipArchive::open_split(volumes)?;
+    /// assert_eq!(archive.by_name("big.bin")?.size(), 100_000);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_split(max_volume_size: u64, make_volume: F) -> ZipResult<Self> {
+        let mut zip = ZipWriter::new(SplitWriter::new(max_volume_size, make_volume)?);
+        zip.volumes = Some(|writer| writer);
+        Ok(zip)
+    }
+}
+
 impl<A: Read + Write + Seek> ZipWriter<A> {
     /// Initializes the archive from an existing ZIP archive, making it ready for append.
     ///
@@ -626,7 +793,9 @@ impl<A: Read + Write + Seek> ZipWriter<A> {
     /// This uses the given read configuration to initially read the archive.
     pub fn new_append_with_config(config: Config, mut readwriter: A) -> ZipResult<ZipWriter<A>> {
         readwriter.seek(SeekFrom::Start(0))?;
-        if let Ok((footer, shared)) = ZipArchive::get_metadata(config, &mut readwriter) {
+        if let Ok((footer, shared)) =
+            ZipArchive::get_metadata(config, Limits::default(), &mut readwriter, &[])
+        {
             Ok(ZipWriter {
                 inner: Storer(MaybeEncrypted::Unencrypted(readwriter)),
                 files: shared.files,
@@ -635,6 +804,8 @@ impl<A: Read + Write + Seek> ZipWriter<A> {
                 comment: footer.zip_file_comment,
                 writing_raw: true, // avoid recomputing the last file's header
                 flush_on_finish_file: false,
+                seek_possible: true,
+                volumes: None,
             })
         } else {
             Err(InvalidArchive("No central-directory end header found"))
@@ -710,6 +881,8 @@ impl<A: Read + Write + Seek> ZipWriter<A> {
                 alignment: 1,
                 #[cfg(feature = "deflate-zopfli")]
                 zopfli_buffer_size: None,
+                #[cfg(feature = "parallelism")]
+                compression_threads: 1,
             };
             if let Some(perms) = src_data.unix_mode() {
                 options = options.unix_permissions(perms);
@@ -730,6 +903,8 @@ impl<A: Read + Write + Seek> ZipWriter<A> {
                 alignment: 1,
                 #[cfg(feature = "deflate-zopfli")]
                 zopfli_buffer_size: None,
+                #[cfg(feature = "parallelism")]
+                compression_threads: 1,
             };
             if let Some(perms) = src_data.unix_mode() {
                 options = options.unix_permissions(perms);
@@ -809,6 +984,8 @@ impl<W: Write + Seek> ZipWriter<W> {
             writing_raw: false,
             comment: Box::new([]),
             flush_on_finish_file: false,
+            seek_possible: true,
+            volumes: None,
         }
     }
 
@@ -869,6 +1046,8 @@ impl<W: Write + Seek> ZipWriter<W> {
     {
         self.finish_file()?;
 
+        // Without seeking, the CRC and sizes can only be written after the data
+        let using_data_descriptor = !self.seek_possible && raw_values.is_none();
         let raw_values = raw_values.unwrap_or(ZipRawValues {
             crc32: 0,
             compressed_size: 0,
@@ -886,8 +1065,11 @@ impl<W: Write + Seek> ZipWriter<W> {
         let mut aes_extra_data_start = 0;
         #[cfg(feature = "aes-crypto")]
         if let Some(EncryptWith::Aes { mode, .. }) = options.encrypt_with {
+            // The compression method is final here; the vendor version is too when streaming.
+            let [method_lo, method_hi] =
+                options.compression_method.serialize_to_u16().to_le_bytes();
             let aes_dummy_extra_data =
-                vec![0x02, 0x00, 0x41, 0x45, mode as u8, 0x00, 0x00].into_boxed_slice();
+                vec![0x02, 0x00, 0x41, 0x45, mode as u8, method_lo, method_hi].into_boxed_slice();
             aes_extra_data_start = extra_data.len() as u64;
             ExtendedFileOptions::add_extra_data_unchecked(
                 &mut extra_data,
@@ -895,6 +1077,17 @@ impl<W: Write + Seek> ZipWriter<W> {
                 aes_dummy_extra_data,
             )?;
         }
+        let name: Box<str> = name.into();
+        if let Some(volumes) = self.volumes {
+            // A local header can't be split across volumes. Its ZIP64 extra field takes at most
+            // 28 bytes, and alignment padding fewer than `alignment + 6`.
+            let mut header_len =
+                size_of::<ZipLocalEntryBlock>() + name.len() + 28 + extra_data.len();
+            if options.alignment > 1 {
+                header_len += options.alignment as usize + 6;
+            }
+            volumes(self.inner.get_plain()).keep_together(header_len as u64)?;
+        }
         {
             let header_start = self.inner.get_plain().stream_position()?;
 
@@ -918,6 +1111,7 @@ impl<W: Write + Seek> ZipWriter<W> {
                 &extra_data,
             );
             file.version_made_by = file.version_made_by.max(file.version_needed() as u8);
+            file.using_data_descriptor = using_data_descriptor;
             let block = file.local_block();
             let index = self.insert_file_data(file)?;
             let writer = self.inner.get_plain();
@@ -998,11 +1192,16 @@ impl<W: Write + Seek> ZipWriter<W> {
                         writer: mem::replace(&mut self.inner, Closed).unwrap(),
                         buffer: vec![],
                         keys,
+                        header_written: false,
                     };
                     let crypto_header = [0u8; 12];
 
                     zipwriter.write_all(&crypto_header)?;
                     self.stats.start = zipwriter.writer.stream_position()?;
+                    if file.using_data_descriptor {
+                        let timepart = options.last_modified_time.timepart();
+                        zipwriter.write_header((timepart >> 8) as u8)?;
+                    }
                     self.inner = Storer(MaybeEncrypted::ZipCrypto(zipwriter));
                 }
                 None => {}
@@ -1036,6 +1235,8 @@ impl<W: Write + Seek> ZipWriter<W> {
             None,
             #[cfg(feature = "deflate-zopfli")]
             None,
+            #[cfg(feature = "parallelism")]
+            1,
         )?;
         self.inner.switch_to(make_plain_writer)?;
         self.switch_to_non_encrypting_writer()?;
@@ -1058,7 +1259,9 @@ impl<W: Write + Seek> ZipWriter<W> {
                 // unencrypted contents.
                 //
                 // C.f. https://www.winzip.com/en/support/aes-encryption/#crc-faq
-                aes_mode.1 = if self.stats.bytes_written < 20 {
+                //
+                // A streamed file's local header can't be revised, so it keeps AE-2.
+                aes_mode.1 = if self.stats.bytes_written < 20 || file.using_data_descriptor {
                     crc = false;
                     AesVendorVersion::Ae2
                 } else {
@@ -1070,16 +1273,20 @@ impl<W: Write + Seek> ZipWriter<W> {
             } else {
                 0
             };
-            update_aes_extra_data(writer, file)?;
-            update_local_file_header(writer, file)?;
-            writer.seek(SeekFrom::Start(file_end))?;
+            if file.using_data_descriptor {
+                file.write_data_descriptor(writer)?;
+            } else {
+                update_aes_extra_data(writer, file)?;
+                update_local_file_header(writer, file)?;
+                writer.seek(SeekFrom::Start(file_end))?;
+            }
         }
+        // The file is complete, so a failure to flush it aborts it as a whole.
+        self.writing_to_file = false;
         if self.flush_on_finish_file {
             let result = writer.flush();
             self.ok_or_abort_file(result)?;
         }
-
-        self.writing_to_file = false;
         Ok(())
     }
 
@@ -1103,13 +1310,28 @@ impl<W: Write + Seek> ZipWriter<W> {
 
     /// Removes the file currently being written from the archive if there is one, or else removes
     /// the file most recently written.
+    ///
+    /// Without seeking, as with [`ZipWriter::new_stream`], what's been written of the file can't
+    /// be taken back. It's finished with its data descriptor as usual, so that the output can
+    /// still be read from start to end, and is only left out of the central directory. If it can't
+    /// be finished, such as a copied file that was only partly written, it's still removed but
+    /// this fails, since the output can no longer be read that way.
     pub fn abort_file(&mut self) -> ZipResult<()> {
+        let finished = match (self.seek_possible, self.writing_to_file, self.writing_raw) {
+            (false, true, true) => Err(ZipError::UnsupportedArchive(
+                "A partly copied file can't be aborted without seeking",
+            )),
+            (false, true, false) => self.finish_file(),
+            _ => Ok(()),
+        };
         let (_, last_file) = self.files.pop().ok_or(ZipError::FileNotFound)?;
         let make_plain_writer = self.inner.prepare_next_writer(
             Stored,
             None,
             #[cfg(feature = "deflate-zopfli")]
             None,
+            #[cfg(feature = "parallelism")]
+            1,
         )?;
         self.inner.switch_to(make_plain_writer)?;
         self.switch_to_non_encrypting_writer()?;
@@ -1123,13 +1345,14 @@ impl<W: Write + Seek> ZipWriter<W> {
                     .is_some_and(|start| start < last_file_start)
             }),
         };
-        if rewind_safe {
+        // The volumes of a split archive can't be truncated, so the bytes are left in place
+        if rewind_safe && self.seek_possible && self.volumes.is_none() {
             self.inner
                 .get_plain()
                 .seek(SeekFrom::Start(last_file.header_start))?;
         }
         self.writing_to_file = false;
-        Ok(())
+        finished
     }
 
     /// Create a file in the archive and start writing its' contents. The file must not have the
@@ -1151,6 +1374,8 @@ impl<W: Write + Seek> ZipWriter<W> {
             options.compression_level,
             #[cfg(feature = "deflate-zopfli")]
             options.zopfli_buffer_size,
+            #[cfg(feature = "parallelism")]
+            options.compression_threads,
         )?;
         self.start_entry(name, options, None)?;
         let result = self.inner.switch_to(make_new_self);
@@ -1247,7 +1472,7 @@ impl<W: Write + Seek> ZipWriter<W> {
     /// Add a new file using the already compressed data from a ZIP file being read and renames it, this
     /// allows faster copies of the `ZipFile` since there is no need to decompress and compress it again.
     /// Any `ZipFile` metadata is copied and not checked, for example the file CRC.
-
+    ///
     /// ```no_run
     /// use std::fs::File;
     /// use std::io::{Read, Seek, Write};
@@ -1274,6 +1499,19 @@ impl<W: Write + Seek> ZipWriter<W> {
     where
         S: Into<Box<str>> + ToOwned<Owned = SToOwned>,
         SToOwned: Into<Box<str>>,
+    {
+        self.start_raw_entry(&file, name)?;
+        io::copy(file.get_raw_reader(), self)?;
+
+        Ok(())
+    }
+
+    /// Starts a file that the compressed data of `file` is then copied into as-is.
+    fn start_raw_entry<R, S, SToOwned>(&mut self, file: &ZipFile<R>, name: S) -> ZipResult<()>
+    where
+        R: Read + ?Sized,
+        S: Into<Box<str>> + ToOwned<Owned = SToOwned>,
+        SToOwned: Into<Box<str>>,
     {
         let mut options = SimpleFileOptions::default()
             .large_file(file.compressed_size().max(file.size()) > spec::ZIP64_BYTES_THR)
@@ -1296,9 +1534,6 @@ impl<W: Write + Seek> ZipWriter<W> {
         self.start_entry(name, options, Some(raw_values))?;
         self.writing_to_file = true;
         self.writing_raw = true;
-
-        io::copy(file.get_raw_reader(), self)?;
-
         Ok(())
     }
 
@@ -1368,7 +1603,13 @@ impl<W: Write + Seek> ZipWriter<W> {
             _ => name_as_string + "/",
         };
 
-        self.start_entry(name_with_slash, options, None)?;
+        // A directory has no contents, so its (zero) CRC and sizes are already known.
+        let raw_values = ZipRawValues {
+            crc32: 0,
+            compressed_size: 0,
+            uncompressed_size: 0,
+        };
+        self.start_entry(name_with_slash, options, Some(raw_values))?;
         self.writing_to_file = false;
         self.switch_to_non_encrypting_writer()?;
         Ok(())
@@ -1456,6 +1697,10 @@ impl<W: Write + Seek> ZipWriter<W> {
         self.finish_file()?;
 
         let mut central_start = self.write_central_and_footer()?;
+        if !self.seek_possible {
+            // Nothing can have been written past the footer, since we never rewind.
+            return Ok(central_start);
+        }
         let writer = self.inner.get_plain();
         let footer_end = writer.stream_position()?;
         let file_end = writer.seek(SeekFrom::End(0))?;
@@ -1480,6 +1725,9 @@ impl<W: Write + Seek> ZipWriter<W> {
     }
 
     fn write_central_and_footer(&mut self) -> Result<u64, ZipError> {
+        if let Some(volumes) = self.volumes {
+            return self.write_split_central_and_footer(volumes);
+        }
         let writer = self.inner.get_plain();
 
         let mut version_needed = MIN_VERSION as u16;
@@ -1530,6 +1778,101 @@ impl<W: Write + Seek> ZipWriter<W> {
         Ok(central_start)
     }
 
+    /// Like [`ZipWriter::write_central_and_footer`], but records which volume each local header is
+    /// on and keeps each central directory record within one volume, as required by APPNOTE 8.5.
+    fn write_split_central_and_footer(
+        &mut self,
+        volumes: fn(&mut W) -> &mut dyn Volumes,
+    ) -> ZipResult<u64> {
+        // Volumes holding local headers are complete by now.
+        let header_volume_starts = volumes(self.inner.get_plain()).volume_starts().to_vec();
+        let mut version_needed = MIN_VERSION as u16;
+        let mut central_start = None;
+        let mut files_on_last_disk = 0;
+        let mut last_disk = header_volume_starts.len() - 1;
+        let mut header = Vec::new();
+        for file in self.files.values() {
+            let mut file = file.clone();
+            let (disk_number, header_start) = locate(&header_volume_starts, file.header_start);
+            file.disk_number = disk_number;
+            file.header_start = header_start;
+            header.clear();
+            write_central_directory_header(&mut header, &file)?;
+            let writer = volumes(self.inner.get_plain());
+            writer.keep_together(header.len() as u64)?;
+            let disk = writer.volume_starts().len() - 1;
+            if disk != last_disk {
+                last_disk = disk;
+                files_on_last_disk = 0;
+            }
+            files_on_last_disk += 1;
+            let writer = self.inner.get_plain();
+            central_start.get_or_insert(writer.stream_position()?);
+            writer.write_all(&header)?;
+            version_needed = version_needed.max(file.version_needed());
+        }
+
+        let footer_len = size_of::<spec::Zip64CDEBlock>()
+            + size_of::<spec::Zip64CDELocatorBlock>()
+            + size_of::<Zip32CDEBlock>()
+            + self.comment.len();
+        let writer = volumes(self.inner.get_plain());
+        writer.keep_together(footer_len as u64)?;
+        let volume_starts = writer.volume_starts().to_vec();
+        if volume_starts.len() - 1 != last_disk {
+            last_disk = volume_starts.len() - 1;
+            files_on_last_disk = 0;
+        }
+        let writer = self.inner.get_plain();
+        let central_end = writer.stream_position()?;
+        let central_start = central_start.unwrap_or(central_end);
+        let central_size = central_end - central_start;
+        let (disk_with_central_directory, central_offset) = locate(&volume_starts, central_start);
+        let (_, footer_offset) = locate(&volume_starts, central_end);
+
+        if volume_starts.len() > 1
+            || self.files.len() > spec::ZIP64_ENTRY_THR
+            || central_size.max(central_offset) > spec::ZIP64_BYTES_THR
+        {
+            let zip64_footer = spec::Zip64CentralDirectoryEnd {
+                version_made_by: version_needed,
+                version_needed_to_extract: version_needed,
+                disk_number: last_disk as u32,
+                disk_with_central_directory,
+                number_of_files_on_this_disk: files_on_last_disk,
+                number_of_files: self.files.len() as u64,
+                central_directory_size: central_size,
+                central_directory_offset: central_offset,
+            };
+
+            zip64_footer.write(writer)?;
+
+            let zip64_footer = spec::Zip64CentralDirectoryEndLocator {
+                disk_with_central_directory: last_disk as u32,
+                end_of_central_directory_offset: footer_offset,
+                number_of_disks: volume_starts.len() as u32,
+            };
+
+            zip64_footer.write(writer)?;
+        }
+
+        let footer = spec::Zip32CentralDirectoryEnd {
+            // There are fewer than 0xFFFF volumes, so these fit.
+            disk_number: last_disk as u16,
+            disk_with_central_directory: disk_with_central_directory as u16,
+            zip_file_comment: self.comment.clone(),
+            number_of_files_on_this_disk: files_on_last_disk.min(spec::ZIP64_ENTRY_THR as u64)
+                as u16,
+            number_of_files: self.files.len().min(spec::ZIP64_ENTRY_THR) as u16,
+            central_directory_size: central_size.min(spec::ZIP64_BYTES_THR) as u32,
+            central_directory_offset: central_offset.min(spec::ZIP64_BYTES_THR) as u32,
+        };
+
+        footer.write(writer)?;
+        volumes(self.inner.get_plain()).mark_single_segment()?;
+        Ok(central_start)
+    }
+
     fn index_by_name(&self, name: &str) -> ZipResult<usize> {
         self.files.get_index_of(name).ok_or(ZipError::FileNotFound)
     }
@@ -1584,6 +1927,10 @@ impl<W: Write + Seek> GenericZipWriter<W> {
         compression: CompressionMethod,
         compression_level: Option<i64>,
         #[cfg(feature = "deflate-zopfli")] zopfli_buffer_size: Option<usize>,
+        // Only Deflate and Zstd compress on more than one thread
+        #[cfg(feature = "parallelism")]
+        #[allow(unused_variables)]
+        compression_threads: usize,
     ) -> ZipResult<SwitchWriterFunction<W>> {
         if let Closed = self {
             return Err(
@@ -1654,6 +2001,16 @@ impl<W: Write + Seek> GenericZipWriter<W> {
 
                     #[cfg(feature = "deflate-flate2")]
                     {
+                        #[cfg(feature = "parallelism")]
+                        if compression_threads > 1 {
+                            return Ok(Box::new(move |bare| {
+                                GenericZipWriter::ParallelDeflater(ParallelDeflateEncoder::new(
+                                    bare,
+                                    Compression::new(level),
+                                    compression_threads,
+                                ))
+                            }));
+                        }
                         Ok(Box::new(move |bare| {
                             GenericZipWriter::Deflater(DeflateEncoder::new(
                                 bare,
@@ -1695,7 +2052,16 @@ impl<W: Write + Seek> GenericZipWriter<W> {
                         "Unsupported compression level",
                     ))?;
                     Ok(Box::new(move |bare| {
-                        GenericZipWriter::Zstd(ZstdEncoder::new(bare, level as i32).unwrap())
+                        #[allow(unused_mut)]
+                        let mut encoder = ZstdEncoder::new(bare, level as i32).unwrap();
+                        // zstd clamps the number of workers to what it supports
+                        #[cfg(feature = "parallelism")]
+                        if compression_threads > 1 {
+                            encoder
+                                .multithread(compression_threads.try_into().unwrap_or(u32::MAX))
+                                .unwrap();
+                        }
+                        GenericZipWriter::Zstd(encoder)
                     }))
                 }
                 #[cfg(feature = "lzma")]
@@ -1718,6 +2084,8 @@ impl<W: Write + Seek> GenericZipWriter<W> {
             Storer(w) => w,
             #[cfg(feature = "deflate-flate2")]
             GenericZipWriter::Deflater(w) => w.finish()?,
+            #[cfg(all(feature = "deflate-flate2", feature = "parallelism"))]
+            GenericZipWriter::ParallelDeflater(w) => w.finish()?,
             #[cfg(feature = "deflate-zopfli")]
             GenericZipWriter::ZopfliDeflater(w) => w.finish()?,
             #[cfg(feature = "deflate-zopfli")]
@@ -1746,6 +2114,8 @@ impl<W: Write + Seek> GenericZipWriter<W> {
             Storer(ref mut w) => Some(w as &mut dyn Write),
             #[cfg(feature = "deflate-flate2")]
             GenericZipWriter::Deflater(ref mut w) => Some(w as &mut dyn Write),
+            #[cfg(all(feature = "deflate-flate2", feature = "parallelism"))]
+            GenericZipWriter::ParallelDeflater(ref mut w) => Some(w as &mut dyn Write),
             #[cfg(feature = "deflate-zopfli")]
             GenericZipWriter::ZopfliDeflater(w) => Some(w as &mut dyn Write),
             #[cfg(feature = "deflate-zopfli")]
@@ -1981,6 +2351,194 @@ mod test {
     use std::marker::PhantomData;
     use std::path::PathBuf;
 
+    #[test]
+    fn write_stream_data_descriptor() -> ZipResult<()> {
+        let options = SimpleFileOptions::default().compression_method(Stored);
+        let mut writer = ZipWriter::new_stream(Vec::new());
+        writer.start_file("small", options)?;
+        writer.write_all(b"hello")?;
+        writer.start_file("large", options.large_file(true))?;
+        writer.write_all(b"world")?;
+        writer.add_directory("dir/", SimpleFileOptions::default())?;
+        let bytes = writer.finish()?.into_inner();
+
+        // Bit 3 is set, and the CRC and both sizes are zero in the local header.
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        assert_eq!(bytes[6] & (1 << 3), 1 << 3);
+        assert_eq!(&bytes[14..26], &[0; 12]);
+        let crc = crc32fast::hash(b"hello").to_le_bytes();
+        let data_end = 30 + "small".len() + 5;
+        let mut descriptor = b"PK\x07\x08".to_vec();
+        descriptor.extend_from_slice(&crc);
+        descriptor.extend_from_slice(&[5, 0, 0, 0, 5, 0, 0, 0]);
+        assert_eq!(&bytes[data_end..data_end + 16], &descriptor[..]);
+
+        // The ZIP64 descriptor uses 8-byte sizes.
+        let large_start = data_end + 16;
+        let large_data_end = large_start + 30 + "large".len() + 20 + 5;
+        let crc = crc32fast::hash(b"world").to_le_bytes();
+        let mut descriptor = b"PK\x07\x08".to_vec();
+        descriptor.extend_from_slice(&crc);
+        descriptor.extend_from_slice(&[5, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&bytes[large_data_end..large_data_end + 24], &descriptor[..]);
+
+        // Directories have no descriptor, since their sizes are known up front.
+        let dir_start = large_data_end + 24;
+        assert_eq!(bytes[dir_start + 6] & (1 << 3), 0);
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        for (name, contents) in [("small", "hello"), ("large", "world")] {
+            let mut file = archive.by_name(name)?;
+            assert_eq!(file.size(), 5);
+            let mut s = String::new();
+            file.read_to_string(&mut s)?;
+            assert_eq!(s, contents);
+        }
+        assert!(archive.by_name("dir/")?.is_dir());
+        Ok(())
+    }
+
+    #[test]
+    fn write_stream_encrypted() -> ZipResult<()> {
+        let mut writer = ZipWriter::new_stream(Vec::new());
+        let options = SimpleFileOptions::default().with_deprecated_encryption(b"password");
+        writer.start_file("zipcrypto", options)?;
+        writer.write_all(b"ZipCrypto contents")?;
+        #[cfg(feature = "aes-crypto")]
+        {
+            let options = SimpleFileOptions::default()
+                .with_aes_encryption(crate::AesMode::Aes256, "password");
+            writer.start_file("aes", options)?;
+            writer.write_all(b"AES contents that are longer than twenty bytes")?;
+        }
+        writer.add_symlink("link", "zipcrypto", SimpleFileOptions::default())?;
+        let bytes = writer.finish()?.into_inner();
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        let mut s = String::new();
+        archive
+            .by_name_decrypt("zipcrypto", b"password")?
+            .read_to_string(&mut s)?;
+        assert_eq!(s, "ZipCrypto contents");
+        assert!(archive.by_name_decrypt("zipcrypto", b"wrong").is_err());
+        #[cfg(feature = "aes-crypto")]
+        {
+            s.clear();
+            archive
+                .by_name_decrypt("aes", b"password")?
+                .read_to_string(&mut s)?;
+            assert_eq!(s, "AES contents that are longer than twenty bytes");
+        }
+        s.clear();
+        archive.by_name("link")?.read_to_string(&mut s)?;
+        assert_eq!(s, "zipcrypto");
+        Ok(())
+    }
+
+    #[test]
+    fn abort_stream_file() -> ZipResult<()> {
+        let mut writer = ZipWriter::new_stream(Vec::new());
+        writer.start_file("aborted", SimpleFileOptions::default())?;
+        writer.write_all(b"aborted contents")?;
+        writer.abort_file()?;
+        writer.start_file("kept", SimpleFileOptions::default())?;
+        writer.write_all(b"kept contents")?;
+        let bytes = writer.finish()?.into_inner();
+
+        // The aborted file is still whole, so the one after it can be read from the stream.
+        let mut reader = Cursor::new(&bytes[..]);
+        for (name, contents) in [("aborted", "aborted contents"), ("kept", "kept contents")] {
+            let mut file = crate::read::read_zipfile_from_stream(&mut reader)?.unwrap();
+            assert_eq!(file.name(), name);
+            let mut s = String::new();
+            file.read_to_string(&mut s)?;
+            assert_eq!(s, contents);
+        }
+        assert!(crate::read::read_zipfile_from_stream(&mut reader)?.is_none());
+
+        let archive = ZipArchive::new(Cursor::new(bytes))?;
+        assert_eq!(archive.file_names().collect::<Vec<_>>(), ["kept"]);
+        Ok(())
+    }
+
+    #[test]
+    fn write_split_round_trip() -> ZipResult<()> {
+        let max_volume_size = super::MIN_VOLUME_SIZE;
+        let mut writer = ZipWriter::new_split(max_volume_size, |_| Ok(Cursor::new(Vec::new())))?;
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        writer.start_file(
+            "big.bin",
+            SimpleFileOptions::default().compression_method(Stored),
+        )?;
+        writer.write_all(&big)?;
+        writer.start_file("deflated.txt", SimpleFileOptions::default())?;
+        writer.write_all(&b"Hello, volumes! ".repeat(1000))?;
+        // Enough entries for the central directory to span a volume boundary.
+        for i in 0..1500 {
+            writer.start_file(format!("small/{i:04}.txt"), SimpleFileOptions::default())?;
+            writer.write_all(i.to_string().as_bytes())?;
+        }
+        writer.set_comment("split");
+        let volumes: Vec<_> = writer
+            .finish()?
+            .into_volumes()
+            .into_iter()
+            .map(Cursor::into_inner)
+            .collect();
+
+        assert!(volumes.len() > 4);
+        assert_eq!(&volumes[0][..4], b"PK\x07\x08");
+        assert!(volumes
+            .iter()
+            .all(|volume| volume.len() as u64 <= max_volume_size));
+        // The ZIP64 locator records the number of disks.
+        let last = volumes.last().unwrap();
+        let locator = &last[last.len() - 22 - 5 - 20..last.len() - 22 - 5];
+        assert_eq!(&locator[..4], b"PK\x06\x07");
+        assert_eq!(
+            u32::from_le_bytes(locator[16..].try_into().unwrap()),
+            volumes.len() as u32
+        );
+
+        let mut archive = ZipArchive::open_split(volumes.into_iter().map(Cursor::new).collect())?;
+        assert_eq!(archive.len(), 1502);
+        assert_eq!(archive.comment(), b"split");
+        let mut contents = Vec::new();
+        archive.by_name("big.bin")?.read_to_end(&mut contents)?;
+        assert_eq!(contents, big);
+        contents.clear();
+        archive
+            .by_name("deflated.txt")?
+            .read_to_end(&mut contents)?;
+        assert_eq!(contents, b"Hello, volumes! ".repeat(1000));
+        for i in [0, 749, 1499] {
+            let mut s = String::new();
+            archive
+                .by_name(&format!("small/{i:04}.txt"))?
+                .read_to_string(&mut s)?;
+            assert_eq!(s, i.to_string());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn write_split_single_segment() -> ZipResult<()> {
+        let mut writer =
+            ZipWriter::new_split(super::MIN_VOLUME_SIZE, |_| Ok(Cursor::new(Vec::new())))?;
+        writer.start_file("hello.txt", SimpleFileOptions::default())?;
+        writer.write_all(b"Hello, World!")?;
+        let mut volumes = writer.finish()?.into_volumes();
+        assert_eq!(volumes.len(), 1);
+        let bytes = volumes.pop().unwrap().into_inner();
+        assert_eq!(&bytes[..4], b"PK00");
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        let mut s = String::new();
+        archive.by_name("hello.txt")?.read_to_string(&mut s)?;
+        assert_eq!(s, "Hello, World!");
+        Ok(())
+    }
+
     #[test]
     fn write_empty_zip() {
         let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
@@ -2119,6 +2677,8 @@ mod test {
             alignment: 1,
             #[cfg(feature = "deflate-zopfli")]
             zopfli_buffer_size: None,
+            #[cfg(feature = "parallelism")]
+            compression_threads: 1,
         };
         writer.start_file("mimetype", options).unwrap();
         writer
@@ -2156,6 +2716,8 @@ mod test {
             alignment: 1,
             #[cfg(feature = "deflate-zopfli")]
             zopfli_buffer_size: None,
+            #[cfg(feature = "parallelism")]
+            compression_threads: 1,
         };
 
         // GB18030
@@ -2208,6 +2770,8 @@ mod test {
             alignment: 0,
             #[cfg(feature = "deflate-zopfli")]
             zopfli_buffer_size: None,
+            #[cfg(feature = "parallelism")]
+            compression_threads: 1,
         };
         writer.start_file(RT_TEST_FILENAME, options).unwrap();
         writer.write_all(RT_TEST_TEXT.as_ref()).unwrap();
@@ -2258,6 +2822,8 @@ mod test {
             alignment: 0,
             #[cfg(feature = "deflate-zopfli")]
             zopfli_buffer_size: None,
+            #[cfg(feature = "parallelism")]
+            compression_threads: 1,
         };
         writer.start_file(RT_TEST_FILENAME, options).unwrap();
         writer.write_all(RT_TEST_TEXT.as_ref()).unwrap();
@@ -3498,4 +4064,45 @@ mod test {
         assert!(archive.comment().starts_with(&[33]));
         Ok(())
     }
+
+    #[cfg(all(
+        feature = "parallelism",
+        any(feature = "deflate-flate2", feature = "zstd")
+    ))]
+    #[test]
+    fn compression_threads() -> ZipResult<()> {
+        let data: Vec<u8> = (0..700_000u32)
+            .flat_map(|i| (i / 3).to_le_bytes())
+            .collect();
+        // Not looped over directly, since it holds only one method with some features
+        let methods = [
+            #[cfg(feature = "deflate-flate2")]
+            CompressionMethod::Deflated,
+            #[cfg(feature = "zstd")]
+            CompressionMethod::Zstd,
+        ];
+        for method in methods {
+            let write = |threads| -> ZipResult<Vec<u8>> {
+                let options = SimpleFileOptions::default()
+                    .compression_method(method)
+                    .last_modified_time(DateTime::default())
+                    .compression_threads(threads);
+                let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+                writer.start_file("data", options)?;
+                writer.write_all(&data)?;
+                writer.start_file("small", options)?;
+                writer.write_all(b"small")?;
+                Ok(writer.finish()?.into_inner())
+            };
+            let written = write(2)?;
+            assert!(written == write(5)?);
+            let mut archive = ZipArchive::new(Cursor::new(written))?;
+            let mut file = archive.by_name("data")?;
+            assert!(file.compressed_size() < data.len() as u64 / 4);
+            let mut read = Vec::new();
+            file.read_to_end(&mut read)?;
+            assert!(read == data);
+        }
+        Ok(())
+    }
 }
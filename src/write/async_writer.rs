@@ -0,0 +1,654 @@
+//! Writing archives to [`tokio`] writers.
+//!
+//! Entries are compressed, encrypted and laid out by the same code as [`ZipWriter`], which writes
+//! to an in-memory spool that is then written out asynchronously. When a local header is updated
+//! after the bytes it's in have been written out, the update is kept and written once the current
+//! method completes, by seeking back.
+
+use super::{FileOptionExtension, FileOptions, ZipWriter};
+use crate::read::AsyncZipFile;
+use crate::result::ZipResult;
+use std::fmt::{self, Debug, Formatter};
+use std::future::poll_fn;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+/// How much can be spooled before it's written out, which is also the most that's taken from a
+/// single write or copied at once.
+const SPOOL_LEN: usize = 64 * 1024;
+
+/// ZIP archive generator for [`tokio`] writers.
+///
+/// This mirrors [`ZipWriter`], with the same [`FileOptions`]; the contents of each file are
+/// written through [`AsyncWrite`]. Call [`AsyncZipWriter::finish`] once all files are written,
+/// since unlike a [`ZipWriter`], this can't write the central directory when it's dropped. If a
+/// future returned by one of its methods is dropped before it completes, the archive is left
+/// incomplete.
+///
+/// Shutting this down through [`AsyncWrite::poll_shutdown`] only flushes it, since the central
+/// directory has yet to be written by [`AsyncZipWriter::finish`].
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> zip::result::ZipResult<()> {
+/// use tokio::io::AsyncWriteExt;
+/// use zip::write::SimpleFileOptions;
+///
+/// let mut zip = zip::AsyncZipWriter::new(std::io::Cursor::new(Vec::new()));
+/// zip.start_file("hello.txt", SimpleFileOptions::default()).await?;
+/// zip.write_all(b"Hello, World!").await?;
+/// let bytes = zip.finish().await?.into_inner();
+///
+/// let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+/// assert_eq!(archive.by_name("hello.txt")?.size(), 13);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncZipWriter<W> {
+    inner: W,
+    zip: ZipWriter<Spool>,
+    spool: Spool,
+    /// Whether [`AsyncWrite::poll_flush`] has already flushed the compressor, and is now writing
+    /// out what that spooled.
+    flushing: bool,
+}
+
+impl<W: AsyncWrite + AsyncSeek + Unpin> AsyncZipWriter<W> {
+    /// Initializes an archive that is written to `inner`, starting at its current position.
+    ///
+    /// Offsets in the archive are counted from that position, as with
+    /// [`ZipWriter::new_stream`]; readers find the archive either way.
+    pub fn new(inner: W) -> AsyncZipWriter<W> {
+        let spool = Spool::default();
+        AsyncZipWriter {
+            inner,
+            zip: ZipWriter::new(spool.clone()),
+            spool,
+            flushing: false,
+        }
+    }
+
+    /// Set ZIP archive comment.
+    pub fn set_comment<S>(&mut self, comment: S)
+    where
+        S: Into<Box<str>>,
+    {
+        self.zip.set_comment(comment)
+    }
+
+    /// Create a file in the archive and start writing its contents. The file must not have the
+    /// same name as a file already in the archive.
+    ///
+    /// The data should be written using the [`AsyncWrite`] implementation on this
+    /// [`AsyncZipWriter`].
+    pub async fn start_file<S, T: FileOptionExtension, SToOwned>(
+        &mut self,
+        name: S,
+        options: FileOptions<'_, T>,
+    ) -> ZipResult<()>
+    where
+        S: Into<Box<str>> + ToOwned<Owned = SToOwned>,
+        SToOwned: Into<Box<str>>,
+    {
+        self.zip.start_file(name, options)?;
+        self.write_out().await
+    }
+
+    /// Add a directory entry.
+    ///
+    /// As directories have no content, you must not write to this [`AsyncZipWriter`] before
+    /// adding a new file.
+    pub async fn add_directory<S, T: FileOptionExtension>(
+        &mut self,
+        name: S,
+        options: FileOptions<'_, T>,
+    ) -> ZipResult<()>
+    where
+        S: Into<String>,
+    {
+        self.zip.add_directory(name, options)?;
+        self.write_out().await
+    }
+
+    /// Add a symlink entry.
+    ///
+    /// The same warnings apply as for [`ZipWriter::add_symlink`].
+    pub async fn add_symlink<N, NToOwned, T, E: FileOptionExtension>(
+        &mut self,
+        name: N,
+        target: T,
+        options: FileOptions<'_, E>,
+    ) -> ZipResult<()>
+    where
+        N: Into<Box<str>> + ToOwned<Owned = NToOwned>,
+        NToOwned: Into<Box<str>>,
+        T: Into<Box<str>>,
+    {
+        self.zip.add_symlink(name, target, options)?;
+        self.write_out().await
+    }
+
+    /// Add a new file using the already compressed data from an [`crate::AsyncZipArchive`],
+    /// without decompressing and compressing it again. Any metadata is copied and not checked,
+    /// for example the file CRC.
+    pub async fn raw_copy_file<R: AsyncRead + Unpin>(
+        &mut self,
+        file: AsyncZipFile<'_, R>,
+    ) -> ZipResult<()> {
+        let name = file.name().to_owned();
+        self.raw_copy_file_rename(file, name).await
+    }
+
+    /// Add a new file using the already compressed data from an [`crate::AsyncZipArchive`],
+    /// with a new name. The same notes apply as for [`AsyncZipWriter::raw_copy_file`].
+    pub async fn raw_copy_file_rename<R, S, SToOwned>(
+        &mut self,
+        mut file: AsyncZipFile<'_, R>,
+        name: S,
+    ) -> ZipResult<()>
+    where
+        R: AsyncRead + Unpin,
+        S: Into<Box<str>> + ToOwned<Owned = SToOwned>,
+        SToOwned: Into<Box<str>>,
+    {
+        self.zip.start_raw_entry(&file, name)?;
+        self.write_out().await?;
+        let mut buf = vec![0; SPOOL_LEN];
+        loop {
+            let count = poll_fn(|cx| file.poll_read_raw(cx, &mut buf)).await?;
+            if count == 0 {
+                return Ok(());
+            }
+            self.write_all(&buf[..count]).await?;
+        }
+    }
+
+    /// Removes the file currently being written from the archive if there is one, or else removes
+    /// the file most recently written.
+    ///
+    /// The same notes apply as for [`ZipWriter::abort_file`].
+    pub fn abort_file(&mut self) -> ZipResult<()> {
+        self.zip.abort_file()
+    }
+
+    /// Finish the last file and write all other zip-structures, returning the writer once
+    /// everything has been written to it and flushed.
+    pub async fn finish(self) -> ZipResult<W> {
+        let AsyncZipWriter {
+            mut inner,
+            zip,
+            spool,
+            ..
+        } = self;
+        zip.finish()?;
+        write_out(&mut inner, &spool).await?;
+        inner.flush().await?;
+        Ok(inner)
+    }
+
+    async fn write_out(&mut self) -> ZipResult<()> {
+        Ok(write_out(&mut self.inner, &self.spool).await?)
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncZipWriter<AsyncStreamWriter<W>> {
+    /// Initializes an archive that is written to an output that can't seek, such as an HTTP
+    /// response body.
+    ///
+    /// The archive is laid out as by [`ZipWriter::new_stream`], whose notes also apply here.
+    ///
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> zip::result::ZipResult<()> {
+    /// use tokio::io::AsyncWriteExt;
+    /// use zip::write::SimpleFileOptions;
+    ///
+    /// // A `Vec<u8>` doesn't implement `AsyncSeek`.
+    /// let mut zip = zip::AsyncZipWriter::new_stream(Vec::new());
+    /// zip.start_file("hello.txt", SimpleFileOptions::default()).await?;
+    /// zip.write_all(b"Hello, World!").await?;
+    /// let bytes = zip.finish().await?.into_inner();
+    ///
+    /// let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+    /// assert_eq!(archive.by_name("hello.txt")?.size(), 13);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_stream(inner: W) -> AsyncZipWriter<AsyncStreamWriter<W>> {
+        let mut writer = AsyncZipWriter::new(AsyncStreamWriter::new(inner));
+        writer.zip.seek_possible = false;
+        writer
+    }
+}
+
+impl<W: AsyncWrite + AsyncSeek + Unpin> AsyncWrite for AsyncZipWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.spool.lock().pending.len() >= SPOOL_LEN {
+            ready!(this.spool.poll_drain(&mut this.inner, cx))?;
+        }
+        let count = this.zip.write(&buf[..buf.len().min(SPOOL_LEN)])?;
+        Poll::Ready(Ok(count))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.flushing {
+            this.zip.flush()?;
+            this.flushing = true;
+        }
+        ready!(this.spool.poll_drain(&mut this.inner, cx))?;
+        this.flushing = false;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+/// Writes out everything spooled so far, then seeks back to apply the updates to bytes that were
+/// already written out.
+async fn write_out<W: AsyncWrite + AsyncSeek + Unpin>(
+    inner: &mut W,
+    spool: &Spool,
+) -> io::Result<()> {
+    poll_fn(|cx| spool.poll_drain(inner, cx)).await?;
+    let (patches, end) = {
+        let mut spool = spool.lock();
+        (mem::take(&mut spool.patches), spool.written)
+    };
+    if patches.is_empty() {
+        return Ok(());
+    }
+    let mut position = end;
+    for (offset, bytes) in patches {
+        inner
+            .seek(SeekFrom::Current(offset as i64 - position as i64))
+            .await?;
+        inner.write_all(&bytes).await?;
+        position = offset + bytes.len() as u64;
+    }
+    inner
+        .seek(SeekFrom::Current(end as i64 - position as i64))
+        .await?;
+    Ok(())
+}
+
+/// Adapter for writing a ZIP archive to a [`tokio`] output that can't seek. See
+/// [`AsyncZipWriter::new_stream`].
+///
+/// This implements [`AsyncSeek`] only to report how many bytes have been written so far; any
+/// other seek fails.
+#[derive(Debug)]
+pub struct AsyncStreamWriter<W> {
+    inner: W,
+    bytes_written: u64,
+}
+
+impl<W> AsyncStreamWriter<W> {
+    /// Wraps `inner`, counting bytes from the current position as offset 0.
+    pub const fn new(inner: W) -> AsyncStreamWriter<W> {
+        AsyncStreamWriter {
+            inner,
+            bytes_written: 0,
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub const fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Consumes this wrapper, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncStreamWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let count = ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        this.bytes_written += count as u64;
+        Poll::Ready(Ok(count))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncSeek for AsyncStreamWriter<W> {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        match position {
+            SeekFrom::Current(0) => Ok(()),
+            SeekFrom::Start(offset) if offset == self.bytes_written => Ok(()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "AsyncStreamWriter can't seek; it only reports its current position",
+            )),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.bytes_written))
+    }
+}
+
+/// The archive as written by [`ZipWriter`], of which only the end that hasn't been written out
+/// yet is kept. Shared with the [`AsyncZipWriter`] that writes it out.
+#[derive(Clone, Default)]
+struct Spool(Arc<Mutex<SpoolState>>);
+
+#[derive(Default)]
+struct SpoolState {
+    /// The bytes after those written out so far.
+    pending: Vec<u8>,
+    /// How many bytes have been written out so far.
+    written: u64,
+    position: u64,
+    /// Updates to bytes that were already written out, with their offsets.
+    patches: Vec<(u64, Vec<u8>)>,
+}
+
+impl Spool {
+    fn lock(&self) -> MutexGuard<'_, SpoolState> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Writes the pending bytes to `inner`.
+    fn poll_drain<W: AsyncWrite + Unpin>(
+        &self,
+        inner: &mut W,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        let mut spool = self.lock();
+        while !spool.pending.is_empty() {
+            let count = ready!(Pin::new(&mut *inner).poll_write(cx, &spool.pending))?;
+            if count == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            spool.pending.drain(..count);
+            spool.written += count as u64;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Debug for Spool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let spool = self.lock();
+        f.debug_struct("Spool")
+            .field("pending", &spool.pending.len())
+            .field("written", &spool.written)
+            .field("position", &spool.position)
+            .field("patches", &spool.patches.len())
+            .finish()
+    }
+}
+
+impl Write for Spool {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut spool = self.lock();
+        let position = spool.position;
+        let count = if position < spool.written {
+            let count = buf.len().min((spool.written - position) as usize);
+            spool.patches.push((position, buf[..count].to_vec()));
+            count
+        } else {
+            let start = (position - spool.written) as usize;
+            let end = start + buf.len();
+            if end > spool.pending.len() {
+                spool.pending.resize(end, 0);
+            }
+            spool.pending[start..end].copy_from_slice(buf);
+            buf.len()
+        };
+        spool.position += count as u64;
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for Spool {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let mut spool = self.lock();
+        let len = spool.written + spool.pending.len() as u64;
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => spool.position.checked_add_signed(offset),
+        }
+        .filter(|&position| position <= len)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek outside what has been written so far",
+            )
+        })?;
+        spool.position = position;
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AsyncZipWriter;
+    use crate::result::ZipResult;
+    use crate::write::SimpleFileOptions;
+    use crate::{AsyncZipArchive, CompressionMethod, DateTime, ZipArchive, ZipWriter};
+    use std::io::{self, Cursor, Read, SeekFrom, Write};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncSeek, AsyncWrite, AsyncWriteExt};
+
+    /// Accepts at most a few hundred bytes when it's polled, and only every other time.
+    #[derive(Default)]
+    struct Trickle {
+        inner: Cursor<Vec<u8>>,
+        ready: bool,
+    }
+
+    impl AsyncWrite for Trickle {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            if !std::mem::replace(&mut self.ready, false) {
+                self.ready = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let len = buf.len().min(300);
+            Poll::Ready(Write::write(&mut self.inner, &buf[..len]))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncSeek for Trickle {
+        fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+            Pin::new(&mut self.inner).start_seek(position)
+        }
+
+        fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+            Pin::new(&mut self.inner).poll_complete(cx)
+        }
+    }
+
+    /// Options with a fixed modification time, so that archives written at different times match.
+    fn options() -> SimpleFileOptions {
+        SimpleFileOptions::default().last_modified_time(DateTime::default())
+    }
+
+    fn contents() -> Vec<u8> {
+        (0..100_000u32)
+            .flat_map(|i| (i % 1000).to_le_bytes())
+            .collect()
+    }
+
+    fn methods() -> Vec<CompressionMethod> {
+        #[allow(unused_mut)]
+        let mut methods = vec![CompressionMethod::Stored];
+        #[cfg(feature = "_deflate-any")]
+        methods.push(CompressionMethod::Deflated);
+        #[cfg(feature = "bzip2")]
+        methods.push(CompressionMethod::Bzip2);
+        #[cfg(feature = "zstd")]
+        methods.push(CompressionMethod::Zstd);
+        methods
+    }
+
+    /// Writes the same files as [`write_sync`].
+    async fn write_async<W>(mut zip: AsyncZipWriter<W>) -> ZipResult<W>
+    where
+        W: AsyncWrite + AsyncSeek + Unpin,
+    {
+        let contents = contents();
+        zip.set_comment("async");
+        for method in methods() {
+            let options = options().compression_method(method);
+            zip.start_file(format!("{method}"), options).await?;
+            zip.write_all(&contents).await?;
+        }
+        zip.add_directory("dir/", options()).await?;
+        zip.add_symlink("dir/link", "../Stored", options()).await?;
+        zip.start_file("aborted", options()).await?;
+        zip.write_all(&contents).await?;
+        zip.abort_file()?;
+        zip.start_file("empty", options()).await?;
+        zip.finish().await
+    }
+
+    fn write_sync<W: Write + io::Seek>(mut zip: ZipWriter<W>) -> ZipResult<W> {
+        let contents = contents();
+        zip.set_comment("async");
+        for method in methods() {
+            let options = options().compression_method(method);
+            zip.start_file(format!("{method}"), options)?;
+            zip.write_all(&contents)?;
+        }
+        zip.add_directory("dir/", options())?;
+        zip.add_symlink("dir/link", "../Stored", options())?;
+        zip.start_file("aborted", options())?;
+        zip.write_all(&contents)?;
+        zip.abort_file()?;
+        zip.start_file("empty", options())?;
+        zip.finish()
+    }
+
+    #[tokio::test]
+    async fn write_same_as_sync() -> ZipResult<()> {
+        let expected = write_sync(ZipWriter::new(Cursor::new(Vec::new())))?.into_inner();
+        let actual = write_async(AsyncZipWriter::new(Trickle::default()))
+            .await?
+            .inner
+            .into_inner();
+        assert_eq!(actual, expected);
+
+        let expected = write_sync(ZipWriter::new_stream(Vec::new()))?.into_inner();
+        let actual = write_async(AsyncZipWriter::new_stream(Trickle::default()))
+            .await?
+            .into_inner()
+            .inner
+            .into_inner();
+        assert_eq!(actual, expected);
+
+        let mut archive = ZipArchive::new(Cursor::new(actual))?;
+        assert_eq!(archive.comment(), b"async");
+        let mut stored = Vec::new();
+        archive.by_name("Stored")?.read_to_end(&mut stored)?;
+        assert_eq!(stored, contents());
+        assert!(archive.by_name("aborted").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_encrypted() -> ZipResult<()> {
+        let contents = contents();
+        let mut zip = AsyncZipWriter::new(Trickle::default());
+        zip.start_file(
+            "ZipCrypto",
+            options().with_deprecated_encryption(b"password"),
+        )
+        .await?;
+        zip.write_all(&contents).await?;
+        #[cfg(feature = "aes-crypto")]
+        {
+            let options = options().with_aes_encryption(crate::AesMode::Aes256, "password");
+            zip.start_file("AES", options).await?;
+            zip.write_all(&contents).await?;
+        }
+        let bytes = zip.finish().await?.inner.into_inner();
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        for i in 0..archive.len() {
+            let mut actual = Vec::new();
+            archive
+                .by_index_decrypt(i, b"password")?
+                .read_to_end(&mut actual)?;
+            assert_eq!(actual, contents);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn raw_copy_from_async_archive() -> ZipResult<()> {
+        let bytes = write_sync(ZipWriter::new(Cursor::new(Vec::new())))?.into_inner();
+        let mut source = AsyncZipArchive::new(Cursor::new(bytes)).await?;
+        let mut zip = AsyncZipWriter::new_stream(Trickle::default());
+        for i in 0..source.len() {
+            let file = source.by_index(i).await?;
+            let name = format!("copy/{}", file.name());
+            zip.raw_copy_file_rename(file, name).await?;
+        }
+        let bytes = zip.finish().await?.into_inner().inner.into_inner();
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        assert_eq!(archive.len(), source.len());
+        for method in methods() {
+            let mut actual = Vec::new();
+            archive
+                .by_name(&format!("copy/{method}"))?
+                .read_to_end(&mut actual)?;
+            assert_eq!(actual, contents());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn writer_is_send() {
+        fn assert_send<T: Send>(_: &T) {}
+        let mut zip = AsyncZipWriter::new(Cursor::new(Vec::new()));
+        assert_send(&zip.start_file("file", options()));
+        assert_send(&zip);
+    }
+}
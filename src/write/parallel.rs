@@ -0,0 +1,330 @@
+//! Compressing several entries at once.
+//!
+//! Each entry is written by a worker thread to its own single-entry archive in memory, with the
+//! same code and options as [`ZipWriter::start_file`]. The bytes of that archive up to its central
+//! directory are then appended to the destination in input order, and the entry's offsets are
+//! moved by where it landed, as when [`ZipWriter::raw_copy_file`] copies an entry.
+
+use super::{FileOptionExtension, FileOptions, GenericZipWriter, ZipWriter};
+use crate::result::{ZipError, ZipResult};
+use crate::types::{ZipFileData, ZipLocalEntryBlock};
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Read, Seek, Write};
+use std::mem::{self, size_of};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+/// An entry that was compressed by a worker: its metadata, relative to the start of its bytes.
+type CompressedEntry = ZipResult<(ZipFileData, Vec<u8>)>;
+
+/// An entry sent to a worker, with its position in the input.
+type Job<'k, T, R> = (usize, Box<str>, FileOptions<'k, T>, R);
+
+/// An entry whose turn to be appended hasn't come yet.
+enum Pending<'k, T: FileOptionExtension, R> {
+    Compressed(CompressedEntry),
+    /// The padding that aligns an entry's data depends on where its header lands, so an aligned
+    /// entry is written in place once the entries before it are.
+    Aligned(Box<str>, FileOptions<'k, T>, R),
+}
+
+impl<W: Write + Seek> ZipWriter<W> {
+    /// Adds files whose contents are read from `files`, compressing and encrypting up to `threads`
+    /// of them at once.
+    ///
+    /// The archive is the same as one written by calling [`ZipWriter::start_file`] with each name
+    /// and options in turn and copying each reader into it, so it doesn't depend on `threads` or
+    /// on the order in which the workers finish. As when writing sequentially, AES encryption
+    /// uses a random salt for each entry. Entries with an
+    /// [`alignment`](FileOptions::with_alignment) are written on the calling thread once the
+    /// entries before them are.
+    ///
+    /// At most twice as many entries as `threads` are held in memory while waiting for their turn.
+    /// If a reader or an entry fails, the entries before it are kept, and the error is returned
+    /// without adding it or any later entry.
+    ///
+    /// ```
+    /// # fn main() -> zip::result::ZipResult<()> {
+    /// use std::io::Cursor;
+    /// use zip::write::SimpleFileOptions;
+    ///
+    /// let options = SimpleFileOptions::default();
+    /// let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    /// zip.add_files_parallel(
+    ///     [
+    ///         ("first.txt", options, &b"Hello"[..]),
+    ///         ("second.txt", options, &b"World"[..]),
+    ///     ],
+    ///     2,
+    /// )?;
+    /// let archive = zip::ZipArchive::new(zip.finish()?)?;
+    /// assert_eq!(archive.file_names().count(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_files_parallel<'k, S, T, R, I>(&mut self, files: I, threads: usize) -> ZipResult<()>
+    where
+        I: IntoIterator<Item = (S, FileOptions<'k, T>, R)>,
+        S: Into<Box<str>>,
+        T: FileOptionExtension + Send,
+        R: Read + Send,
+    {
+        self.finish_file()?;
+        let threads = threads.max(1);
+        let seek_possible = self.seek_possible;
+        let (job_sender, job_receiver) = mpsc::channel::<Job<'k, T, R>>();
+        let job_receiver = Mutex::new(job_receiver);
+        let (result_sender, result_receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for i in 0..threads {
+                let job_receiver = &job_receiver;
+                let result_sender = result_sender.clone();
+                thread::Builder::new()
+                    .name(format!("zip-compressor-{i}"))
+                    .spawn_scoped(scope, move || loop {
+                        let job = job_receiver.lock().unwrap().recv();
+                        let Ok((index, name, options, reader)) = job else {
+                            break;
+                        };
+                        let result = compress_entry(name, options, reader, seek_possible);
+                        if result_sender.send((index, result)).is_err() {
+                            break;
+                        }
+                    })?;
+            }
+            drop(result_sender);
+            // Returning drops both ends of the channels, which stops the workers.
+            self.append_in_order(files, job_sender, result_receiver, 2 * threads)
+        })
+    }
+
+    /// Hands out entries to the workers while at most `window` of them are in flight, and appends
+    /// them in input order as they come back.
+    fn append_in_order<'k, S, T, R>(
+        &mut self,
+        files: impl IntoIterator<Item = (S, FileOptions<'k, T>, R)>,
+        job_sender: Sender<Job<'k, T, R>>,
+        result_receiver: Receiver<(usize, CompressedEntry)>,
+        window: usize,
+    ) -> ZipResult<()>
+    where
+        S: Into<Box<str>>,
+        T: FileOptionExtension,
+        R: Read,
+    {
+        let mut files = files.into_iter();
+        let mut pending = BTreeMap::new();
+        let mut exhausted = false;
+        let mut queued = 0;
+        let mut next = 0;
+        loop {
+            while !exhausted && queued - next < window {
+                let Some((name, options, reader)) = files.next() else {
+                    exhausted = true;
+                    break;
+                };
+                let name = name.into();
+                if options.alignment > 1 {
+                    pending.insert(queued, Pending::Aligned(name, options, reader));
+                } else if job_sender.send((queued, name, options, reader)).is_err() {
+                    unreachable!("The receiver outlives the workers");
+                }
+                queued += 1;
+            }
+            if next == queued {
+                return Ok(());
+            }
+            match pending.remove(&next) {
+                Some(Pending::Compressed(result)) => {
+                    let (file, bytes) = result?;
+                    self.append_compressed_entry(file, &bytes)?;
+                    next += 1;
+                }
+                Some(Pending::Aligned(name, options, mut reader)) => {
+                    self.start_file(name, options)?;
+                    io::copy(&mut reader, self)?;
+                    self.finish_file()?;
+                    next += 1;
+                }
+                None => {
+                    let (index, result) = result_receiver.recv().map_err(|_| {
+                        io::Error::new(io::ErrorKind::Other, "Compression workers exited")
+                    })?;
+                    pending.insert(index, Pending::Compressed(result));
+                }
+            }
+        }
+    }
+
+    /// Appends an entry compressed by [`compress_entry`], whose offsets start from 0.
+    fn append_compressed_entry(&mut self, mut file: ZipFileData, bytes: &[u8]) -> ZipResult<()> {
+        if self.files.contains_key(&file.file_name) {
+            return Err(ZipError::InvalidArchive("Duplicate filename"));
+        }
+        if let Some(volumes) = self.volumes {
+            // The same bound as in `start_entry`, so that volumes are split where they would be anyway
+            let header_len = size_of::<ZipLocalEntryBlock>()
+                + file.file_name.len()
+                + 28
+                + file.extra_field.as_ref().map_or(0, |extra| extra.len());
+            volumes(self.inner.get_plain()).keep_together(header_len as u64)?;
+        }
+        let writer = self.inner.get_plain();
+        let offset = writer.stream_position()?;
+        file.header_start += offset;
+        file.extra_data_start = file.extra_data_start.map(|start| start + offset);
+        file.data_start = OnceLock::from(file.data_start() + offset);
+        self.insert_file_data(file)?;
+        let writer = self.inner.get_plain();
+        let mut result = writer.write_all(bytes);
+        if self.flush_on_finish_file && result.is_ok() {
+            result = writer.flush();
+        }
+        self.ok_or_abort_file(result)
+    }
+}
+
+/// Writes a single entry to an archive in memory, and takes it apart again before its central
+/// directory is written.
+fn compress_entry<T: FileOptionExtension>(
+    name: Box<str>,
+    options: FileOptions<T>,
+    mut reader: impl Read,
+    seek_possible: bool,
+) -> CompressedEntry {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    // A destination that can't seek gets data descriptors, so this entry must get them too
+    writer.seek_possible = seek_possible;
+    writer.start_file(name, options)?;
+    io::copy(&mut reader, &mut writer)?;
+    writer.finish_file()?;
+    let (_, file) = writer.files.pop().unwrap();
+    let bytes = mem::replace(&mut writer.inner, GenericZipWriter::Closed)
+        .unwrap()
+        .into_inner();
+    Ok((file, bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::result::ZipResult;
+    use crate::write::{FullFileOptions, SimpleFileOptions};
+    use crate::{CompressionMethod, ZipArchive, ZipWriter};
+    use std::io::{Cursor, Read, Write};
+
+    fn contents(i: usize) -> Vec<u8> {
+        format!("file {i} ").repeat(i * 37).into_bytes()
+    }
+
+    fn entries() -> Vec<(String, FullFileOptions<'static>)> {
+        let base = FullFileOptions::default().last_modified_time(crate::DateTime::default());
+        (0..24)
+            .map(|i| {
+                let options = match i % 4 {
+                    0 => base
+                        .clone()
+                        .compression_method(CompressionMethod::Stored)
+                        .large_file(i == 8),
+                    1 => base.clone().with_deprecated_encryption(b"password"),
+                    2 => base
+                        .clone()
+                        .compression_method(CompressionMethod::Stored)
+                        .with_alignment(64),
+                    _ => {
+                        let mut options = base.clone();
+                        options
+                            .add_extra_data(0xbeef, vec![i as u8; 5].into_boxed_slice(), false)
+                            .unwrap();
+                        options
+                    }
+                };
+                (format!("dir/file{i}.txt"), options)
+            })
+            .collect()
+    }
+
+    /// Starts an archive with an entry, so that the added ones don't start at offset 0.
+    fn started() -> ZipResult<ZipWriter<Cursor<Vec<u8>>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("before.txt", SimpleFileOptions::default())?;
+        writer.write_all(b"written first")?;
+        Ok(writer)
+    }
+
+    fn sequential() -> ZipResult<Vec<u8>> {
+        let mut writer = started()?;
+        for (i, (name, options)) in entries().into_iter().enumerate() {
+            writer.start_file(name, options)?;
+            writer.write_all(&contents(i))?;
+        }
+        Ok(writer.finish()?.into_inner())
+    }
+
+    fn parallel(threads: usize) -> ZipResult<Vec<u8>> {
+        let mut writer = started()?;
+        let files = entries()
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, options))| (name, options, Cursor::new(contents(i))));
+        writer.add_files_parallel(files, threads)?;
+        Ok(writer.finish()?.into_inner())
+    }
+
+    #[test]
+    fn same_as_sequential() -> ZipResult<()> {
+        let expected = sequential()?;
+        for threads in [1, 3, 8] {
+            assert!(parallel(threads)? == expected);
+        }
+        let mut archive = ZipArchive::new(Cursor::new(expected))?;
+        assert_eq!(archive.len(), 25);
+        // Aligned entries are only compared byte for byte: their local headers don't count the
+        // padding, so they can't be read back yet.
+        for i in (0..24).filter(|i| i % 4 != 2) {
+            let mut file = archive.by_index_decrypt(i + 1, b"password")?;
+            let mut read = Vec::new();
+            file.read_to_end(&mut read)?;
+            assert_eq!(read, contents(i));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_name() -> ZipResult<()> {
+        let mut writer = started()?;
+        let options = SimpleFileOptions::default();
+        let files = [
+            ("a.txt", options, &b"a"[..]),
+            ("before.txt", options, &b"b"[..]),
+            ("c.txt", options, &b"c"[..]),
+        ];
+        assert!(writer.add_files_parallel(files, 2).is_err());
+        let archive = ZipArchive::new(writer.finish()?)?;
+        assert_eq!(
+            archive.file_names().collect::<Vec<_>>(),
+            ["before.txt", "a.txt"]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "aes-crypto")]
+    #[test]
+    fn aes() -> ZipResult<()> {
+        let mut writer = started()?;
+        let files = (0..6).map(|i| {
+            let options =
+                SimpleFileOptions::default().with_aes_encryption(crate::AesMode::Aes256, "secret");
+            (format!("{i}.txt"), options, Cursor::new(contents(i)))
+        });
+        writer.add_files_parallel(files, 3)?;
+        let mut archive = ZipArchive::new(writer.finish()?)?;
+        for i in 0..6 {
+            let mut file = archive.by_name_decrypt(&format!("{i}.txt"), b"secret")?;
+            let mut read = Vec::new();
+            file.read_to_end(&mut read)?;
+            assert_eq!(read, contents(i));
+        }
+        Ok(())
+    }
+}
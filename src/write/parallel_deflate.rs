@@ -0,0 +1,246 @@
+//! Compressing a single Deflate stream on several threads.
+//!
+//! As in pigz, the input is cut into blocks that are compressed independently, each ending with a
+//! sync flush so that it stops on a byte boundary, and only the last one is marked final. Their
+//! concatenation is one ordinary Deflate stream. Since the blocks don't share a dictionary, the
+//! first matches in each block are found again, which costs a little compression.
+
+use flate2::{Compress, Compression, FlushCompress, Status};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// How much input goes into each block. This and the level alone determine the output, so it
+/// doesn't depend on the number of threads.
+const BLOCK_LEN: usize = 128 * 1024;
+
+type Job = (u64, Vec<u8>);
+type Compressed = (u64, io::Result<Vec<u8>>);
+
+/// Compresses `block` as a part of a raw Deflate stream that's either the last one or ends with a
+/// sync flush.
+fn compress_block(level: Compression, block: &[u8], last: bool) -> io::Result<Vec<u8>> {
+    let mut compress = Compress::new(level, false);
+    let flush = if last {
+        FlushCompress::Finish
+    } else {
+        FlushCompress::Sync
+    };
+    let mut output = Vec::with_capacity(block.len() + block.len() / 16 + 64);
+    loop {
+        let consumed = compress.total_in() as usize;
+        let status = compress.compress_vec(&block[consumed..], &mut output, flush)?;
+        let done = if last {
+            status == Status::StreamEnd
+        } else {
+            // The flush is complete once it no longer fills the output.
+            compress.total_in() as usize == block.len() && output.len() < output.capacity()
+        };
+        if done {
+            return Ok(output);
+        }
+        output.reserve(output.capacity().max(64));
+    }
+}
+
+/// Threads that compress the blocks, which are started once the first block is full.
+struct Workers {
+    jobs: Option<Sender<Job>>,
+    results: Receiver<Compressed>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Workers {
+    fn spawn(level: Compression, threads: usize) -> io::Result<Self> {
+        let (job_sender, job_receiver) = mpsc::channel::<Job>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let (result_sender, results) = mpsc::channel();
+        let mut workers = Workers {
+            jobs: Some(job_sender),
+            results,
+            handles: Vec::with_capacity(threads),
+        };
+        for i in 0..threads {
+            let job_receiver = job_receiver.clone();
+            let result_sender = result_sender.clone();
+            let handle = thread::Builder::new()
+                .name(format!("zip-deflate-{i}"))
+                .spawn(move || loop {
+                    let job = job_receiver.lock().unwrap().recv();
+                    let Ok((index, block)) = job else {
+                        break;
+                    };
+                    let result = compress_block(level, &block, false);
+                    if result_sender.send((index, result)).is_err() {
+                        break;
+                    }
+                })?;
+            workers.handles.push(handle);
+        }
+        Ok(workers)
+    }
+}
+
+impl Drop for Workers {
+    fn drop(&mut self) {
+        // Closing the queue stops each worker once it has compressed what's left in it
+        self.jobs = None;
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Deflate encoder that compresses blocks of its input on `threads` threads, and writes them out
+/// in order.
+pub(super) struct ParallelDeflateEncoder<W: Write> {
+    inner: W,
+    level: Compression,
+    threads: usize,
+    block: Vec<u8>,
+    workers: Option<Workers>,
+    /// Compressed blocks that are waiting for the ones before them.
+    done: BTreeMap<u64, io::Result<Vec<u8>>>,
+    queued: u64,
+    written: u64,
+}
+
+impl<W: Write> ParallelDeflateEncoder<W> {
+    pub(super) fn new(inner: W, level: Compression, threads: usize) -> Self {
+        ParallelDeflateEncoder {
+            inner,
+            level,
+            threads,
+            block: Vec::new(),
+            workers: None,
+            done: BTreeMap::new(),
+            queued: 0,
+            written: 0,
+        }
+    }
+
+    pub(super) const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Hands the current block to the workers, once fewer than two per thread are in flight.
+    fn queue_block(&mut self) -> io::Result<()> {
+        if self.workers.is_none() {
+            self.workers = Some(Workers::spawn(self.level, self.threads)?);
+        }
+        while self.queued - self.written >= 2 * self.threads as u64 {
+            self.receive_block()?;
+        }
+        let block = std::mem::take(&mut self.block);
+        let workers = self.workers.as_ref().unwrap();
+        if workers
+            .jobs
+            .as_ref()
+            .unwrap()
+            .send((self.queued, block))
+            .is_err()
+        {
+            return Err(workers_exited());
+        }
+        self.queued += 1;
+        Ok(())
+    }
+
+    /// Waits for a block to be compressed, and writes out those that are next in line.
+    fn receive_block(&mut self) -> io::Result<()> {
+        let workers = self.workers.as_ref().unwrap();
+        let (index, result) = workers.results.recv().map_err(|_| workers_exited())?;
+        self.done.insert(index, result);
+        while let Some(result) = self.done.remove(&self.written) {
+            self.inner.write_all(&result?)?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    fn write_queued(&mut self) -> io::Result<()> {
+        while self.written < self.queued {
+            self.receive_block()?;
+        }
+        Ok(())
+    }
+
+    /// Writes out the rest of the stream, ending with the last block, and returns the writer.
+    pub(super) fn finish(mut self) -> io::Result<W> {
+        self.write_queued()?;
+        self.workers = None;
+        let last = compress_block(self.level, &self.block, true)?;
+        self.inner.write_all(&last)?;
+        Ok(self.inner)
+    }
+}
+
+fn workers_exited() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "Deflate workers exited")
+}
+
+impl<W: Write> Write for ParallelDeflateEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.block.capacity() == 0 {
+            self.block.reserve_exact(BLOCK_LEN);
+        }
+        let len = buf.len().min(BLOCK_LEN - self.block.len());
+        self.block.extend_from_slice(&buf[..len]);
+        if self.block.len() == BLOCK_LEN {
+            self.queue_block()?;
+        }
+        Ok(len)
+    }
+
+    /// Ends the current block early with a sync flush, so that everything written so far can be
+    /// decompressed.
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.block.is_empty() {
+            self.queue_block()?;
+        }
+        self.write_queued()?;
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ParallelDeflateEncoder, BLOCK_LEN};
+    use flate2::read::DeflateDecoder;
+    use flate2::Compression;
+    use std::io::{Read, Write};
+
+    fn compress(data: &[u8], threads: usize) -> Vec<u8> {
+        let mut encoder = ParallelDeflateEncoder::new(Vec::new(), Compression::default(), threads);
+        for chunk in data.chunks(10_000) {
+            encoder.write_all(chunk).unwrap();
+        }
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<u8> = (0..5 * BLOCK_LEN + 1234)
+            .map(|i| (i % 251) as u8 ^ (i / 1000) as u8)
+            .collect();
+        let compressed = compress(&data, 3);
+        assert!(compressed.len() < data.len() / 4);
+        assert_eq!(compressed, compress(&data, 1));
+        assert_eq!(compressed, compress(&data, 8));
+        let mut decompressed = Vec::new();
+        DeflateDecoder::new(&compressed[..])
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert!(decompressed == data);
+
+        for len in [0, 1, BLOCK_LEN] {
+            let mut decompressed = Vec::new();
+            DeflateDecoder::new(&compress(&data[..len], 2)[..])
+                .read_to_end(&mut decompressed)
+                .unwrap();
+            assert!(decompressed == data[..len]);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Writing archives that are split across several files (volumes).
+
+use crate::spec::Magic;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// The smallest volume size accepted by [`crate::ZipWriter::new_split`], which is also the
+/// smallest that Info-ZIP will write.
+pub const MIN_VOLUME_SIZE: u64 = 64 * 1024;
+
+/// Disk numbers must fit in the 16-bit fields of the end of central directory record, where
+/// `0xFFFF` would mean that the ZIP64 record should be consulted instead.
+const MAX_VOLUMES: usize = u16::MAX as usize;
+
+/// Lets [`crate::ZipWriter`] place records on the volumes of a [`SplitWriter`] without knowing
+/// its type parameters.
+pub(crate) trait Volumes {
+    /// Where each volume starts, relative to the start of the first one.
+    fn volume_starts(&self) -> &[u64];
+
+    /// Starts a new volume unless the next `len` bytes fit in the current one, since headers and
+    /// end records must not be split.
+    fn keep_together(&mut self, len: u64) -> io::Result<()>;
+
+    /// Marks an archive that turned out to fit in its first volume as a single-segment one, by
+    /// replacing the spanning signature with the marker that APPNOTE 8.5.4 reserves for this.
+    fn mark_single_segment(&mut self) -> io::Result<()>;
+}
+
+/// Locates `position` within volumes starting at `volume_starts`, returning the disk number and
+/// the offset into that volume.
+pub(crate) fn locate(volume_starts: &[u64], position: u64) -> (u32, u64) {
+    let disk = volume_starts.partition_point(|&start| start <= position) - 1;
+    (disk as u32, position - volume_starts[disk])
+}
+
+/// Writer for the volumes of a split archive, such as `archive.z01`, `archive.z02`, ...,
+/// `archive.zip`, which behaves as if they were concatenated. Created by
+/// [`crate::ZipWriter::new_split`].
+///
+/// A new volume is requested from `make_volume` whenever the current one reaches the maximum
+/// size. Earlier volumes are kept, since their local headers are updated once each file is
+/// finished.
+pub struct SplitWriter<V, F> {
+    volumes: Vec<V>,
+    /// Where each volume starts, relative to the start of the first one.
+    starts: Vec<u64>,
+    /// Length of the last volume.
+    last_len: u64,
+    max_volume_size: u64,
+    make_volume: F,
+    position: u64,
+    /// Whether the volume at `position` has been positioned there.
+    synced: bool,
+}
+
+impl<V, F> Debug for SplitWriter<V, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SplitWriter")
+            .field("starts", &self.starts)
+            .field("last_len", &self.last_len)
+            .field("max_volume_size", &self.max_volume_size)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<V: Write + Seek, F: FnMut(u32) -> io::Result<V>> SplitWriter<V, F> {
+    /// Creates the first volume, starting with the spanning signature.
+    pub(crate) fn new(max_volume_size: u64, mut make_volume: F) -> io::Result<Self> {
+        if max_volume_size < MIN_VOLUME_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Volumes must be at least 64 KiB",
+            ));
+        }
+        let first = make_volume(0)?;
+        let mut writer = SplitWriter {
+            volumes: vec![first],
+            starts: vec![0],
+            last_len: 0,
+            max_volume_size,
+            make_volume,
+            position: 0,
+            synced: true,
+        };
+        writer.write_all(&Magic::SPANNING_SIGNATURE.to_le_bytes())?;
+        Ok(writer)
+    }
+
+    fn total_len(&self) -> u64 {
+        self.starts[self.starts.len() - 1] + self.last_len
+    }
+
+    fn start_volume(&mut self) -> io::Result<()> {
+        if self.volumes.len() == MAX_VOLUMES {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Too many volumes for a split archive",
+            ));
+        }
+        let volume = (self.make_volume)(self.volumes.len() as u32)?;
+        let start = self.total_len();
+        self.volumes.push(volume);
+        self.starts.push(start);
+        self.last_len = 0;
+        self.synced = true;
+        Ok(())
+    }
+}
+
+impl<V, F> SplitWriter<V, F> {
+    /// Number of volumes written so far.
+    pub fn volume_count(&self) -> usize {
+        self.volumes.len()
+    }
+
+    /// Where each volume starts, relative to the start of the first one.
+    pub fn volume_starts(&self) -> &[u64] {
+        &self.starts
+    }
+
+    /// Consumes this writer, returning the volumes in order. The last one contains the central
+    /// directory and is conventionally named with a `.zip` extension.
+    pub fn into_volumes(self) -> Vec<V> {
+        self.volumes
+    }
+}
+
+impl<V: Write + Seek, F: FnMut(u32) -> io::Result<V>> Volumes for SplitWriter<V, F> {
+    fn volume_starts(&self) -> &[u64] {
+        &self.starts
+    }
+
+    fn keep_together(&mut self, len: u64) -> io::Result<()> {
+        debug_assert_eq!(self.position, self.total_len());
+        if len > self.max_volume_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Header is larger than the maximum volume size",
+            ));
+        }
+        if self.last_len > 0 && self.last_len + len > self.max_volume_size {
+            self.start_volume()?;
+        }
+        Ok(())
+    }
+
+    fn mark_single_segment(&mut self) -> io::Result<()> {
+        if self.volumes.len() != 1 {
+            return Ok(());
+        }
+        let position = self.position;
+        self.seek(SeekFrom::Start(0))?;
+        self.write_all(&Magic::SINGLE_SEGMENT_MARKER.to_le_bytes())?;
+        self.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+}
+
+impl<V: Write + Seek, F: FnMut(u32) -> io::Result<V>> Write for SplitWriter<V, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let total_len = self.total_len();
+        let last = self.volumes.len() - 1;
+        let (index, offset) = if self.position == total_len {
+            if self.last_len == self.max_volume_size {
+                self.start_volume()?;
+                (last + 1, 0)
+            } else {
+                (last, self.last_len)
+            }
+        } else {
+            let (disk, offset) = locate(&self.starts, self.position);
+            (disk as usize, offset)
+        };
+        let volume_len = if index == self.volumes.len() - 1 {
+            self.max_volume_size
+        } else {
+            // Earlier volumes are only ever overwritten in place.
+            self.starts[index + 1] - self.starts[index]
+        };
+        if !self.synced {
+            self.volumes[index].seek(SeekFrom::Start(offset))?;
+            self.synced = true;
+        }
+        let max_len = (volume_len - offset).min(buf.len() as u64) as usize;
+        let count = self.volumes[index].write(&buf[..max_len])?;
+        self.position += count as u64;
+        if index == self.volumes.len() - 1 {
+            self.last_len = self.last_len.max(offset + count as u64);
+        }
+        if offset + count as u64 == volume_len {
+            // The next write goes to the start of another volume.
+            self.synced = false;
+        }
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.volumes.iter_mut().try_for_each(Write::flush)
+    }
+}
+
+impl<V: Write + Seek, F: FnMut(u32) -> io::Result<V>> Seek for SplitWriter<V, F> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let total_len = self.total_len();
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => total_len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .filter(|&position| position <= total_len)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek outside the volumes written so far",
+            )
+        })?;
+        if position != self.position {
+            self.position = position;
+            self.synced = false;
+        }
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SplitWriter, Volumes, MIN_VOLUME_SIZE};
+    use std::io::{Cursor, Seek, SeekFrom, Write};
+
+    #[test]
+    fn writes_across_volumes() {
+        let mut writer =
+            SplitWriter::new(MIN_VOLUME_SIZE, |_| Ok(Cursor::new(Vec::new()))).unwrap();
+        writer
+            .write_all(&[1; MIN_VOLUME_SIZE as usize - 4])
+            .unwrap();
+        writer.keep_together(8).unwrap();
+        writer.write_all(&[2; 8]).unwrap();
+        assert_eq!(writer.volume_starts(), &[0, MIN_VOLUME_SIZE]);
+
+        // A header that doesn't fit in the rest of the volume starts the next one.
+        writer
+            .write_all(&[3; MIN_VOLUME_SIZE as usize - 16])
+            .unwrap();
+        writer.keep_together(16).unwrap();
+        writer.write_all(&[4; 16]).unwrap();
+        assert_eq!(
+            writer.volume_starts(),
+            &[0, MIN_VOLUME_SIZE, 2 * MIN_VOLUME_SIZE - 8]
+        );
+
+        // Overwriting in place across a boundary.
+        writer.seek(SeekFrom::Start(MIN_VOLUME_SIZE - 2)).unwrap();
+        writer.write_all(&[5; 4]).unwrap();
+        writer.seek(SeekFrom::End(0)).unwrap();
+
+        let volumes: Vec<Vec<u8>> = writer
+            .into_volumes()
+            .into_iter()
+            .map(Cursor::into_inner)
+            .collect();
+        assert_eq!(volumes[0].len(), MIN_VOLUME_SIZE as usize);
+        assert_eq!(&volumes[0][..4], b"PK\x07\x08");
+        assert_eq!(&volumes[0][MIN_VOLUME_SIZE as usize - 2..], &[5, 5]);
+        assert_eq!(&volumes[1][..2], &[5, 5]);
+        assert_eq!(&volumes[1][2..6], &[2; 4]);
+        assert_eq!(volumes[1].len(), MIN_VOLUME_SIZE as usize - 8);
+        assert_eq!(volumes[2], [4; 16]);
+    }
+}
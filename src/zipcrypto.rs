@@ -20,6 +20,7 @@ pub(crate) struct ZipCryptoKeys {
 
 impl Debug for ZipCryptoKeys {
     #[allow(unreachable_code)]
+    #[allow(clippy::needless_return)] // the early return is only unconditional in one cfg
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         #[cfg(not(any(test, fuzzing)))]
         {
@@ -161,15 +162,36 @@ pub(crate) struct ZipCryptoWriter<W> {
     pub(crate) writer: W,
     pub(crate) buffer: Vec<u8>,
     pub(crate) keys: ZipCryptoKeys,
+    /// Once the header has been written, data is encrypted as it arrives instead of being buffered
+    /// until the CRC is known.
+    pub(crate) header_written: bool,
 }
 impl<W: std::io::Write> ZipCryptoWriter<W> {
-    #[allow(unused)]
-    pub(crate) fn finish(mut self, crc32: u32) -> std::io::Result<W> {
-        self.buffer[11] = (crc32 >> 24) as u8;
+    /// Write out the buffered 12-byte encryption header using `check_byte` for password
+    /// verification. This is used for entries with a data descriptor, which take the check byte
+    /// from the modification time rather than the CRC.
+    pub(crate) fn write_header(&mut self, check_byte: u8) -> std::io::Result<()> {
+        self.buffer[11] = check_byte;
+        self.encrypt_buffer()?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    fn encrypt_buffer(&mut self) -> std::io::Result<()> {
         for byte in self.buffer.iter_mut() {
             *byte = self.keys.encrypt_byte(*byte);
         }
         self.writer.write_all(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    #[allow(unused)]
+    pub(crate) fn finish(mut self, crc32: u32) -> std::io::Result<W> {
+        if !self.header_written {
+            self.buffer[11] = (crc32 >> 24) as u8;
+        }
+        self.encrypt_buffer()?;
         self.writer.flush()?;
         Ok(self.writer)
     }
@@ -177,6 +199,9 @@ impl<W: std::io::Write> ZipCryptoWriter<W> {
 impl<W: std::io::Write> std::io::Write for ZipCryptoWriter<W> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         self.buffer.extend_from_slice(buf);
+        if self.header_written {
+            self.encrypt_buffer()?;
+        }
         Ok(buf.len())
     }
     fn flush(&mut self) -> std::io::Result<()> {
@@ -103,6 +103,85 @@ fn append() {
     }
 }
 
+// This test asserts that an archive written to a non-seekable output, where each entry is followed
+// by a data descriptor, reads back the same as one written with seeking.
+#[test]
+fn stream_end_to_end() {
+    for &method in SUPPORTED_COMPRESSION_METHODS {
+        if method == CompressionMethod::DEFLATE64 {
+            continue;
+        }
+        println!("Streaming file with {method} compression");
+        let file = &mut Cursor::new(write_test_archive_to_stream(method));
+
+        println!("Checking file contents");
+        check_archive_file(file, ENTRY_NAME, Some(method), LOREM_IPSUM);
+        check_archive_file(file, INTERNAL_COPY_ENTRY_NAME, Some(method), LOREM_IPSUM);
+    }
+}
+
+// This test asserts that an archive written to a non-seekable output can also be read back from a
+// non-seekable input, by decoding each entry up to its data descriptor.
+#[test]
+fn stream_read_end_to_end() {
+    for &method in SUPPORTED_COMPRESSION_METHODS {
+        if method == CompressionMethod::DEFLATE64 {
+            continue;
+        }
+        println!("Reading streamed file with {method} compression");
+        let bytes = write_test_archive_to_stream(method);
+        let mut reader = &bytes[..];
+        let mut contents = Vec::new();
+        while let Some(mut file) = zip::read::read_zipfile_from_stream(&mut reader).unwrap() {
+            let mut data = Vec::new();
+            file.read_to_end(&mut data).unwrap();
+            contents.push((file.name().to_owned(), data));
+        }
+        let names: Vec<_> = contents.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "test/",
+                ENTRY_NAME,
+                "test/☃.txt",
+                "test_with_extra_data/🐢.txt"
+            ]
+        );
+        assert_eq!(contents[1].1, LOREM_IPSUM);
+        assert_eq!(contents[2].1, b"Hello, World!\n");
+    }
+}
+
+// Write a test zip archive to a `Vec`, which doesn't implement `Seek`.
+fn write_test_archive_to_stream(method: CompressionMethod) -> Vec<u8> {
+    let mut zip = ZipWriter::new_stream(Vec::new());
+
+    zip.add_directory("test/", SimpleFileOptions::default())
+        .unwrap();
+
+    let mut options = FileOptions::<ExtendedFileOptions>::default()
+        .compression_method(method)
+        .unix_permissions(0o755);
+
+    zip.start_file(ENTRY_NAME, options.clone()).unwrap();
+    zip.write_all(LOREM_IPSUM).unwrap();
+    zip.shallow_copy_file(ENTRY_NAME, INTERNAL_COPY_ENTRY_NAME)
+        .unwrap();
+
+    zip.start_file("test/☃.txt", options.clone()).unwrap();
+    zip.write_all(b"Hello, World!\n").unwrap();
+
+    options
+        .add_extra_data(0xbeef, EXTRA_DATA.to_owned().into_boxed_slice(), false)
+        .unwrap();
+
+    zip.start_file("test_with_extra_data/🐢.txt", options)
+        .unwrap();
+    zip.write_all(b"Hello, World! Again.\n").unwrap();
+
+    zip.finish().unwrap().into_inner()
+}
+
 // Write a test zip archive to buffer.
 fn write_test_archive(file: &mut Cursor<Vec<u8>>, method: CompressionMethod, shallow_copy: bool) {
     let mut zip = ZipWriter::new(file);
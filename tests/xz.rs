@@ -1,6 +1,8 @@
 #![cfg(feature = "xz")]
 
 use std::io::{self, Read};
+use zip::read::{Config, LazyZipArchive, Limits, ZipSliceArchive};
+use zip::result::{ZipError, ZipResult};
 use zip::ZipArchive;
 
 #[test]
@@ -17,3 +19,51 @@ fn decompress_xz() -> io::Result<()> {
     assert_eq!("Hello world\n", String::from_utf8(content).unwrap());
     Ok(())
 }
+
+#[test]
+fn decompress_xz_within_limits() -> ZipResult<()> {
+    let bytes = include_bytes!("data/xz.zip");
+    let read = |bytes: &[u8], limits: Limits| -> ZipResult<Vec<u8>> {
+        let mut archive =
+            ZipArchive::with_limits(Config::default(), limits, io::Cursor::new(bytes))?;
+        let mut content = Vec::new();
+        archive.by_name("hello.txt")?.read_to_end(&mut content)?;
+        Ok(content)
+    };
+    assert_eq!(
+        read(bytes, Limits::default().with_max_total_size(12))?,
+        b"Hello world\n"
+    );
+    assert!(matches!(
+        read(bytes, Limits::default().with_max_total_size(11)),
+        Err(ZipError::LimitExceeded(_))
+    ));
+
+    // An entry that decompresses to more than its recorded size is stopped there.
+    let mut understated = bytes.to_vec();
+    let central = understated
+        .windows(4)
+        .position(|window| window == b"PK\x01\x02")
+        .unwrap();
+    understated[central + 24..central + 28].copy_from_slice(&5u32.to_le_bytes());
+    assert!(matches!(
+        read(&understated, Limits::default()),
+        Err(ZipError::LimitExceeded(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn every_reader_refuses_xz_over_limit() -> ZipResult<()> {
+    let bytes = &include_bytes!("data/xz.zip")[..];
+    let limits = Limits::default().with_max_total_size(11);
+    let refused = |result: ZipResult<()>| matches!(result, Err(ZipError::LimitExceeded(_)));
+
+    let mut archive = ZipArchive::with_limits(Config::default(), limits, io::Cursor::new(bytes))?;
+    assert!(refused(archive.by_index_seek(0).map(drop)));
+    let mut lazy = LazyZipArchive::with_limits(Config::default(), limits, io::Cursor::new(bytes))?;
+    assert!(refused(lazy.by_index(0).map(drop)));
+    let slice = ZipSliceArchive::with_limits(Config::default(), limits, bytes)?;
+    assert!(refused(slice.reader_by_index(0).map(drop)));
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::io::{Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+const CONTENT: &[u8] = b"small entry written with the ZIP64 option";
+
+/// Reads the sizes from the ZIP64 extra field that directly follows the file name of a header.
+fn zip64_extra_field(header: &[u8], name_len_offset: usize, fixed_len: usize) -> (u64, u64) {
+    let name_len = u16::from_le_bytes([header[name_len_offset], header[name_len_offset + 1]]);
+    let extra = &header[fixed_len + name_len as usize..];
+    assert_eq!(u16::from_le_bytes([extra[0], extra[1]]), 0x0001);
+    let uncompressed = u64::from_le_bytes(extra[4..12].try_into().unwrap());
+    let compressed = u64::from_le_bytes(extra[12..20].try_into().unwrap());
+    (uncompressed, compressed)
+}
+
+#[test]
+fn zip64_extra_field_records_real_sizes() {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .large_file(true);
+    writer.start_file("large.txt", options).unwrap();
+    writer.write_all(CONTENT).unwrap();
+    let bytes = writer.finish().unwrap().into_inner();
+    let expected = (CONTENT.len() as u64, CONTENT.len() as u64);
+
+    // Local file header: 30 fixed bytes, name length at offset 26.
+    assert_eq!(&bytes[..4], b"PK\x03\x04");
+    assert_eq!(zip64_extra_field(&bytes, 26, 30), expected);
+
+    // Central directory header: 46 fixed bytes, name length at offset 28.
+    let central = bytes
+        .windows(4)
+        .position(|window| window == b"PK\x01\x02")
+        .unwrap();
+    assert_eq!(zip64_extra_field(&bytes[central..], 28, 46), expected);
+
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut file = archive.by_index(0).unwrap();
+    assert_eq!(file.size(), CONTENT.len() as u64);
+    assert_eq!(file.compressed_size(), CONTENT.len() as u64);
+    let mut read_back = Vec::new();
+    file.read_to_end(&mut read_back).unwrap();
+    assert_eq!(read_back, CONTENT);
+}